@@ -0,0 +1,48 @@
+//! Verify every author's signature on a CIP-100 document, without touching the network
+//!
+//! Run with `cargo run --example verify_offline [path]`; checks the CIP-100 example.json test vector
+//! if no path is given
+
+use anyhow::Context;
+use cardano_governance_metadata::{parse_str, Document};
+use url::Url;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let path = std::env::args().nth(1).unwrap_or_else(|| {
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/example.json").to_string()
+    });
+    let path = std::fs::canonicalize(path)?;
+    let content = std::fs::read_to_string(&path)?;
+    let base = Url::from_file_path(&path)
+        .ok()
+        .context("document path isn't absolute")?;
+
+    // Only the contexts bundled with the crate are used, so nothing is fetched
+    let document: Document = parse_str(&content, base).await?;
+    println!("{document}");
+    println!(
+        "{} hash: {}",
+        document.hash_algorithm.as_str(),
+        hex::encode(document.hash_with(document.hash_algorithm.clone())?)
+    );
+
+    let mut valid = true;
+    for (author, result) in document.verify_all()? {
+        match result {
+            Ok(true) => println!("{author}: valid signature"),
+            Ok(false) => {
+                valid = false;
+                println!("{author}: INVALID signature");
+            }
+            Err(err) => {
+                valid = false;
+                println!("{author}: {err}");
+            }
+        }
+    }
+    if !valid {
+        anyhow::bail!("not every signature is valid");
+    }
+    Ok(())
+}
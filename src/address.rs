@@ -0,0 +1,249 @@
+//! Keeping the client away from internal networks, for services that fetch anchor urls submitted by users
+
+use std::{
+    collections::HashSet,
+    error::Error,
+    fmt,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    sync::Arc,
+};
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use url::{Host, Url};
+
+use crate::error::{MetadataError, Result};
+
+/// Which addresses a [crate::MetadataClient] may connect to; see [crate::MetadataClient::with_address_policy]
+///
+/// Hosts are resolved, and every address checked, as each connection is made, including connections to hosts
+/// redirected to, so a host can't pass a check up front and then resolve to an internal address. Unless a host
+/// is allowed with [AddressPolicy::allow_host], connecting to any of these ranges is refused:
+/// - loopback: 127.0.0.0/8 and ::1
+/// - private networks: 10.0.0.0/8, 172.16.0.0/12, 192.168.0.0/16 and fc00::/7
+/// - shared address space (carrier-grade NAT): 100.64.0.0/10
+/// - link local, which includes cloud metadata services such as 169.254.169.254: 169.254.0.0/16 and fe80::/10
+/// - "this network" and unspecified: 0.0.0.0/8 and ::
+/// - IETF protocol assignments and benchmarking: 192.0.0.0/24 and 198.18.0.0/15
+/// - multicast, reserved and broadcast: 224.0.0.0/4, 240.0.0.0/4 and ff00::/8
+/// - any of the IPv4 ranges above, mapped into IPv6 (::ffff:0:0/96) or translated by NAT64 (64:ff9b::/96)
+#[derive(Debug, Clone, Default)]
+pub struct AddressPolicy {
+    allowed_hosts: HashSet<String>,
+}
+
+impl AddressPolicy {
+    /// A policy that denies every range listed above, and allows everything else
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow connecting to the given host, such as an IPFS gateway on an internal network, whatever addresses
+    /// it resolves to; an IP address may be given to allow connecting to it directly
+    pub fn allow_host(mut self, host: impl Into<String>) -> Self {
+        self.allowed_hosts.insert(normalize_host(&host.into()));
+        self
+    }
+
+    /// Whether the client may connect to the given address, which the given host resolved to
+    pub fn allows(&self, host: &str, address: IpAddr) -> bool {
+        !is_denied(address) || self.allowed_hosts.contains(&normalize_host(host))
+    }
+
+    /// Check a url whose host is an IP address, which is connected to without being resolved, so never
+    /// reaches the resolver; urls with a domain are checked once it's resolved
+    pub(crate) fn check_url(&self, url: &Url) -> Result<()> {
+        let address = match url.host() {
+            Some(Host::Ipv4(ip)) => IpAddr::V4(ip),
+            Some(Host::Ipv6(ip)) => IpAddr::V6(ip),
+            _ => return Ok(()),
+        };
+        let host = address.to_string();
+        if self.allows(&host, address) {
+            Ok(())
+        } else {
+            Err(MetadataError::DeniedAddress { host, address })
+        }
+    }
+
+    /// A reqwest resolver that refuses to resolve hosts to addresses this policy denies
+    pub(crate) fn resolver(self: Arc<Self>) -> Arc<PolicyResolver> {
+        Arc::new(PolicyResolver(self))
+    }
+}
+
+/// Hosts are compared without case, brackets around IPv6 addresses, or a trailing dot
+fn normalize_host(host: &str) -> String {
+    host.trim_start_matches('[')
+        .trim_end_matches(']')
+        .trim_end_matches('.')
+        .to_ascii_lowercase()
+}
+
+/// Resolves hosts with the system resolver, failing if any of a host's addresses are denied by the policy
+pub(crate) struct PolicyResolver(Arc<AddressPolicy>);
+
+impl Resolve for PolicyResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let policy = self.0.clone();
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addresses: Vec<SocketAddr> =
+                tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            // A host with any internal address is refused outright, rather than connected to on its others
+            if let Some(denied) = addresses.iter().find(|a| !policy.allows(&host, a.ip())) {
+                return Err(Box::new(Denied {
+                    host,
+                    address: denied.ip(),
+                }) as Box<dyn Error + Send + Sync>);
+            }
+            Ok(Box::new(addresses.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// The error the resolver fails with, which reqwest wraps in a connection error
+#[derive(Debug)]
+struct Denied {
+    host: String,
+    address: IpAddr,
+}
+
+impl fmt::Display for Denied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} resolves to the denied address {}",
+            self.host, self.address
+        )
+    }
+}
+
+impl Error for Denied {}
+
+/// The error to report for a failed request, if it failed because the resolver denied the host's address
+pub(crate) fn denied_address(err: &reqwest::Error) -> Option<MetadataError> {
+    let mut source = err.source();
+    while let Some(err) = source {
+        if let Some(denied) = err.downcast_ref::<Denied>() {
+            return Some(MetadataError::DeniedAddress {
+                host: denied.host.clone(),
+                address: denied.address,
+            });
+        }
+        source = err.source();
+    }
+    None
+}
+
+/// Whether the address is in one of the ranges denied by default
+pub(crate) fn is_denied(address: IpAddr) -> bool {
+    match address {
+        IpAddr::V4(ip) => is_denied_ipv4(&ip),
+        IpAddr::V6(ip) => is_denied_ipv6(&ip),
+    }
+}
+
+fn is_denied_ipv4(ip: &Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    a == 0
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        // shared address space, 100.64.0.0/10
+        || (a == 100 && b & 0xc0 == 64)
+        // IETF protocol assignments, 192.0.0.0/24
+        || (a == 192 && b == 0 && c == 0)
+        // benchmarking, 198.18.0.0/15
+        || (a == 198 && b & 0xfe == 18)
+        // multicast, reserved and broadcast, 224.0.0.0/3
+        || a >= 224
+}
+
+fn is_denied_ipv6(ip: &Ipv6Addr) -> bool {
+    let segments = ip.segments();
+    let first = segments[0];
+    ip.is_loopback()
+        || ip.is_unspecified()
+        // unique local, fc00::/7
+        || first & 0xfe00 == 0xfc00
+        // link local, fe80::/10
+        || first & 0xffc0 == 0xfe80
+        // multicast, ff00::/8
+        || first & 0xff00 == 0xff00
+        || ip.to_ipv4_mapped().is_some_and(|ip| is_denied_ipv4(&ip))
+        // NAT64, 64:ff9b::/96
+        || (segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0]
+            && is_denied_ipv4(&Ipv4Addr::new(
+                (segments[6] >> 8) as u8,
+                segments[6] as u8,
+                (segments[7] >> 8) as u8,
+                segments[7] as u8,
+            )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_denied_ranges() {
+        for address in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.31.255.255",
+            "192.168.0.1",
+            "100.64.0.1",
+            "169.254.169.254",
+            "0.0.0.0",
+            "0.1.2.3",
+            "192.0.0.1",
+            "198.19.0.1",
+            "224.0.0.1",
+            "240.0.0.1",
+            "255.255.255.255",
+            "::",
+            "::1",
+            "fd00:ec2::254",
+            "fe80::1",
+            "ff02::1",
+            "::ffff:10.0.0.1",
+            "64:ff9b::a9fe:a9fe",
+        ] {
+            assert!(is_denied(address.parse().unwrap()), "{address}");
+        }
+        for address in [
+            "8.8.8.8",
+            "100.128.0.1",
+            "172.32.0.1",
+            "198.20.0.1",
+            "2001:db8::1",
+            "::ffff:8.8.8.8",
+            "64:ff9b::808:808",
+        ] {
+            assert!(!is_denied(address.parse().unwrap()), "{address}");
+        }
+    }
+
+    #[test]
+    fn test_allowed_hosts() {
+        let policy = AddressPolicy::new()
+            .allow_host("Mirror.Internal.")
+            .allow_host("[::1]");
+        let loopback = "127.0.0.1".parse().unwrap();
+        assert!(policy.allows("mirror.internal", loopback));
+        assert!(!policy.allows("example.com", loopback));
+        assert!(policy.allows("example.com", "8.8.8.8".parse().unwrap()));
+
+        assert!(policy
+            .check_url(&Url::parse("http://[::1]:8080/doc.json").unwrap())
+            .is_ok());
+        assert!(matches!(
+            policy.check_url(&Url::parse("http://169.254.169.254/latest/meta-data").unwrap()),
+            Err(MetadataError::DeniedAddress { .. })
+        ));
+        // Domains are only checked once they're resolved
+        assert!(policy
+            .check_url(&Url::parse("http://localhost/").unwrap())
+            .is_ok());
+    }
+}
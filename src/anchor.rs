@@ -0,0 +1,95 @@
+//! On-chain anchors, the url and hash a governance action, DRep registration or vote points at its metadata with
+//!
+//! [Anchor] has the same two parts as the anchor types of cardano-serialization-lib, pallas and the like, so it
+//! converts to and from any of them through its url and hash bytes, without depending on a particular version
+
+use blake2::{digest::consts::U32, Blake2b, Digest};
+use url::Url;
+
+use crate::cip100::{decode_hex, Document};
+use crate::error::Result;
+
+/// The url of a metadata document, and the blake2b-256 hash of its exact bytes, as recorded on chain
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Anchor {
+    /// Where the document is published
+    pub url: Url,
+    /// The blake2b-256 hash of the bytes served at the url
+    pub data_hash: [u8; 32],
+}
+
+impl Anchor {
+    pub fn new(url: Url, data_hash: [u8; 32]) -> Self {
+        Self { url, data_hash }
+    }
+
+    /// An anchor with the hash given in hex, as it's usually displayed by explorers and chain indexers
+    pub fn from_hex(url: Url, data_hash: &str) -> Result<Self> {
+        Ok(Self::new(url, decode_hex(data_hash, "anchor hash")?))
+    }
+
+    /// The anchor for the given bytes, published at the given url
+    pub fn for_bytes(url: Url, content: &[u8]) -> Self {
+        Self::new(url, Blake2b::<U32>::digest(content).into())
+    }
+
+    /// The anchor for the document, published at the given url exactly as [Document::to_jsonld] writes it
+    ///
+    /// The hash covers the whole file, not just the signed body, so a document published any other way, even
+    /// with different whitespace, needs [Anchor::for_bytes] with the bytes actually published
+    pub fn for_document(document: &Document, url: Url) -> Result<Self> {
+        Ok(Self::for_bytes(url, document.to_jsonld()?.as_bytes()))
+    }
+
+    /// The hash in lowercase hex
+    pub fn data_hash_hex(&self) -> String {
+        hex::encode(self.data_hash)
+    }
+}
+
+impl From<(Url, [u8; 32])> for Anchor {
+    fn from((url, data_hash): (Url, [u8; 32])) -> Self {
+        Self::new(url, data_hash)
+    }
+}
+
+impl From<Anchor> for (Url, [u8; 32]) {
+    fn from(anchor: Anchor) -> Self {
+        (anchor.url, anchor.data_hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::MetadataError;
+
+    #[test]
+    fn test_anchor() -> anyhow::Result<()> {
+        let url = Url::parse("https://example.com/metadata.jsonld")?;
+        let content = include_bytes!("../tests/fixtures/example.json");
+        let anchor = Anchor::for_bytes(url.clone(), content);
+        assert_eq!(
+            Anchor::from_hex(url.clone(), &anchor.data_hash_hex())?,
+            anchor
+        );
+
+        let (round_tripped_url, hash) = anchor.clone().into();
+        assert_eq!(Anchor::from((round_tripped_url, hash)), anchor);
+
+        let document = Document::builder().comment("Anchored").build();
+        let for_document = Anchor::for_document(&document, url.clone())?;
+        assert_eq!(
+            for_document,
+            Anchor::for_bytes(url.clone(), document.to_jsonld()?.as_bytes())
+        );
+        assert_ne!(for_document.data_hash, anchor.data_hash);
+
+        assert!(matches!(
+            Anchor::from_hex(url, "abcd"),
+            Err(MetadataError::InvalidValue { .. })
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,208 @@
+use iref::Iri;
+use json_ld::Node;
+
+use crate::cip100::{Cip100BaseRef, Document, CIP100_FIELDS};
+use crate::cip108::{GovernanceActionDocument, CIP108_FIELDS};
+use crate::cip119::{DRepDocument, CIP119_FIELDS};
+use crate::cip136::{VoteRationaleDocument, CIP136_FIELDS};
+use crate::error::{MetadataError, Result};
+
+/// A governance metadata document of whichever supported CIP it follows, for when that isn't known in advance
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AnyDocument {
+    /// A plain CIP-100 document
+    Cip100(Document),
+    /// A CIP-108 governance action document
+    Cip108(GovernanceActionDocument),
+    /// A CIP-119 DRep document
+    Cip119(DRepDocument),
+    /// A CIP-136 constitutional committee vote rationale document
+    Cip136(VoteRationaleDocument),
+}
+
+impl AnyDocument {
+    /// The number of the CIP the document follows
+    pub fn cip(&self) -> u16 {
+        match self {
+            AnyDocument::Cip100(_) => 100,
+            AnyDocument::Cip108(_) => 108,
+            AnyDocument::Cip119(_) => 119,
+            AnyDocument::Cip136(_) => 136,
+        }
+    }
+
+    /// See [Document::base](crate::Document::base)
+    pub fn base(&self) -> Cip100BaseRef<'_> {
+        match self {
+            AnyDocument::Cip100(document) => document.base(),
+            AnyDocument::Cip108(document) => document.base(),
+            AnyDocument::Cip119(document) => document.base(),
+            AnyDocument::Cip136(document) => document.base(),
+        }
+    }
+
+    /// The IRI the document's body is kept under, which is what its authors sign
+    pub fn body_iri(&self) -> &'static str {
+        match self {
+            AnyDocument::Cip100(_) => CIP100_FIELDS.body,
+            AnyDocument::Cip108(_) => CIP108_FIELDS.body,
+            AnyDocument::Cip119(_) => CIP119_FIELDS.body,
+            AnyDocument::Cip136(_) => CIP136_FIELDS.body,
+        }
+    }
+
+    /// Check every author's witness against the body of the expanded node the document was parsed from,
+    /// whichever CIP it follows; see [Cip100BaseRef::verify_all]
+    pub fn verify_all(&self, object: &Node) -> Result<Vec<(String, Result<bool>)>> {
+        self.base().verify_all(object, self.body_iri())
+    }
+}
+
+/// The CIP is chosen by which CIP's body field the document has, since each extension of CIP-100 uses
+/// its own IRI for the body; a document with none of them, or several, is parsed as plain CIP-100
+impl TryFrom<&Node> for AnyDocument {
+    type Error = MetadataError;
+
+    fn try_from(object: &Node) -> Result<Self, Self::Error> {
+        let mut bodies = vec![];
+        for (cip, body) in [
+            (108, CIP108_FIELDS.body),
+            (119, CIP119_FIELDS.body),
+            (136, CIP136_FIELDS.body),
+        ] {
+            if object.get_any(&Iri::new(body)?).is_some() {
+                bodies.push(cip);
+            }
+        }
+        match bodies.as_slice() {
+            [108] => Ok(AnyDocument::Cip108(object.try_into()?)),
+            [119] => Ok(AnyDocument::Cip119(object.try_into()?)),
+            [136] => Ok(AnyDocument::Cip136(object.try_into()?)),
+            _ => Ok(AnyDocument::Cip100(object.try_into()?)),
+        }
+    }
+}
+
+impl From<Document> for AnyDocument {
+    fn from(document: Document) -> Self {
+        AnyDocument::Cip100(document)
+    }
+}
+
+impl From<GovernanceActionDocument> for AnyDocument {
+    fn from(document: GovernanceActionDocument) -> Self {
+        AnyDocument::Cip108(document)
+    }
+}
+
+impl From<DRepDocument> for AnyDocument {
+    fn from(document: DRepDocument) -> Self {
+        AnyDocument::Cip119(document)
+    }
+}
+
+impl From<VoteRationaleDocument> for AnyDocument {
+    fn from(document: VoteRationaleDocument) -> Self {
+        AnyDocument::Cip136(document)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cip100::{canonical_body_of, Witness};
+    use crate::test_server::EXAMPLE;
+    use anyhow::Result;
+    use blake2::{digest::consts::U32, Blake2b, Digest};
+    use ed25519_dalek::SigningKey;
+    use url::Url;
+
+    #[tokio::test]
+    async fn test_any_document() -> Result<()> {
+        let base = Url::parse("https://example.com/metadata.jsonld")?;
+        let fixtures = [
+            (EXAMPLE, 100),
+            (include_str!("../tests/fixtures/cip108-example.json"), 108),
+            (include_str!("../tests/fixtures/cip119-example.json"), 119),
+            (include_str!("../tests/fixtures/cip136-example.json"), 136),
+        ];
+        for (content, cip) in fixtures {
+            let document: AnyDocument = crate::parse_str(content, base.clone()).await?;
+            assert_eq!(document.cip(), cip);
+        }
+
+        let expected: GovernanceActionDocument =
+            crate::parse_str(fixtures[1].0, base.clone()).await?;
+        let document: AnyDocument = crate::parse_str(fixtures[1].0, base.clone()).await?;
+        assert_eq!(document.base(), expected.base());
+        assert_eq!(document, expected.into());
+
+        // A document with a CIP-108 body that isn't valid CIP-108 is an error, rather than quietly
+        // parsed as something else
+        let untitled = fixtures[1]
+            .0
+            .replace("\"title\": \"Withdraw", "\"untitled\": \"Withdraw");
+        let result = crate::parse_str::<AnyDocument>(&untitled, base).await;
+        assert!(matches!(
+            result,
+            Err(MetadataError::MissingField { ref field, .. }) if field == "body title"
+        ));
+        Ok(())
+    }
+
+    /// A document along with the expanded node it was parsed from
+    struct WithNode(AnyDocument, Node);
+
+    impl TryFrom<&Node> for WithNode {
+        type Error = MetadataError;
+
+        fn try_from(node: &Node) -> Result<Self, Self::Error> {
+            Ok(Self(node.try_into()?, node.clone()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_verify_all() -> Result<()> {
+        let base = Url::parse("https://example.com/metadata.jsonld")?;
+
+        // For CIP-100, the expanded body canonicalizes just as the parsed document does
+        let WithNode(document, node) = crate::parse_str(EXAMPLE, base.clone()).await?;
+        let AnyDocument::Cip100(parsed) = &document else {
+            panic!("example.json is CIP-100");
+        };
+        assert_eq!(
+            canonical_body_of(&node, CIP100_FIELDS.body)?,
+            parsed.canonical_body()?
+        );
+        let verified = document.verify_all(&node)?;
+        assert_eq!(verified.len(), 1);
+        assert!(matches!(verified[0], (ref name, Ok(true)) if name == "Pi Lanningham"));
+
+        // Any other CIP's body can be signed and checked, though this library doesn't model all of it
+        let mut content: serde_json::Value =
+            serde_json::from_str(include_str!("../tests/fixtures/cip119-example.json"))?;
+        let WithNode(document, node) = crate::parse_str(&content.to_string(), base.clone()).await?;
+        let hash = Blake2b::<U32>::digest(canonical_body_of(&node, document.body_iri())?);
+        let signing_key = SigningKey::from_bytes(&std::array::from_fn(|i| i as u8));
+        let witness = Witness::sign(&signing_key, &hash);
+        content["authors"] = serde_json::json!([{
+            "name": "Ada",
+            "witness": {
+                "witnessAlgorithm": witness.algorithm.as_str(),
+                "publicKey": witness.public_key,
+                "signature": witness.signature,
+            }
+        }]);
+        let WithNode(signed, node) = crate::parse_str(&content.to_string(), base.clone()).await?;
+        assert_eq!(signed.cip(), 119);
+        let verified = signed.verify_all(&node)?;
+        assert_eq!(verified.len(), 1);
+        assert!(matches!(verified[0], (ref name, Ok(true)) if name == "Ada"));
+
+        content["body"]["givenName"] = "Someone else".into();
+        let WithNode(tampered, node) = crate::parse_str(&content.to_string(), base).await?;
+        assert!(matches!(tampered.verify_all(&node)?[0].1, Ok(false)));
+        Ok(())
+    }
+}
@@ -0,0 +1,798 @@
+//! A synchronous client, for codebases that aren't async
+//!
+//! Documents are fetched with reqwest's blocking client, but are parsed by exactly the same code
+//! as the async [crate::MetadataClient], so both produce identical documents and errors
+//!
+//! As with reqwest's blocking client, this must not be used from within an async runtime
+
+use std::{future::Future, io::Read, path::Path, sync::Arc, time::Duration};
+
+use iref::IriBuf;
+use json_ld::{ExpandedDocument, Node};
+use url::Url;
+
+use crate::{
+    address,
+    cache::{ResponseCache, DEFAULT_CACHE_SIZE},
+    hash::HashCheck,
+    next_retry_delay, redirect,
+    scheme::SchemeHandlers,
+    AddressPolicy, MetadataError, ParseOptions, Result, StaticContextLoader,
+    DEFAULT_ARWEAVE_GATEWAY, DEFAULT_CONTENT_TYPES, DEFAULT_IPFS_GATEWAY, DEFAULT_MAX_ATTEMPTS,
+    DEFAULT_MAX_REDIRECTS, DEFAULT_MAX_SIZE, DEFAULT_RETRY_DELAY, DEFAULT_TIMEOUT,
+    DEFAULT_USER_AGENT,
+};
+
+/// A client for fetching governance metadata from the web, blocking the current thread
+///
+/// Clones share the connection pool and cache, so a configured client can be handed to other threads
+#[derive(Clone)]
+pub struct MetadataClient {
+    client: reqwest::blocking::Client,
+    timeout: Duration,
+    max_size: usize,
+    max_attempts: u32,
+    retry_delay: Duration,
+    max_redirects: usize,
+    private_redirects: bool,
+    address_policy: Option<Arc<AddressPolicy>>,
+    ipfs_gateway: Url,
+    arweave_gateway: Url,
+    schemes: SchemeHandlers,
+    headers: reqwest::header::HeaderMap,
+    options: ParseOptions,
+    content_types: Option<Vec<String>>,
+    cache: Option<Arc<ResponseCache>>,
+    contexts: StaticContextLoader,
+}
+
+impl Default for MetadataClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetadataClient {
+    pub fn new() -> MetadataClient {
+        let client = reqwest::blocking::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .user_agent(DEFAULT_USER_AGENT)
+            .build()
+            .expect("the default reqwest client can be built");
+        Self::with_client(client)
+    }
+
+    /// Create a metadata client that fetches documents with the given reqwest client,
+    /// for example one configured with a proxy or user agent
+    ///
+    /// Build it with `redirect(reqwest::redirect::Policy::none())` to have redirects checked as described on
+    /// [MetadataClient::with_max_redirects]; otherwise the client follows redirects by its own policy
+    pub fn with_client(client: reqwest::blocking::Client) -> MetadataClient {
+        MetadataClient {
+            client,
+            timeout: DEFAULT_TIMEOUT,
+            max_size: DEFAULT_MAX_SIZE,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            retry_delay: DEFAULT_RETRY_DELAY,
+            max_redirects: DEFAULT_MAX_REDIRECTS,
+            private_redirects: false,
+            address_policy: None,
+            ipfs_gateway: Url::parse(DEFAULT_IPFS_GATEWAY)
+                .expect("the default gateway is a valid url"),
+            arweave_gateway: Url::parse(DEFAULT_ARWEAVE_GATEWAY)
+                .expect("the default gateway is a valid url"),
+            schemes: SchemeHandlers::default(),
+            headers: reqwest::header::HeaderMap::new(),
+            options: ParseOptions::default(),
+            content_types: None,
+            cache: None,
+            contexts: StaticContextLoader::new(),
+        }
+    }
+
+    /// Resolve references to the given context IRI with the given JSON content, in addition to the
+    /// CIP-100, CIP-108, CIP-119 and CIP-136 contexts bundled with this crate; contexts are never fetched
+    pub fn with_context(mut self, iri: IriBuf, content: &str) -> Result<Self> {
+        self.contexts = self.contexts.with_context(iri, content)?;
+        Ok(self)
+    }
+
+    /// Refuse to download documents larger than the given number of bytes; defaults to 512KiB
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Give up on fetching a document if it takes longer than the given duration; defaults to 30 seconds
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Try fetching a document up to max_attempts times, waiting base_delay before the first retry and
+    /// doubling the wait each time after, up to a minute; only network errors, timeouts, and 5xx or 429
+    /// responses are retried
+    pub fn with_retries(mut self, max_attempts: u32, base_delay: Duration) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self.retry_delay = base_delay;
+        self
+    }
+
+    /// Fetch ipfs:// urls through the given HTTP gateway; defaults to https://ipfs.io/ipfs/
+    ///
+    /// Only the fetch goes through the gateway; the document is still expanded with its ipfs:// url as the base IRI
+    pub fn with_ipfs_gateway(mut self, gateway: Url) -> Self {
+        self.ipfs_gateway = crate::with_trailing_slash(gateway);
+        self
+    }
+
+    /// Fetch ar:// urls through the given Arweave gateway; defaults to https://arweave.net/
+    ///
+    /// As with IPFS, the document is still expanded with its ar:// url as the base IRI
+    pub fn with_arweave_gateway(mut self, gateway: Url) -> Self {
+        self.arweave_gateway = crate::with_trailing_slash(gateway);
+        self
+    }
+
+    /// Load urls of the given scheme, such as a storage network or an internal document store, with the given
+    /// handler, which resolves each to an http url to fetch or to the document itself; see [crate::Resolved]
+    ///
+    /// A handler for ipfs or ar takes the place of the gateway, and the scheme is also allowed in reference and
+    /// update uris, unless [MetadataClient::without_uri_validation] already allows every scheme
+    pub fn register_scheme<F>(mut self, scheme: &str, handler: F) -> Self
+    where
+        F: Fn(&Url) -> Result<crate::Resolved> + Send + Sync + 'static,
+    {
+        self.schemes.insert(scheme, Arc::new(handler));
+        self.options.allow_scheme(scheme);
+        self
+    }
+
+    /// Send the given User-Agent with every request, rather than this crate's name and version, such as to
+    /// identify your service to the gateways and archives that ask for it
+    ///
+    /// This also replaces the User-Agent of a reqwest client given to [MetadataClient::with_client]
+    pub fn with_user_agent(self, user_agent: &str) -> Result<Self> {
+        self.with_header(reqwest::header::USER_AGENT.as_str(), user_agent)
+    }
+
+    /// Send the given header with every request, such as an API key for an IPFS gateway, replacing any
+    /// value set for it before
+    ///
+    /// Headers are only sent to the origin of the url being loaded; if it redirects elsewhere, only the
+    /// User-Agent is sent on, so a key meant for one host isn't handed to another
+    pub fn with_header(mut self, name: &str, value: &str) -> Result<Self> {
+        let (name, value) = crate::header(name, value)?;
+        self.headers.insert(name, value);
+        Ok(self)
+    }
+
+    /// Follow at most the given number of redirects when fetching a document; defaults to 5
+    ///
+    /// Redirects are only followed to http and https urls, and not to localhost or private network
+    /// addresses, so a malicious host can't point the client at a local file or internal service
+    pub fn with_max_redirects(mut self, max_redirects: usize) -> Self {
+        self.max_redirects = max_redirects;
+        self
+    }
+
+    /// Follow redirects to localhost and private network addresses, such as in tests against a local server
+    pub fn with_private_redirects(mut self) -> Self {
+        self.private_redirects = true;
+        self
+    }
+
+    /// Refuse to connect to the addresses the policy denies, such as loopback, private network and cloud
+    /// metadata addresses; see [AddressPolicy] for the ranges denied by default
+    ///
+    /// Hosts are checked each time they're resolved to connect, including after redirects, so this holds even
+    /// for hosts that change what they resolve to. This replaces the reqwest client, including one given to
+    /// [MetadataClient::with_client], with one that resolves hosts through the policy; to keep settings such
+    /// as TLS roots, use [MetadataClient::with_address_policy_and_builder] instead
+    ///
+    /// Proxies, including any set by the HTTP_PROXY and HTTPS_PROXY environment variables, are never used,
+    /// since a proxy resolves the hosts it connects to itself, out of reach of the policy
+    pub fn with_address_policy(self, policy: AddressPolicy) -> Self {
+        self.with_address_policy_and_builder(
+            policy,
+            reqwest::blocking::Client::builder().user_agent(DEFAULT_USER_AGENT),
+        )
+        .expect("the default reqwest client can be built")
+    }
+
+    /// Refuse to connect to the addresses the policy denies, as [MetadataClient::with_address_policy] does,
+    /// fetching documents with a client built from the given builder
+    ///
+    /// The builder's settings are kept, except that redirects are left to this client to check, hosts are
+    /// resolved through the policy, and proxies are turned off, for the same reason as above; fails if the
+    /// builder can't build a client
+    pub fn with_address_policy_and_builder(
+        mut self,
+        policy: AddressPolicy,
+        builder: reqwest::blocking::ClientBuilder,
+    ) -> Result<Self> {
+        let policy = Arc::new(policy);
+        self.client = builder
+            .redirect(reqwest::redirect::Policy::none())
+            .no_proxy()
+            .dns_resolver(policy.clone().resolver())
+            .build()?;
+        self.address_policy = Some(policy);
+        Ok(self)
+    }
+
+    /// Only accept documents whose reference and update uris use one of the given schemes; defaults to
+    /// https, http, ipfs and ar
+    pub fn with_uri_schemes<S: Into<String>>(
+        mut self,
+        schemes: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.options.uri_schemes = Some(schemes.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Accept reference and update uris with any scheme, for lenient parsing of unusual documents
+    pub fn without_uri_validation(mut self) -> Self {
+        self.options.uri_schemes = None;
+        self
+    }
+
+    /// Reject documents whose hashAlgorithm isn't one of [crate::HashAlgorithm::RECOGNIZED], rather than parsing them
+    /// with an unknown algorithm that fails only once a signature is checked
+    pub fn with_strict_hash_algorithm(mut self) -> Self {
+        self.options.strict_hash_algorithm = true;
+        self
+    }
+
+    /// Trim the text of documents, and strip control and zero-width characters from it, so that stray
+    /// characters don't break exact matching or display; text is kept exactly as written by default
+    ///
+    /// Signatures are over the text as written, so a normalized document that had any stray characters no
+    /// longer verifies; use [crate::LangString::normalized] on a document parsed as is to have both
+    pub fn with_text_normalization(mut self) -> Self {
+        self.options.normalize_text = true;
+        self
+    }
+
+    /// Reject documents served with a Content-Type other than application/json, application/ld+json or
+    /// text/plain, which usually means a broken link to an HTML error page
+    ///
+    /// Off by default, since many IPFS gateways mislabel content
+    pub fn with_content_type_validation(self) -> Self {
+        self.with_content_types(DEFAULT_CONTENT_TYPES.iter().copied())
+    }
+
+    /// Reject documents served with a Content-Type other than the given media types, ignoring any parameters
+    /// such as charset
+    pub fn with_content_types<S: Into<String>>(
+        mut self,
+        content_types: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.content_types = Some(content_types.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Remember the ETag and Last-Modified of each document fetched, and send them as If-None-Match and
+    /// If-Modified-Since when fetching it again, reusing the body already downloaded on a 304 Not Modified
+    ///
+    /// Only documents served with one of those headers are kept, in memory, up to 16MiB of them; the least
+    /// recently used are dropped to make room for others. Clones of the client share the one cache
+    pub fn with_cache(self) -> Self {
+        self.with_cache_size(DEFAULT_CACHE_SIZE)
+    }
+
+    /// Cache documents as [MetadataClient::with_cache] does, but hold at most the given number of bytes of them
+    pub fn with_cache_size(mut self, max_size: usize) -> Self {
+        self.cache = Some(Arc::new(ResponseCache::new(max_size)));
+        self
+    }
+
+    /// Load a document of type T from the given JSON-LD document
+    ///
+    /// Fails if the document contains more than one node that can be parsed as a T; use load_all for those
+    pub fn load<T>(&self, url: Url) -> Result<T>
+    where
+        T: for<'a> TryFrom<&'a Node>,
+        for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+    {
+        let content = crate::decode(self.fetch_bytes(url.clone())?)?;
+        block_on(crate::parse(
+            &content,
+            url,
+            &mut &self.contexts,
+            &self.options,
+        ))?
+    }
+
+    /// Load a document of whichever supported CIP it follows, for when that isn't known in advance
+    ///
+    /// The CIP is chosen by which body field the document has; see [crate::AnyDocument]
+    pub fn load_any(&self, url: Url) -> Result<crate::AnyDocument> {
+        self.load(url)
+    }
+
+    /// Load every node of type T from the given JSON-LD document, such as one with a top level @graph
+    ///
+    /// Nodes that can't be parsed as a T are skipped; if none can, the error from the first is returned
+    pub fn load_all<T>(&self, url: Url) -> Result<Vec<T>>
+    where
+        T: for<'a> TryFrom<&'a Node>,
+        for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+    {
+        let content = crate::decode(self.fetch_bytes(url.clone())?)?;
+        block_on(crate::parse_all(
+            &content,
+            url,
+            &mut &self.contexts,
+            &self.options,
+        ))?
+    }
+
+    /// How many top level objects the document at the given url expands to, without parsing any of them
+    ///
+    /// Anything other than 1 means [MetadataClient::load] will fail, so this is a cheap pre-flight check for
+    /// documents with a top level @graph, or with no @context to give their keys meaning
+    pub fn count_objects(&self, url: Url) -> Result<usize> {
+        let content = crate::decode(self.fetch_bytes(url.clone())?)?;
+        let expanded = block_on(crate::expand(&content, url, &mut &self.contexts))??;
+        Ok(expanded.len())
+    }
+
+    /// Load a document of type T from the given url, along with the expanded JSON-LD it was parsed from
+    ///
+    /// The expanded document holds every property in the document, including any that T doesn't model,
+    /// such as vendor extensions, so nothing is lost when re-publishing it
+    pub fn load_raw<T>(&self, url: Url) -> Result<(T, ExpandedDocument)>
+    where
+        T: for<'a> TryFrom<&'a Node>,
+        for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+    {
+        let content = crate::decode(self.fetch_bytes(url.clone())?)?;
+        block_on(crate::parse_raw(
+            &content,
+            url,
+            &mut &self.contexts,
+            &self.options,
+        ))?
+    }
+
+    /// Load a document of type T from the given url, along with any [crate::Warning]s about parts of it that
+    /// were guessed at or skipped, such as an unrecognized reference type
+    ///
+    /// Documents that don't match T's schema still fail to load, so imperfect documents can be ingested and
+    /// flagged for review, without accepting ones that are simply broken
+    pub fn load_with_warnings<T>(&self, url: Url) -> Result<(T, Vec<crate::Warning>)>
+    where
+        T: for<'a> TryFrom<&'a Node>,
+        for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+    {
+        let (document, expanded) = self.load_raw(url)?;
+        Ok((document, crate::warning::collect(&expanded)))
+    }
+
+    /// Load a document of type T from the given url, along with the contexts it declares at its top level, to
+    /// tell proper JSON-LD apart from plain JSON that happens to parse; see [crate::declared_contexts]
+    pub fn load_with_contexts<T>(&self, url: Url) -> Result<(T, Vec<crate::DeclaredContext>)>
+    where
+        T: for<'a> TryFrom<&'a Node>,
+        for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+    {
+        let content = crate::decode(self.fetch_bytes(url.clone())?)?;
+        let contexts = crate::declared_contexts(&content)?;
+        let document = block_on(crate::parse(
+            &content,
+            url,
+            &mut &self.contexts,
+            &self.options,
+        ))??;
+        Ok((document, contexts))
+    }
+
+    /// Load a document of type T from a JSON-LD file on disk
+    ///
+    /// The canonical file:// url of the file is used as the document IRI, so relative IRIs resolve against it
+    pub fn load_file<T>(&self, path: impl AsRef<Path>) -> Result<T>
+    where
+        T: for<'a> TryFrom<&'a Node>,
+        for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+    {
+        let path = std::fs::canonicalize(path)?;
+        let url = Url::from_file_path(&path).map_err(|_| MetadataError::InvalidIri {
+            field: "document path".to_string(),
+            iri: path.display().to_string(),
+        })?;
+        let content = crate::decode(std::fs::read(&path)?)?;
+        block_on(crate::parse(
+            &content,
+            url,
+            &mut &self.contexts,
+            &self.options,
+        ))?
+    }
+
+    /// Load a document of type T from JSON-LD content that has already been fetched
+    ///
+    /// The base url is used as the document IRI, so relative IRIs resolve against it
+    pub fn load_str<T>(&self, content: &str, base: Url) -> Result<T>
+    where
+        T: for<'a> TryFrom<&'a Node>,
+        for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+    {
+        block_on(crate::parse(
+            content,
+            base,
+            &mut &self.contexts,
+            &self.options,
+        ))?
+    }
+
+    /// Load a document of type T from JSON-LD bytes that have already been fetched, such as through your own transport
+    ///
+    /// The bytes must be UTF-8, optionally starting with a byte order mark; the base url is used as the document IRI
+    pub fn load_bytes<T>(&self, content: &[u8], base: Url) -> Result<T>
+    where
+        T: for<'a> TryFrom<&'a Node>,
+        for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+    {
+        self.load_str(&crate::decode(content.to_vec())?, base)
+    }
+
+    /// Load a document of type T from a reader, such as a download from an S3 or IPFS SDK
+    ///
+    /// At most the client's max size is read, as with fetched documents, so an endless stream fails with
+    /// [MetadataError::TooLarge] rather than exhausting memory; the base url is used as the document IRI
+    pub fn load_reader<T>(&self, reader: impl Read, base: Url) -> Result<T>
+    where
+        T: for<'a> TryFrom<&'a Node>,
+        for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+    {
+        let mut content = vec![];
+        reader
+            .take(self.max_size as u64 + 1)
+            .read_to_end(&mut content)?;
+        if content.len() > self.max_size {
+            return Err(MetadataError::TooLarge {
+                limit: self.max_size,
+            });
+        }
+        self.load_str(&crate::decode(content)?, base)
+    }
+
+    /// Load a document of type T from where the on-chain anchor points, checking it against the anchor's hash;
+    /// see [MetadataClient::load_verified]
+    #[cfg(feature = "anchor")]
+    pub fn load_anchor<T>(&self, anchor: &crate::Anchor) -> Result<T>
+    where
+        T: for<'a> TryFrom<&'a Node>,
+        for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+    {
+        self.load_verified(anchor.url.clone(), anchor.data_hash)
+    }
+
+    /// Load a document of type T from the given url, but only if the raw bytes served match the expected blake2b-256 hash
+    ///
+    /// This is the hash recorded alongside the url in an on-chain governance anchor, so this guards against the
+    /// document being edited after the anchor was published
+    pub fn load_verified<T>(&self, url: Url, expected_hash: [u8; 32]) -> Result<T>
+    where
+        T: for<'a> TryFrom<&'a Node>,
+        for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+    {
+        // The hash is computed as the document downloads, so it's never hashed from a second copy
+        let content = crate::decode(self.fetch_checked(url.clone(), Some(expected_hash))?)?;
+        block_on(crate::parse(
+            &content,
+            url,
+            &mut &self.contexts,
+            &self.options,
+        ))?
+    }
+
+    fn fetch_bytes(&self, url: Url) -> Result<Vec<u8>> {
+        self.fetch_checked(url, None)
+    }
+
+    /// Fetch the document, failing if it doesn't have the expected blake2b-256 hash, if one is given
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(url = %url))
+    )]
+    fn fetch_checked(&self, url: Url, expected_hash: Option<[u8; 32]>) -> Result<Vec<u8>> {
+        let url = match self.schemes.resolve(&url).transpose()? {
+            Some(crate::Resolved::Content(content)) => {
+                return HashCheck::check(
+                    expected_hash,
+                    crate::within_limit(content, self.max_size)?,
+                )
+            }
+            Some(crate::Resolved::Url(resolved)) => resolved,
+            None if url.scheme() == "data" => {
+                return HashCheck::check(
+                    expected_hash,
+                    crate::decode_data_url(&url, self.max_size)?,
+                )
+            }
+            None => crate::gateway_url(url, &self.ipfs_gateway, &self.arweave_gateway)?,
+        };
+        let mut delay = self.retry_delay;
+        for _ in 1..self.max_attempts {
+            match self.fetch_once(url.clone(), expected_hash) {
+                Err(err) if err.is_transient() => {
+                    debug!(error = %err, ?delay, "retrying after a transient error");
+                    std::thread::sleep(delay);
+                    delay = next_retry_delay(delay);
+                }
+                result => return result,
+            }
+        }
+        self.fetch_once(url, expected_hash)
+    }
+
+    fn fetch_once(&self, url: Url, expected_hash: Option<[u8; 32]>) -> Result<Vec<u8>> {
+        #[cfg(feature = "tracing")]
+        let started = std::time::Instant::now();
+        let mut target = url.clone();
+        let mut redirects = 0;
+        let (mut response, cached) = loop {
+            if let Some(policy) = &self.address_policy {
+                policy.check_url(&target)?;
+            }
+            let mut request = self
+                .client
+                .get(target.clone())
+                .timeout(self.timeout)
+                .headers(redirect::headers_for(&self.headers, &url, &target));
+            // The copy is kept with the validators sent for it, to answer a 304 even if it's evicted meanwhile
+            let cached = self.cache.as_ref().and_then(|cache| cache.lookup(&target));
+            if let Some(cached) = &cached {
+                request = request.headers(cached.validators.clone());
+            }
+            let response = request.send().map_err(|err| {
+                if err.is_timeout() {
+                    MetadataError::Timeout(self.timeout)
+                } else if let Some(denied) = address::denied_address(&err) {
+                    denied
+                } else {
+                    MetadataError::Fetch(err)
+                }
+            })?;
+            if !redirect::is_redirect(response.status()) {
+                break (response, cached);
+            }
+            if redirects == self.max_redirects {
+                return Err(MetadataError::TooManyRedirects(self.max_redirects));
+            }
+            let from = target;
+            target = redirect::redirect_target(&from, response.headers(), self.private_redirects)?;
+            debug!(%from, to = %target, "following redirect");
+            redirects += 1;
+        };
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            if let Some(cached) = cached {
+                let content =
+                    cached.reuse(self.max_size, self.content_types.as_deref(), expected_hash)?;
+                debug!(bytes = content.len(), elapsed = ?started.elapsed(), "document not modified, using cached copy");
+                return Ok(content);
+            }
+        }
+        crate::error::check_status(response.status(), response.url(), response.headers())?;
+        if let Some(content_types) = &self.content_types {
+            crate::check_content_type(response.headers(), content_types)?;
+        }
+        let too_large = MetadataError::TooLarge {
+            limit: self.max_size,
+        };
+        if response.content_length().unwrap_or(0) > self.max_size as u64 {
+            return Err(too_large);
+        }
+        let headers = response.headers().clone();
+        // Read the body in chunks, rather than trusting the content length, so a lying server can't exhaust our memory
+        let mut content = vec![];
+        let mut hash = expected_hash.map(HashCheck::new);
+        let mut chunk = [0; 8192];
+        loop {
+            let read = match response.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(err) if err.kind() == std::io::ErrorKind::TimedOut => {
+                    return Err(MetadataError::Timeout(self.timeout))
+                }
+                Err(err) => return Err(MetadataError::Io(err)),
+            };
+            if content.len() + read > self.max_size {
+                return Err(too_large);
+            }
+            if let Some(hash) = &mut hash {
+                hash.update(&chunk[..read]);
+            }
+            content.extend_from_slice(&chunk[..read]);
+        }
+        // Only a document that matches is cached, so a bad copy isn't revalidated and reused later
+        if let Some(hash) = hash {
+            hash.finish()?;
+        }
+        if let Some(cache) = &self.cache {
+            cache.insert(target, &headers, &content);
+        }
+        debug!(bytes = content.len(), elapsed = ?started.elapsed(), "fetched document");
+        Ok(content)
+    }
+}
+
+/// Drive the shared, async parsing code to completion on the current thread
+fn block_on<F: Future>(future: F) -> Result<F::Output> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    Ok(runtime.block_on(future))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::{TestServer, EXAMPLE};
+    use crate::Document;
+    use anyhow::Result;
+    use wiremock::ResponseTemplate;
+
+    #[tokio::test]
+    async fn test_blocking_client() -> Result<()> {
+        let server = TestServer::start().await;
+        let url = server.serve("/example.json", EXAMPLE).await;
+        let missing = server.url("/missing.json");
+
+        // The blocking client can't be used from within the runtime that wiremock needs
+        let (fetched, parsed, counted, not_found, too_large) = std::thread::spawn(move || {
+            let client = MetadataClient::new();
+            (
+                client.load::<Document>(url.clone()),
+                client.load_str::<Document>(EXAMPLE, url.clone()),
+                client.count_objects(url.clone()),
+                client.load::<Document>(missing),
+                MetadataClient::new()
+                    .with_max_size(16)
+                    .load::<Document>(url),
+            )
+        })
+        .join()
+        .unwrap();
+
+        let fetched = fetched?;
+        assert_eq!(fetched, parsed?);
+        assert_eq!(counted?, 1);
+        assert!(fetched.authors[0].verify(&fetched.canonical_hash()?)?);
+        assert!(matches!(
+            not_found,
+            Err(MetadataError::Status {
+                status: reqwest::StatusCode::NOT_FOUND,
+                ..
+            })
+        ));
+        assert!(matches!(
+            too_large,
+            Err(MetadataError::TooLarge { limit: 16 })
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_address_policy() -> Result<()> {
+        let server = TestServer::start().await;
+        let direct = server.serve("/example.json", EXAMPLE).await;
+        let mut localhost = direct.clone();
+        localhost.set_host(Some("localhost"))?;
+        let moved = server
+            .respond(
+                "/moved.json",
+                ResponseTemplate::new(302).insert_header("Location", localhost.as_str()),
+            )
+            .await;
+
+        let proxy = server.url("/").to_string();
+        let (literal, resolved, redirected, allowed, parsed, proxied) =
+            std::thread::spawn(move || {
+                let client = MetadataClient::new().with_address_policy(AddressPolicy::new());
+                let allowing_ip = MetadataClient::new()
+                    .with_private_redirects()
+                    .with_address_policy(AddressPolicy::new().allow_host("127.0.0.1"));
+                let allowing_localhost = MetadataClient::new()
+                    .with_address_policy(AddressPolicy::new().allow_host("localhost"));
+                let proxied = reqwest::Proxy::all(proxy)
+                    .map(|proxy| reqwest::blocking::Client::builder().proxy(proxy))
+                    .map_err(MetadataError::from)
+                    .and_then(|builder| {
+                        MetadataClient::new().with_address_policy_and_builder(
+                            AddressPolicy::new().allow_host("127.0.0.1"),
+                            builder,
+                        )
+                    });
+                (
+                    client.load::<Document>(direct),
+                    client.load::<Document>(localhost.clone()),
+                    allowing_ip.load::<Document>(moved),
+                    allowing_localhost.load::<Document>(localhost.clone()),
+                    allowing_localhost.load_str::<Document>(EXAMPLE, localhost.clone()),
+                    proxied.and_then(|client| client.load::<Document>(localhost)),
+                )
+            })
+            .join()
+            .unwrap();
+
+        // A literal IP is checked before connecting, and a domain once it's resolved, as with the async client
+        let denied = |result: crate::Result<Document>| match result {
+            Err(MetadataError::DeniedAddress { host, .. }) => host,
+            Err(err) => panic!("expected a denied address, got {err}"),
+            Ok(_) => panic!("expected a denied address"),
+        };
+        assert_eq!(denied(literal), "127.0.0.1");
+        assert_eq!(denied(resolved), "localhost");
+        // Allowing a host doesn't allow the hosts it redirects to
+        assert_eq!(denied(redirected), "localhost");
+        assert_eq!(allowed?, parsed?);
+        // A proxy would resolve hosts itself, out of reach of the policy, so one on the builder is turned off
+        assert_eq!(denied(proxied), "localhost");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_load_verified() -> Result<()> {
+        use blake2::{digest::consts::U32, Blake2b, Digest};
+
+        let server = TestServer::start().await;
+        let url = server
+            .respond(
+                "/example.json",
+                ResponseTemplate::new(200)
+                    .insert_header("ETag", "\"v1\"")
+                    .set_body_string(EXAMPLE),
+            )
+            .await;
+        let hash: [u8; 32] = Blake2b::<U32>::digest(EXAMPLE.as_bytes()).into();
+
+        let (mismatched, cached_mismatch, verified, cached, parsed) =
+            std::thread::spawn(move || {
+                let client = MetadataClient::new().with_cache();
+                let mismatched = client.load_verified::<Document>(url.clone(), [0; 32]);
+                let cache = client.cache.as_ref().unwrap();
+                let cached_mismatch = cache.lookup(&url).is_some();
+                let verified = client.load_verified::<Document>(url.clone(), hash);
+                (
+                    mismatched,
+                    cached_mismatch,
+                    verified,
+                    cache.lookup(&url).is_some(),
+                    client.load_str::<Document>(EXAMPLE, url),
+                )
+            })
+            .join()
+            .unwrap();
+
+        assert!(
+            matches!(mismatched, Err(MetadataError::HashMismatch { expected, actual }) if expected == [0; 32] && actual == hash)
+        );
+        // Only a document that matches its hash is cached
+        assert!(!cached_mismatch);
+        assert_eq!(verified?, parsed?);
+        assert!(cached);
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_reader() -> Result<()> {
+        let url = Url::parse("https://example.com/metadata.jsonld")?;
+        let client = MetadataClient::new();
+        let document = client.load_reader::<Document>(EXAMPLE.as_bytes(), url.clone())?;
+        assert_eq!(document, client.load_str::<Document>(EXAMPLE, url.clone())?);
+
+        let err = client
+            .with_max_size(1024)
+            .load_reader::<Document>(std::io::repeat(b' '), url)
+            .unwrap_err();
+        assert!(
+            matches!(err, MetadataError::TooLarge { limit: 1024 }),
+            "{err}"
+        );
+        Ok(())
+    }
+}
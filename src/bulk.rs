@@ -0,0 +1,69 @@
+//! Checking many on-chain anchors at once, from the newline-delimited JSON a chain indexer exports
+
+use json_ld::syntax::{Parse, Value};
+use url::Url;
+
+use crate::cip100::decode_hex;
+use crate::error::{MetadataError, OptionExt, Result};
+
+/// The outcome of checking one line of anchors; see [crate::MetadataClient::verify_anchors]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnchorCheck {
+    /// The line the anchor was read from, counting from 1
+    pub line: usize,
+    /// The anchor's url, or an empty string if the line didn't hold one
+    pub url: String,
+    /// Whether the document loaded, matched its hash, and parsed
+    pub ok: bool,
+    /// Why the check failed, if it did
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub error: Option<String>,
+}
+
+impl AnchorCheck {
+    pub(crate) fn new(line: usize, url: String, result: Result<()>) -> Self {
+        Self {
+            line,
+            url,
+            ok: result.is_ok(),
+            error: result.err().map(|err| err.to_string()),
+        }
+    }
+}
+
+/// The url and expected blake2b-256 hash of a line like `{"url": "https://...", "hash": "<hex>"}`, ignoring any
+/// other fields, such as a transaction id; the url is returned as written, if there is one, even when the
+/// line is otherwise malformed, so the failure can be reported against it
+pub(crate) fn parse_anchor(line: &str) -> (String, Result<(Url, [u8; 32])>) {
+    let value = match Value::parse_str(line) {
+        Ok((value, _)) => value,
+        Err(err) => {
+            let err = MetadataError::Parse {
+                position: err.position(),
+                message: err.to_string(),
+            };
+            return (String::new(), Err(err));
+        }
+    };
+    let field = |name: &str| {
+        value
+            .as_object()
+            .and_then(|object| object.get(name).next())
+            .and_then(Value::as_str)
+    };
+    let url = field("url").unwrap_or_default().to_string();
+    let anchor = || -> Result<(Url, [u8; 32])> {
+        let url = field("url").missing("anchor url")?;
+        let hash = decode_hex(field("hash").missing("anchor hash")?, "anchor hash")?;
+        let url = Url::parse(url).map_err(|_| MetadataError::InvalidIri {
+            field: "anchor url".to_string(),
+            iri: url.to_string(),
+        })?;
+        Ok((url, hash))
+    };
+    (url, anchor())
+}
@@ -0,0 +1,223 @@
+//! Revalidation of previously fetched documents with HTTP conditional requests
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use reqwest::header::{self, HeaderMap, HeaderValue};
+use url::Url;
+
+use crate::{error::Result, hash::HashCheck, MetadataError};
+
+/// How many bytes of document bodies a cache holds, unless configured otherwise
+pub(crate) const DEFAULT_CACHE_SIZE: usize = 16 * 1024 * 1024;
+
+/// A document body, along with the validators and content type the server sent with it
+struct Entry {
+    etag: Option<HeaderValue>,
+    last_modified: Option<HeaderValue>,
+    content_type: Option<HeaderValue>,
+    content: Arc<[u8]>,
+    /// When the entry was last stored or reused, on the cache's clock, to evict the least recently used first
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct Entries {
+    entries: HashMap<Url, Entry>,
+    /// The total length of the bodies held
+    size: usize,
+    clock: u64,
+}
+
+impl Entries {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn remove(&mut self, url: &Url) {
+        if let Some(entry) = self.entries.remove(url) {
+            self.size -= entry.content.len();
+        }
+    }
+}
+
+/// Remembers the ETag and Last-Modified of fetched documents, so later fetches of the same url can ask the
+/// server whether the document changed, and reuse the body already downloaded if it hasn't
+///
+/// At most max_size bytes of bodies are held; the least recently used are dropped to make room for new ones
+pub(crate) struct ResponseCache {
+    max_size: usize,
+    entries: Mutex<Entries>,
+}
+
+impl ResponseCache {
+    pub(crate) fn new(max_size: usize) -> Self {
+        Self {
+            max_size,
+            entries: Mutex::default(),
+        }
+    }
+
+    /// The copy of the url last fetched, along with the If-None-Match and If-Modified-Since headers to
+    /// revalidate it with; None if it hasn't been fetched before, or has since been evicted
+    ///
+    /// The headers and the body are taken together, so a 304 answering the headers can always be served from
+    /// the body, even if the entry is evicted while the request is in flight
+    pub(crate) fn lookup(&self, url: &Url) -> Option<Cached> {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        let now = entries.tick();
+        let entry = entries.entries.get_mut(url)?;
+        entry.last_used = now;
+        let mut validators = HeaderMap::new();
+        if let Some(etag) = &entry.etag {
+            validators.insert(header::IF_NONE_MATCH, etag.clone());
+        }
+        if let Some(last_modified) = &entry.last_modified {
+            validators.insert(header::IF_MODIFIED_SINCE, last_modified.clone());
+        }
+        Some(Cached {
+            validators,
+            content_type: entry.content_type.clone(),
+            content: entry.content.clone(),
+        })
+    }
+
+    /// Remember the body served for the url, if the response had an ETag or Last-Modified to revalidate it with
+    /// and it fits in the cache at all
+    pub(crate) fn insert(&self, url: Url, headers: &HeaderMap, content: &[u8]) {
+        let etag = headers.get(header::ETAG).cloned();
+        let last_modified = headers.get(header::LAST_MODIFIED).cloned();
+        let content_type = headers.get(header::CONTENT_TYPE).cloned();
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        entries.remove(&url);
+        if (etag.is_none() && last_modified.is_none()) || content.len() > self.max_size {
+            return;
+        }
+        while entries.size + content.len() > self.max_size {
+            let oldest = entries
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(url, _)| url.clone())
+                .expect("a cache over its size holds some entry");
+            entries.remove(&oldest);
+        }
+        let last_used = entries.tick();
+        entries.size += content.len();
+        entries.entries.insert(
+            url,
+            Entry {
+                etag,
+                last_modified,
+                content_type,
+                content: content.into(),
+                last_used,
+            },
+        );
+    }
+}
+
+/// A cached copy of a document, taken when a request to revalidate it is made
+pub(crate) struct Cached {
+    /// The headers that ask the server whether the document changed since this copy
+    pub(crate) validators: HeaderMap,
+    content_type: Option<HeaderValue>,
+    content: Arc<[u8]>,
+}
+
+impl Cached {
+    /// The copy's body, to use in place of a 304 Not Modified response
+    ///
+    /// Clones of a client share its cache, but may have their own limits, so the copy is held to the same
+    /// size limit, content types and expected hash as a document that was downloaded
+    pub(crate) fn reuse(
+        self,
+        max_size: usize,
+        content_types: Option<&[String]>,
+        expected_hash: Option<[u8; 32]>,
+    ) -> Result<Vec<u8>> {
+        if self.content.len() > max_size {
+            return Err(MetadataError::TooLarge { limit: max_size });
+        }
+        if let Some(content_types) = content_types {
+            let headers = HeaderMap::from_iter(
+                self.content_type
+                    .map(|content_type| (header::CONTENT_TYPE, content_type)),
+            );
+            crate::check_content_type(&headers, content_types)?;
+        }
+        HashCheck::check(expected_hash, self.content.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    fn get(cache: &ResponseCache, url: &Url) -> Option<Vec<u8>> {
+        cache.lookup(url).map(|cached| cached.content.to_vec())
+    }
+
+    #[test]
+    fn test_eviction() -> Result<()> {
+        let headers = HeaderMap::from_iter([(header::ETAG, HeaderValue::from_static("\"v1\""))]);
+        let url = |name: &str| Url::parse(&format!("https://example.com/{name}"));
+        let cache = ResponseCache::new(10);
+        cache.insert(url("a")?, &headers, b"aaaa");
+        cache.insert(url("b")?, &headers, b"bbbb");
+        // Reusing a makes b the least recently used, so b is dropped to make room for c
+        assert!(get(&cache, &url("a")?).is_some());
+        cache.insert(url("c")?, &headers, b"cccc");
+        assert_eq!(get(&cache, &url("a")?).as_deref(), Some(&b"aaaa"[..]));
+        assert_eq!(get(&cache, &url("b")?), None);
+        assert_eq!(get(&cache, &url("c")?).as_deref(), Some(&b"cccc"[..]));
+
+        // Replacing an entry frees the space its old body took
+        cache.insert(url("c")?, &headers, b"cccccc");
+        assert!(get(&cache, &url("a")?).is_some());
+        assert_eq!(get(&cache, &url("c")?).as_deref(), Some(&b"cccccc"[..]));
+
+        // A body that could never fit isn't kept, and doesn't evict anything trying
+        cache.insert(url("d")?, &headers, &[0; 11]);
+        assert_eq!(get(&cache, &url("d")?), None);
+        assert!(get(&cache, &url("a")?).is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_reuse() -> Result<()> {
+        let url = Url::parse("https://example.com/a")?;
+        let headers = HeaderMap::from_iter([
+            (header::ETAG, HeaderValue::from_static("\"v1\"")),
+            (header::CONTENT_TYPE, HeaderValue::from_static("text/html")),
+        ]);
+        let cache = ResponseCache::new(1024);
+        cache.insert(url.clone(), &headers, b"<html>");
+        let cached = || cache.lookup(&url).unwrap();
+        assert_eq!(
+            cached().validators.get(header::IF_NONE_MATCH).unwrap(),
+            "\"v1\""
+        );
+        assert_eq!(cached().reuse(1024, None, None)?, b"<html>");
+
+        // A clone with a smaller limit, or stricter content types, doesn't accept the copy another cached
+        assert!(matches!(
+            cached().reuse(4, None, None),
+            Err(MetadataError::TooLarge { limit: 4 })
+        ));
+        let json = ["application/json".to_string()];
+        assert!(matches!(
+            cached().reuse(1024, Some(&json), None),
+            Err(MetadataError::UnsupportedMediaType(_))
+        ));
+        assert!(matches!(
+            cached().reuse(1024, None, Some([0; 32])),
+            Err(MetadataError::HashMismatch { .. })
+        ));
+        Ok(())
+    }
+}
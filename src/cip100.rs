@@ -1,6 +1,22 @@
-use anyhow::*;
+use blake2::{digest::consts::U28, Blake2b, Digest};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
 use iref::{Iri, IriBuf};
-use json_ld::Node;
+use json_ld::{syntax::IntoJsonWithContext, Node, Object};
+use json_syntax::{json, Print, Value};
+use oxrdf::{
+    vocab::{rdf, xsd},
+    BlankNode, Dataset, Literal, NamedNode, Quad, Subject, Term,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt,
+    str::FromStr,
+};
+
+use crate::error::{MetadataError, OptionExt, Result};
+use crate::hash::{CanonicalizationMethod, HashAlgorithm};
+use crate::lang::{LangString, Localized};
+use crate::util::{get_named_string, iri_value, normalize_iri};
 
 // The context fields used in the context of CIP-100 documents
 // Mostly just here for convenience, to have namespaced constants
@@ -46,150 +62,1262 @@ pub const CIP100_FIELDS: CIP100Fields = CIP100Fields {
     witness_signature: "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0100/README.md#signature",
 };
 
+/// The algorithm a witness declares its signature was made with
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "String", into = "String")
+)]
+pub enum WitnessAlgorithm {
+    /// A plain ed25519 signature of the document hash
+    Ed25519,
+    /// A CIP-8 COSE_Sign1 message signing the document hash, as produced by a wallet's CIP-30 signData;
+    /// only verifiable with the cip8 feature
+    Cip8,
+    /// Some algorithm this library doesn't recognize; kept so that the document still parses
+    Unknown(String),
+}
+
+impl WitnessAlgorithm {
+    /// The identifiers of every algorithm this library recognizes, as used in the witnessAlgorithm field
+    pub const RECOGNIZED: &'static [&'static str] = &["ed25519", "CIP-0008"];
+
+    /// Whether this is an algorithm this library recognizes, rather than some other string
+    pub fn is_recognized(&self) -> bool {
+        !matches!(self, WitnessAlgorithm::Unknown(_))
+    }
+
+    /// The identifier used for this algorithm in the witnessAlgorithm field of a document
+    pub fn as_str(&self) -> &str {
+        match self {
+            WitnessAlgorithm::Ed25519 => "ed25519",
+            WitnessAlgorithm::Cip8 => "CIP-0008",
+            WitnessAlgorithm::Unknown(algorithm) => algorithm,
+        }
+    }
+}
+
+impl From<&str> for WitnessAlgorithm {
+    fn from(value: &str) -> Self {
+        match value {
+            "ed25519" => WitnessAlgorithm::Ed25519,
+            "CIP-0008" => WitnessAlgorithm::Cip8,
+            algorithm => WitnessAlgorithm::Unknown(algorithm.to_string()),
+        }
+    }
+}
+
+impl From<String> for WitnessAlgorithm {
+    fn from(value: String) -> Self {
+        value.as_str().into()
+    }
+}
+
+impl From<WitnessAlgorithm> for String {
+    fn from(value: WitnessAlgorithm) -> Self {
+        value.as_str().to_string()
+    }
+}
+
+impl FromStr for WitnessAlgorithm {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(s.into())
+    }
+}
+
+impl fmt::Display for WitnessAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// A witness from an author who has signed the document
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Witness {
     /// The algorithm used to sign the document
-    pub algorithm: String,
+    pub algorithm: WitnessAlgorithm,
     /// The public key used to sign the document
     pub public_key: String,
     /// The signature of the document
     pub signature: String,
 }
+
+impl Witness {
+    /// An ed25519 witness from the holder of the signing key, signing the hash of a document; usually its
+    /// [Document::canonical_hash], so the witness can be added to the document's authors
+    pub fn sign(signing_key: &SigningKey, document_hash: &[u8]) -> Witness {
+        Witness {
+            algorithm: WitnessAlgorithm::Ed25519,
+            public_key: hex::encode(signing_key.verifying_key().as_bytes()),
+            signature: hex::encode(signing_key.sign(document_hash).to_bytes()),
+        }
+    }
+
+    /// Verify the witness signature against the hash of the document it signs
+    ///
+    /// Returns Ok(false) if the signature is well formed but doesn't match, and an error if the
+    /// algorithm is unsupported or the key / signature can't be decoded
+    pub fn verify(&self, document_hash: &[u8]) -> Result<bool> {
+        match &self.algorithm {
+            WitnessAlgorithm::Ed25519 => {
+                let public_key = self.public_key_bytes()?;
+                let signature: [u8; 64] = decode_hex(&self.signature, "witness signature")?;
+                let public_key = VerifyingKey::from_bytes(&public_key).map_err(|_| {
+                    MetadataError::InvalidValue {
+                        field: "witness public key".to_string(),
+                        reason: "is not a valid ed25519 key".to_string(),
+                    }
+                })?;
+                let signature = Signature::from_bytes(&signature);
+                Ok(public_key.verify(document_hash, &signature).is_ok())
+            }
+            #[cfg(feature = "cip8")]
+            WitnessAlgorithm::Cip8 => {
+                let public_key = crate::cose::witness_public_key(&self.public_key)?;
+                self.cose_sign1()?.verify(&public_key, document_hash)
+            }
+            // Without the cip8 feature, there's no COSE decoder to verify CIP-8 witnesses with
+            #[cfg(not(feature = "cip8"))]
+            WitnessAlgorithm::Cip8 => Err(MetadataError::UnsupportedAlgorithm(
+                self.algorithm.to_string(),
+            )),
+            WitnessAlgorithm::Unknown(_) => Err(MetadataError::UnsupportedAlgorithm(
+                self.algorithm.to_string(),
+            )),
+        }
+    }
+
+    /// Decode the signature of a CIP-0008 witness, for the address it was signed for and the payload it signs
+    #[cfg(feature = "cip8")]
+    pub fn cose_sign1(&self) -> Result<crate::cose::CoseSign1> {
+        crate::cose::CoseSign1::from_hex(&self.signature)
+    }
+
+    /// Check the public key and signature are hex of the lengths the algorithm calls for, such as a 32 byte
+    /// key and 64 byte signature for ed25519, catching a malformed witness before it's verified; for an
+    /// algorithm this library doesn't recognize, they're only checked to be hex
+    pub fn validate(&self) -> Result<()> {
+        let (key_length, signature_length) = witness_lengths(&self.algorithm).unzip();
+        normalize_hex(&self.public_key, "witness public key", key_length)?;
+        normalize_hex(&self.signature, "witness signature", signature_length)?;
+        Ok(())
+    }
+
+    /// The raw bytes of the public key the document was signed with
+    pub fn public_key_bytes(&self) -> Result<[u8; 32]> {
+        decode_hex(&self.public_key, "witness public key")
+    }
+
+    /// The blake2b-224 hash of the public key, as used in Cardano credentials, so the author can be matched
+    /// against a DRep or committee member key hash recorded on-chain
+    pub fn key_hash_blake2b224(&self) -> Result<[u8; 28]> {
+        Ok(Blake2b::<U28>::digest(self.public_key_bytes()?).into())
+    }
+}
+
+/// The digits of a hex string, without any 0x prefix; either case is accepted when they're decoded
+pub(crate) fn hex_digits(value: &str) -> &str {
+    value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value)
+}
+
+/// Decode a hex string that must hold exactly N bytes, written as [hex_digits] accepts it
+pub(crate) fn decode_hex<const N: usize>(value: &str, field: &str) -> Result<[u8; N]> {
+    let invalid = |reason: String| MetadataError::InvalidValue {
+        field: field.to_string(),
+        reason,
+    };
+    hex::decode(hex_digits(value))
+        .map_err(|_| invalid("is not valid hex".to_string()))?
+        .try_into()
+        .map_err(|_| invalid(format!("must be {N} bytes")))
+}
+
 /// An author who has signed the metadata document
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Author {
     /// The authors display name; self-reported, so may be inaccurate, if not strongly associated with a public key via some other means
-    pub name: String,
-    /// The witness attesting to this authors approval of the metadata
-    pub witness: Witness,
+    pub name: LangString,
+    /// The witness attesting to this authors approval of the metadata; absent while the document is
+    /// still a draft that the author hasn't signed
+    pub witness: Option<Witness>,
+}
+
+impl Author {
+    /// Verify the author's witness against the hash of the document it signs
+    ///
+    /// An author who hasn't signed yet is unverified, rather than an error, so drafts can be checked
+    pub fn verify(&self, document_hash: &[u8]) -> Result<bool> {
+        match &self.witness {
+            Some(witness) => witness.verify(document_hash),
+            None => Ok(false),
+        }
+    }
 }
 
 /// The type of document being referenced
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ReferenceType {
     /// The referenced document should be parsed as if it were another governance metadata document, i.e. with reference to CIP-100
+    #[cfg_attr(feature = "serde", serde(rename = "GovernanceMetadataReference"))]
     GovernanceMetadata,
     /// The referenced document is some other type of document, and should not be assumed to be CIP-100 compatible
+    #[cfg_attr(feature = "serde", serde(rename = "OtherReference"))]
     Other,
+    /// A reference type this library doesn't recognize, such as one added by a later CIP; kept so
+    /// that the rest of the document can still be used
+    Unknown(IriBuf),
+}
+
+impl ReferenceType {
+    /// The IRI identifying this reference type, as used for its @type in a document
+    pub fn as_iri(&self) -> &Iri {
+        match self {
+            ReferenceType::GovernanceMetadata => {
+                Iri::new(CIP100_FIELDS.reference_type_governance_metadata)
+                    .expect("CIP-100 reference types are valid IRIs")
+            }
+            ReferenceType::Other => Iri::new(CIP100_FIELDS.reference_type_other)
+                .expect("CIP-100 reference types are valid IRIs"),
+            ReferenceType::Unknown(iri) => iri,
+        }
+    }
 }
 
 /// A reference to some other document for additional context to help understand this governance document
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Reference {
     /// The type of document being referenced
     pub reference_type: ReferenceType,
     /// The label to display for the reference
-    pub label: String,
+    pub label: LangString,
     /// The Internationalized resource identifier for where to find the document
     pub uri: IriBuf,
+    /// Any types the reference declares alongside its reference type, such as a generic Reference; they're
+    /// part of the body its authors signed, so they're kept to reproduce the canonical hash
+    pub other_types: Vec<IriBuf>,
+    /// Any other properties of the reference, keyed by their full IRI and holding their value in expanded
+    /// JSON-LD form, as for [Body::extensions]
+    pub extensions: BTreeMap<IriBuf, Value>,
 }
 
 /// The place to find updated information pertaining to this document
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Update {
     /// The title of the update source
-    pub title: String,
+    pub title: LangString,
     /// The Internationalized resource identifier for the update source
     pub uri: IriBuf,
+    /// Any other properties of the update, keyed by their full IRI and holding their value in expanded
+    /// JSON-LD form, as for [Body::extensions]
+    pub extensions: BTreeMap<IriBuf, Value>,
+}
+
+impl Reference {
+    /// The uri in a canonical form, for deduplicating references whose uris differ only in how they're written,
+    /// such as in case or percent-encoding; lossy, so only for comparing, see [crate::util::normalize_iri]
+    pub fn normalized_uri(&self) -> IriBuf {
+        normalize_iri(&self.uri)
+    }
+}
+
+impl Update {
+    /// The uri in a canonical form, for deduplicating updates whose uris differ only in how they're written,
+    /// such as in case or percent-encoding; lossy, so only for comparing, see [crate::util::normalize_iri]
+    pub fn normalized_uri(&self) -> IriBuf {
+        normalize_iri(&self.uri)
+    }
 }
 
 /// The body of the governance metadata document
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Body {
     /// Any references included in the document
     pub references: Vec<Reference>,
     /// A free-form textual and generic comment associated with this governance metadata document
-    pub comment: String,
+    /// Usually a single value, but may be given in several languages; absent in bodies that leave it out,
+    /// which parse, but fail [Document::validate]
+    pub comment: Option<Localized>,
     /// A series of locations where updates may be found, such as a twitter feed, blog, etc.
     /// Note that the updates themselves should be treated as unauthenticated materials
     pub external_updates: Vec<Update>,
+    /// The default language of the body, usually set via `@language` in the document context
+    /// Values that aren't text, like URIs, are still tagged with it and signed that way, so this
+    /// is needed to reproduce the canonical hash
+    pub language: Option<String>,
+    /// Any other properties of the body, such as those added by CIPs extending CIP-100 or by
+    /// vendors, keyed by their full IRI and holding their value in expanded JSON-LD form
+    pub extensions: HashMap<IriBuf, Value>,
+}
+
+impl Body {
+    /// The first reference with the given label, in any language
+    pub fn reference_by_label(&self, label: &str) -> Option<&Reference> {
+        self.references
+            .iter()
+            .find(|reference| reference.label.value() == label)
+    }
+
+    /// Every reference of the given type, in the order they appear in the document
+    pub fn references_of_type(
+        &self,
+        reference_type: ReferenceType,
+    ) -> impl Iterator<Item = &Reference> + '_ {
+        self.references
+            .iter()
+            .filter(move |reference| reference.reference_type == reference_type)
+    }
 }
 
 /// The governance metadata document itself
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Document {
+    /// The IRI identifying the subject of the document, such as an on-chain entity it describes, if it has
+    /// an @id; documents without one, or with a blank node identifier, are None
+    pub id: Option<IriBuf>,
     /// The hash algorithm used to hash the document when signing
-    pub hash_algorithm: String,
+    pub hash_algorithm: HashAlgorithm,
     /// The authors who cosign / attest to this document
     pub authors: Vec<Author>,
     /// The body of the document
     pub body: Body,
+    /// Any other top level properties of the document, keyed by their full IRI and holding their
+    /// value in expanded JSON-LD form
+    pub extensions: HashMap<IriBuf, Value>,
 }
 
-impl TryFrom<&Node> for Document {
-    type Error = anyhow::Error;
+impl Document {
+    /// The blake2b-256 hash of the canonicalized document body, which is what each author signs
+    pub fn canonical_hash(&self) -> Result<[u8; 32]> {
+        let hash = self.hash_with(HashAlgorithm::Blake2b256)?;
+        Ok(hash.try_into().expect("blake2b-256 hashes are 32 bytes"))
+    }
 
-    fn try_from(object: &Node) -> Result<Self, Self::Error> {
-        let hash_algorithm = object
-            .get_any(&Iri::new(CIP100_FIELDS.hash_algorithm)?)
-            .context("no hash_algorithm field")?
-            .as_str()
-            .context("hash_algorithm is not a string")?
-            .to_string();
-
-        let authors = object
-            .get(&Iri::new(CIP100_FIELDS.authors)?)
-            .map(|author| author.inner().as_node().unwrap().try_into())
-            .collect::<Result<Vec<Author>>>()?;
-
-        let body = object
-            .get_any(&Iri::new(CIP100_FIELDS.body)?)
-            .context("no body field")?
-            .as_node()
-            .context("body field isn't an object")?
-            .try_into()?;
+    /// The hash of the canonicalized document body, using the given hash algorithm; usually the
+    /// document's own hash_algorithm, for documents signed with something other than blake2b-256
+    ///
+    /// Per CIP-100, the body (and only the body) is converted to RDF, canonicalized with URDNA2015,
+    /// and the resulting N-Quads are hashed; the authors and their witnesses are excluded, since
+    /// they are produced by signing that hash
+    pub fn hash_with(&self, hash_algorithm: HashAlgorithm) -> Result<Vec<u8>> {
+        self.canonical_hash_with(CanonicalizationMethod::Urdna2015, hash_algorithm)
+    }
+
+    /// The hash of the document body, canonicalized with the given method; only URDNA2015, the default, is
+    /// CIP-100, but some tooling hashed the JCS form of the body instead, so their anchors and signatures can
+    /// be checked with [CanonicalizationMethod::Jcs]
+    pub fn canonical_hash_with(
+        &self,
+        method: CanonicalizationMethod,
+        hash_algorithm: HashAlgorithm,
+    ) -> Result<Vec<u8>> {
+        hash_algorithm.digest(self.canonical_body_with(method)?.as_bytes())
+    }
+
+    /// The canonical serialization of the document body with the given method; see [CanonicalizationMethod]
+    pub fn canonical_body_with(&self, method: CanonicalizationMethod) -> Result<String> {
+        match method {
+            CanonicalizationMethod::Urdna2015 => self.canonical_body(),
+            CanonicalizationMethod::Jcs => {
+                let mut body = self
+                    .compact()
+                    .as_object()
+                    .and_then(|document| document.get_unique("body").ok().flatten())
+                    .cloned()
+                    .expect("documents always have a body");
+                body.canonicalize();
+                Ok(body.compact_print().to_string())
+            }
+        }
+    }
+
+    /// Whether the document's hash_algorithm is one this library recognizes; a document with a typo such as
+    /// "blake2b256" still parses, unless the client was configured to reject it, but can't be verified
+    pub fn hash_algorithm_recognized(&self) -> bool {
+        self.hash_algorithm.is_recognized()
+    }
+
+    /// Check every author's witness against the document, hashed with its own hash algorithm
+    ///
+    /// The hash is computed once, and every author is checked, rather than stopping at the first bad
+    /// signature; each author's name is paired with whether their signature is valid, or why it
+    /// couldn't be checked, such as an unsupported witness algorithm; authors who haven't signed yet are
+    /// reported as unverified. Only failing to hash the document at all is an error
+    pub fn verify_all(&self) -> Result<Vec<(String, Result<bool>)>> {
+        Ok(self
+            .authors_verified()?
+            .map(|(author, verified)| (author.name.to_string(), verified))
+            .collect())
+    }
+
+    /// Like [Document::verify_all], but checks each author only as the iterator reaches them, so a caller
+    /// looking for one particular signer can stop early
+    ///
+    /// The document is hashed once, up front, however much of the iterator is consumed
+    pub fn authors_verified(&self) -> Result<impl Iterator<Item = (&Author, Result<bool>)> + '_> {
+        let hash = self.hash_with(self.hash_algorithm.clone())?;
+        Ok(self
+            .authors
+            .iter()
+            .map(move |author| (author, author.verify(&hash))))
+    }
+
+    /// Whether an author with the given hex public key has signed the document, and their signature verifies
+    ///
+    /// The key is compared case-insensitively, with or without a 0x prefix; a key no author signed with is
+    /// Ok(false), so only a malformed key, or a matching witness that can't be checked, is an error
+    pub fn is_signed_by(&self, public_key_hex: &str) -> Result<bool> {
+        let public_key = normalize_hex(public_key_hex, "public key", None)?;
+        let mut signed = self
+            .witnesses()
+            .filter(|witness| {
+                normalize_hex(&witness.public_key, "witness public key", None)
+                    .is_ok_and(|key| key == public_key)
+            })
+            .peekable();
+        if signed.peek().is_none() {
+            return Ok(false);
+        }
+        let hash = self.hash_with(self.hash_algorithm.clone())?;
+        for witness in signed {
+            if witness.verify(&hash)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// The hex public keys of the authors who've signed, in the order they're listed; authors of a draft who
+    /// haven't signed yet are skipped
+    pub fn author_public_keys(&self) -> Vec<String> {
+        self.witnesses()
+            .map(|witness| witness.public_key.clone())
+            .collect()
+    }
+
+    /// The raw bytes of [Document::author_public_keys], to compare against keys or key hashes held elsewhere;
+    /// fails if any witness's public key isn't a 32 byte hex key
+    pub fn author_public_key_bytes(&self) -> Result<Vec<[u8; 32]>> {
+        self.witnesses().map(Witness::public_key_bytes).collect()
+    }
+
+    fn witnesses(&self) -> impl Iterator<Item = &Witness> {
+        self.authors
+            .iter()
+            .filter_map(|author| author.witness.as_ref())
+    }
+
+    /// The URDNA2015 canonical N-Quads serialization of the document body
+    pub fn canonical_body(&self) -> Result<String> {
+        let dataset: Dataset = self.body_quads().into_iter().collect();
+        rdf_canon::canonicalize(&dataset)
+            .map_err(|err| MetadataError::Canonicalize(err.to_string()))
+    }
+
+    /// The RDF quads for a document containing only the body, as it would be expanded from JSON-LD
+    fn body_quads(&self) -> Vec<Quad> {
+        let literal = |value: &str, language: Option<&str>| {
+            Term::from(match language {
+                Some(language) => Literal::new_language_tagged_literal_unchecked(value, language),
+                None => Literal::new_simple_literal(value),
+            })
+        };
+        let text = |value: &LangString| literal(value.value(), value.language());
+        let string = |value: &str| literal(value, self.body.language.as_deref());
+
+        let mut quads = QuadWriter::default();
+        let root = quads.blank();
+        let body = quads.blank();
+        quads.add(root.as_ref(), CIP100_FIELDS.body, body.clone());
+        for reference in &self.body.references {
+            let node = quads.blank();
+            quads.add(body.as_ref(), CIP100_FIELDS.body_references, node.clone());
+            for reference_type in std::iter::once(reference.reference_type.as_iri())
+                .chain(reference.other_types.iter().map(IriBuf::as_iri))
+            {
+                quads.add(
+                    node.as_ref(),
+                    rdf::TYPE.as_str(),
+                    NamedNode::new_unchecked(reference_type.as_str()),
+                );
+            }
+            quads.add(
+                node.as_ref(),
+                CIP100_FIELDS.reference_label,
+                text(&reference.label),
+            );
+            // CIP-100's context doesn't give uris an @type, so they're hashed as strings, even if the
+            // document wrote one as an @id
+            quads.add(
+                node.as_ref(),
+                CIP100_FIELDS.reference_uri,
+                string(reference.uri.as_str()),
+            );
+            for (property, value) in &reference.extensions {
+                quads.add_expanded(&node.clone().into(), property.as_str(), value);
+            }
+        }
+        for comment in self.body.comment.iter().flat_map(|comment| comment.iter()) {
+            quads.add(body.as_ref(), CIP100_FIELDS.body_comment, text(comment));
+        }
+        for update in &self.body.external_updates {
+            let node = quads.blank();
+            quads.add(
+                body.as_ref(),
+                CIP100_FIELDS.body_external_updates,
+                node.clone(),
+            );
+            quads.add(
+                node.as_ref(),
+                CIP100_FIELDS.update_title,
+                text(&update.title),
+            );
+            quads.add(
+                node.as_ref(),
+                CIP100_FIELDS.update_uri,
+                string(update.uri.as_str()),
+            );
+            for (property, value) in &update.extensions {
+                quads.add_expanded(&node.clone().into(), property.as_str(), value);
+            }
+        }
+        for (property, value) in &self.body.extensions {
+            quads.add_expanded(&body.clone().into(), property.as_str(), value);
+        }
+        quads.quads
+    }
+
+    /// The parts of the document shared with every other CIP-100 based document, borrowed from it, for
+    /// handling documents of any CIP alike; [Cip100BaseRef::into_owned] copies them out if they need to
+    /// outlive the document
+    pub fn base(&self) -> Cip100BaseRef<'_> {
+        Cip100BaseRef::new(&self.hash_algorithm, &self.authors, &self.body.references)
+    }
+
+    /// Every uri the document links to, for link checking or archival; the references' uris come first,
+    /// then the external updates', each in the order they appear in the document
+    pub fn all_uris(&self) -> Vec<&IriBuf> {
+        let references = self.body.references.iter().map(|reference| &reference.uri);
+        let updates = self.body.external_updates.iter().map(|update| &update.uri);
+        references.chain(updates).collect()
+    }
+
+    /// Whether the documents say the same thing, even if they list it in a different order: the authors,
+    /// references, external updates and comment translations are compared as sets, as CIP-100's context
+    /// declares them, and everything else exactly; use `==` to also compare the order
+    pub fn semantically_eq(&self, other: &Document) -> bool {
+        fn set<T: Ord>(items: &[T]) -> BTreeSet<&T> {
+            items.iter().collect()
+        }
+        self.id == other.id
+            && self.hash_algorithm == other.hash_algorithm
+            && set(&self.authors) == set(&other.authors)
+            && set(&self.body.references) == set(&other.body.references)
+            && set(&self.body.external_updates) == set(&other.body.external_updates)
+            && self.body.comment.as_deref().map(set) == other.body.comment.as_deref().map(set)
+            && self.body.language == other.body.language
+            && self.body.extensions == other.body.extensions
+            && self.extensions == other.extensions
+    }
+
+    /// Whether the expanded node has an authors field at all, even an empty one, which the parsed document
+    /// can't tell apart from a document that leaves it out, since both have no authors
+    pub fn has_authors_field(node: &Node) -> bool {
+        Iri::new(CIP100_FIELDS.authors).is_ok_and(|authors| node.properties().contains(&authors))
+    }
+
+    /// Start building a new document; see [DocumentBuilder]
+    pub fn builder() -> DocumentBuilder {
+        DocumentBuilder::default()
+    }
+
+    /// Serialize the document as CIP-100 JSON-LD, ready to be published and referenced from an anchor
+    ///
+    /// Every term is mapped to the full IRI from CIP100_FIELDS in the embedded context, so the
+    /// output doesn't depend on any remote context to be understood. The output is a single line, with no
+    /// whitespace between tokens, and the same document always serializes to the same bytes, so it can be
+    /// hashed for an anchor
+    pub fn to_jsonld(&self) -> Result<String> {
+        Ok(self.jsonld().compact_print().to_string())
+    }
+
+    /// Like [Document::to_jsonld], but indented over several lines, for reviewing or diffing by hand; an
+    /// anchor for it must be computed from these bytes, since they differ from the compact form
+    pub fn to_jsonld_pretty(&self) -> Result<String> {
+        Ok(self.jsonld().pretty_print().to_string())
+    }
+
+    /// The document with the @context that maps its keys to CIP-100 IRIs
+    fn jsonld(&self) -> Value {
+        let mut context = json!({
+            "hashAlgorithm": CIP100_FIELDS.hash_algorithm,
+            "body": {
+                "@id": CIP100_FIELDS.body,
+                "@context": {
+                    "references": {
+                        "@id": CIP100_FIELDS.body_references,
+                        "@container": "@set",
+                        "@context": {
+                            "GovernanceMetadata": CIP100_FIELDS.reference_type_governance_metadata,
+                            "Other": CIP100_FIELDS.reference_type_other,
+                            "label": CIP100_FIELDS.reference_label,
+                            "uri": CIP100_FIELDS.reference_uri,
+                        }
+                    },
+                    "comment": CIP100_FIELDS.body_comment,
+                    "externalUpdates": {
+                        "@id": CIP100_FIELDS.body_external_updates,
+                        "@container": "@set",
+                        "@context": {
+                            "title": CIP100_FIELDS.update_title,
+                            "uri": CIP100_FIELDS.update_uri,
+                        }
+                    }
+                }
+            },
+            "authors": {
+                "@id": CIP100_FIELDS.authors,
+                "@container": "@set",
+                "@context": {
+                    "name": CIP100_FIELDS.author_name,
+                    "witness": {
+                        "@id": CIP100_FIELDS.author_witness,
+                        "@context": {
+                            "witnessAlgorithm": CIP100_FIELDS.witness_algorithm,
+                            "publicKey": CIP100_FIELDS.witness_public_key,
+                            "signature": CIP100_FIELDS.witness_signature,
+                        }
+                    }
+                }
+            }
+        });
+        if let (Some(language), Some(context)) = (&self.body.language, context.as_object_mut()) {
+            context.insert("@language".into(), language.as_str().into());
+        }
+        let mut document = json!({ "@context": context });
+        if let (Some(document), Value::Object(compact)) = (document.as_object_mut(), self.compact())
+        {
+            document.extend(compact);
+        }
+        document
+    }
+
+    /// The document as plain JSON, in the shape CIP-100 documents are written in before their @context
+    /// is applied, such as `{ "hashAlgorithm": ..., "body": { "comment": ... }, "authors": [...] }`
+    ///
+    /// Unlike [Document::to_jsonld], there's no @context, so the output is meant for APIs that speak
+    /// plain JSON rather than for publishing
+    #[cfg(feature = "serde")]
+    pub fn to_compact_json(&self) -> serde_json::Value {
+        serde_json::to_value(self.compact()).expect("JSON values always serialize")
+    }
+
+    /// The document with its human friendly keys, without the @context that maps them to IRIs
+    fn compact(&self) -> Value {
+        // Text in the default language can be written as is; anything else needs a value object
+        let text = |value: &LangString| match value.language() {
+            language if language == self.body.language.as_deref() => Value::from(value.value()),
+            Some(language) => json!({ "@value": value.value(), "@language": language }),
+            None => json!({ "@value": value.value() }),
+        };
+        // Extensions are already expanded, and keyed by full IRI, so the context doesn't affect them
+        fn extend<'a>(
+            object: &mut Value,
+            extensions: impl IntoIterator<Item = (&'a IriBuf, &'a Value)>,
+        ) {
+            let mut extensions: Vec<_> = extensions.into_iter().collect();
+            extensions.sort_by(|a, b| a.0.cmp(b.0));
+            if let Some(object) = object.as_object_mut() {
+                for (property, value) in extensions {
+                    object.insert(property.as_str().into(), value.clone());
+                }
+            }
+        }
+        let localized = |value: &Localized| match &value[..] {
+            [single] => text(single),
+            variants => Value::Array(variants.iter().map(text).collect()),
+        };
+
+        let references: Vec<Value> = self
+            .body
+            .references
+            .iter()
+            .map(|reference| {
+                let reference_type = match &reference.reference_type {
+                    ReferenceType::GovernanceMetadata => "GovernanceMetadata",
+                    ReferenceType::Other => "Other",
+                    ReferenceType::Unknown(iri) => iri.as_str(),
+                };
+                let reference_type = match reference.other_types.as_slice() {
+                    [] => Value::from(reference_type),
+                    others => Value::Array(
+                        std::iter::once(reference_type)
+                            .chain(others.iter().map(IriBuf::as_str))
+                            .map(Value::from)
+                            .collect(),
+                    ),
+                };
+                let mut value = json!({
+                    "@type": reference_type,
+                    "label": text(&reference.label),
+                    "uri": reference.uri.as_str(),
+                });
+                extend(&mut value, &reference.extensions);
+                value
+            })
+            .collect();
+        let external_updates: Vec<Value> = self
+            .body
+            .external_updates
+            .iter()
+            .map(|update| {
+                let mut value = json!({
+                    "title": text(&update.title),
+                    "uri": update.uri.as_str(),
+                });
+                extend(&mut value, &update.extensions);
+                value
+            })
+            .collect();
+        let authors: Vec<Value> = self
+            .authors
+            .iter()
+            .map(|author| {
+                let mut value = json!({ "name": text(&author.name) });
+                if let (Some(witness), Some(object)) = (&author.witness, value.as_object_mut()) {
+                    object.insert(
+                        "witness".into(),
+                        json!({
+                            "witnessAlgorithm": witness.algorithm.as_str(),
+                            "publicKey": witness.public_key.as_str(),
+                            "signature": witness.signature.as_str(),
+                        }),
+                    );
+                }
+                value
+            })
+            .collect();
+
+        let mut body = json!({
+            "references": references,
+            "externalUpdates": external_updates,
+        });
+        if let (Some(comment), Some(object)) = (&self.body.comment, body.as_object_mut()) {
+            object.insert("comment".into(), localized(comment));
+        }
+        extend(&mut body, &self.body.extensions);
+        let mut document = json!({
+            "hashAlgorithm": self.hash_algorithm.as_str(),
+            "body": body,
+            "authors": authors,
+        });
+        extend(&mut document, &self.extensions);
+        if let (Some(id), Some(document)) = (&self.id, document.as_object_mut()) {
+            document.insert("@id".into(), id.as_str().into());
+        }
+        document
+    }
+}
+
+/// Accumulates RDF quads in the default graph, handing out fresh blank node labels as needed
+#[derive(Default)]
+struct QuadWriter {
+    quads: Vec<Quad>,
+    labels: usize,
+}
+
+impl QuadWriter {
+    fn blank(&mut self) -> BlankNode {
+        self.labels += 1;
+        BlankNode::new_unchecked(format!("b{}", self.labels - 1))
+    }
+
+    fn add(&mut self, subject: impl Into<Subject>, predicate: &str, object: impl Into<Term>) {
+        self.quads.push(Quad::new(
+            subject,
+            NamedNode::new_unchecked(predicate),
+            object,
+            oxrdf::GraphName::DefaultGraph,
+        ));
+    }
+
+    /// Add the quads for a property whose value is in expanded JSON-LD form, such as an extension
+    fn add_expanded(&mut self, subject: &Subject, predicate: &str, value: &Value) {
+        for object in value.as_array().unwrap_or(std::slice::from_ref(value)) {
+            if let Some(term) = self.expanded_term(object) {
+                self.add(subject.clone(), predicate, term);
+            }
+        }
+    }
+
+    /// Convert an expanded JSON-LD object to an RDF term, following the JSON-LD to RDF algorithm,
+    /// adding quads for any nested nodes or lists along the way
+    fn expanded_term(&mut self, object: &Value) -> Option<Term> {
+        let object = object.as_object()?;
+        let get = |key: &str| object.get_unique(key).ok().flatten();
+        if let Some(value) = get("@value") {
+            let lexical = match value {
+                Value::String(value) => value.to_string(),
+                Value::Boolean(value) => value.to_string(),
+                Value::Number(value) => value.to_string(),
+                _ => return None,
+            };
+            let literal = match (
+                get("@language").and_then(Value::as_str),
+                get("@type").and_then(Value::as_str),
+            ) {
+                (Some(language), _) => {
+                    Literal::new_language_tagged_literal_unchecked(lexical, language)
+                }
+                (None, Some(datatype)) => {
+                    Literal::new_typed_literal(lexical, NamedNode::new_unchecked(datatype))
+                }
+                (None, None) => match value {
+                    Value::Boolean(_) => Literal::new_typed_literal(lexical, xsd::BOOLEAN),
+                    Value::Number(number) => match number.as_f64_lossy() {
+                        n if n.fract() == 0.0 && n.abs() < 1e21 => {
+                            Literal::new_typed_literal(format!("{}", n as i128), xsd::INTEGER)
+                        }
+                        n => {
+                            // The canonical xsd:double form always has a fractional part, like 1.0E21
+                            let mut lexical = format!("{:E}", n);
+                            if !lexical.contains('.') {
+                                lexical = lexical.replacen('E', ".0E", 1);
+                            }
+                            Literal::new_typed_literal(lexical, xsd::DOUBLE)
+                        }
+                    },
+                    _ => Literal::new_simple_literal(lexical),
+                },
+            };
+            return Some(literal.into());
+        }
+        if let Some(items) = get("@list") {
+            let items = items.as_array().unwrap_or_default();
+            let mut rest: Term = rdf::NIL.into_owned().into();
+            for item in items.iter().rev() {
+                let Some(first) = self.expanded_term(item) else {
+                    continue;
+                };
+                let node = self.blank();
+                self.add(node.as_ref(), rdf::FIRST.as_str(), first);
+                self.add(node.as_ref(), rdf::REST.as_str(), rest);
+                rest = node.into();
+            }
+            return Some(rest);
+        }
+        let subject: Subject = match get("@id").and_then(Value::as_str) {
+            Some(id) if !id.starts_with("_:") => NamedNode::new_unchecked(id).into(),
+            _ => self.blank().into(),
+        };
+        for entry in object.iter() {
+            match entry.key.as_str() {
+                "@type" => {
+                    let types = entry
+                        .value
+                        .as_array()
+                        .unwrap_or(std::slice::from_ref(&entry.value));
+                    for ty in types.iter().filter_map(Value::as_str) {
+                        self.add(
+                            subject.clone(),
+                            rdf::TYPE.as_str(),
+                            NamedNode::new_unchecked(ty),
+                        );
+                    }
+                }
+                key if key.starts_with('@') => {}
+                key => self.add_expanded(&subject, key, &entry.value),
+            }
+        }
+        Some(subject.into())
+    }
+}
+
+/// Builds up a [Document] piece by piece, for tooling that produces governance metadata
+///
+/// The hash algorithm defaults to blake2b-256, as specified by CIP-100
+#[derive(Debug, Clone, Default)]
+pub struct DocumentBuilder {
+    id: Option<IriBuf>,
+    hash_algorithm: Option<HashAlgorithm>,
+    authors: Vec<Author>,
+    references: Vec<Reference>,
+    comment: Option<Localized>,
+    external_updates: Vec<Update>,
+    language: Option<String>,
+}
+
+impl DocumentBuilder {
+    /// Set the IRI identifying the subject of the document, written as its @id
+    pub fn id(mut self, id: IriBuf) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Set the hash algorithm used to hash the document when signing
+    pub fn hash_algorithm(mut self, hash_algorithm: impl Into<HashAlgorithm>) -> Self {
+        self.hash_algorithm = Some(hash_algorithm.into());
+        self
+    }
+
+    /// Add an author who has signed the document
+    pub fn add_author(mut self, author: Author) -> Self {
+        self.authors.push(author);
+        self
+    }
+
+    /// Set the comment in the body of the document
+    pub fn comment(mut self, comment: impl Into<Localized>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    /// Add a reference to the body of the document
+    pub fn add_reference(mut self, reference: Reference) -> Self {
+        self.references.push(reference);
+        self
+    }
+
+    /// Add a place to find updates pertaining to the document
+    pub fn add_external_update(mut self, update: Update) -> Self {
+        self.external_updates.push(update);
+        self
+    }
 
+    /// Set the default language of the body, such as "en-us"; text in the body that doesn't
+    /// specify a language of its own is tagged with it
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Assemble the document
+    pub fn build(self) -> Document {
+        let language = self.language;
+        let tag = |mut text: LangString| {
+            if text.language.is_none() {
+                text.language.clone_from(&language);
+            }
+            text
+        };
+        let references = self
+            .references
+            .into_iter()
+            .map(|reference| Reference {
+                label: tag(reference.label),
+                ..reference
+            })
+            .collect();
+        let external_updates = self
+            .external_updates
+            .into_iter()
+            .map(|update| Update {
+                title: tag(update.title),
+                ..update
+            })
+            .collect();
+        Document {
+            id: self.id,
+            hash_algorithm: self.hash_algorithm.unwrap_or(HashAlgorithm::Blake2b256),
+            authors: self.authors,
+            body: Body {
+                references,
+                comment: self
+                    .comment
+                    .map(|comment| Localized(comment.into_iter().map(tag).collect())),
+                external_updates,
+                language,
+                extensions: HashMap::new(),
+            },
+            extensions: HashMap::new(),
+        }
+    }
+}
+
+/// Collect every property of the node that isn't one of the known fields, in expanded JSON-LD form
+pub(crate) fn parse_extensions<M>(object: &Node, known: &[&str]) -> M
+where
+    M: FromIterator<(IriBuf, Value)>,
+{
+    object
+        .properties()
+        .iter()
+        .filter_map(|(property, values)| {
+            let iri = property.as_iri()?;
+            if known.contains(&iri.as_str()) {
+                return None;
+            }
+            let values = values
+                .iter()
+                .map(|value| value.clone().into_json_with(&()))
+                .collect();
+            // Sort keys, so equal values compare equal however the document ordered them
+            let mut values = Value::Array(values);
+            values.canonicalize();
+            Some((iri.clone(), values))
+        })
+        .collect()
+}
+
+/// The parts shared by every CIP-100 based document: how it's hashed, who signed it, and what it references
+///
+/// CIPs extending CIP-100 keep their body, and the references within it, under their own IRIs, but
+/// otherwise share these fields; parsing them here keeps those IRIs in one place, so a new document
+/// type only needs to parse the rest of its body
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cip100Base {
+    /// The hash algorithm used to hash the document when signing
+    pub hash_algorithm: HashAlgorithm,
+    /// The authors who cosign / attest to this document
+    pub authors: Vec<Author>,
+    /// Any references included in the body of the document
+    pub references: Vec<Reference>,
+}
+
+impl Cip100Base {
+    /// Parse the shared fields of a document whose body is under the given body IRI, with its
+    /// references under the given references IRI
+    pub fn parse(object: &Node, body: &str, references: &str) -> Result<Self> {
+        let hash_algorithm =
+            get_named_string(object, CIP100_FIELDS.hash_algorithm, "hash_algorithm")?
+                .missing("hash_algorithm")?
+                .into();
+        let authors = parse_nodes(object, CIP100_FIELDS.authors, "author")?;
+        let references = parse_references(body_node(object, body)?, references)
+            .map_err(|err| err.within(path_segment(body)))?;
         Ok(Self {
             hash_algorithm,
             authors,
+            references,
+        })
+    }
+}
+
+impl Cip100Base {
+    /// Check every author's witness against the body of the expanded document this was parsed from, kept under
+    /// the given body IRI, hashed with the document's hash algorithm; see [Document::verify_all]
+    pub fn verify_all(&self, object: &Node, body: &str) -> Result<Vec<(String, Result<bool>)>> {
+        Cip100BaseRef::from(self).verify_all(object, body)
+    }
+}
+
+/// The fields of a [Cip100Base], borrowed from a document that holds them, as returned by [Document::base]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cip100BaseRef<'a> {
+    /// The hash algorithm used to hash the document when signing
+    pub hash_algorithm: &'a HashAlgorithm,
+    /// The authors who cosign / attest to this document
+    pub authors: &'a [Author],
+    /// Any references included in the body of the document
+    pub references: &'a [Reference],
+}
+
+impl<'a> Cip100BaseRef<'a> {
+    pub(crate) fn new(
+        hash_algorithm: &'a HashAlgorithm,
+        authors: &'a [Author],
+        references: &'a [Reference],
+    ) -> Self {
+        Self {
+            hash_algorithm,
+            authors,
+            references,
+        }
+    }
+
+    /// Copy the fields out of the document they're borrowed from
+    pub fn into_owned(self) -> Cip100Base {
+        Cip100Base {
+            hash_algorithm: self.hash_algorithm.clone(),
+            authors: self.authors.to_vec(),
+            references: self.references.to_vec(),
+        }
+    }
+
+    /// See [Cip100Base::verify_all]
+    pub fn verify_all(&self, object: &Node, body: &str) -> Result<Vec<(String, Result<bool>)>> {
+        let hash = self
+            .hash_algorithm
+            .digest(canonical_body_of(object, body)?.as_bytes())?;
+        Ok(self
+            .authors
+            .iter()
+            .map(|author| (author.name.to_string(), author.verify(&hash)))
+            .collect())
+    }
+}
+
+impl<'a> From<&'a Cip100Base> for Cip100BaseRef<'a> {
+    fn from(base: &'a Cip100Base) -> Self {
+        Self::new(&base.hash_algorithm, &base.authors, &base.references)
+    }
+}
+
+impl TryFrom<&Node> for Cip100Base {
+    type Error = MetadataError;
+
+    fn try_from(object: &Node) -> Result<Self, Self::Error> {
+        Self::parse(object, CIP100_FIELDS.body, CIP100_FIELDS.body_references)
+    }
+}
+
+/// The URDNA2015 canonical N-Quads of the body of an expanded document, kept under the given body IRI, exactly
+/// as JSON-LD converts it to RDF
+///
+/// Unlike [Document::canonical_body], this works for the body of any CIP extending CIP-100, since nothing in
+/// it needs to be modelled, so it's what signatures of such documents are checked against
+pub fn canonical_body_of(object: &Node, body: &str) -> Result<String> {
+    let mut quads = QuadWriter::default();
+    let root: Subject = quads.blank().into();
+    for value in object.get(&Iri::new(body)?) {
+        quads.add_expanded(&root, body, &value.clone().into_json_with(&()));
+    }
+    let dataset: Dataset = quads.quads.into_iter().collect();
+    rdf_canon::canonicalize(&dataset).map_err(|err| MetadataError::Canonicalize(err.to_string()))
+}
+
+/// Find the body of a CIP-100 based document under the given IRI
+pub(crate) fn body_node<'a>(object: &'a Node, body: &str) -> Result<&'a Node> {
+    object
+        .get_any(&Iri::new(body)?)
+        .missing("body")?
+        .as_node()
+        .wrong_type("body", "an object")
+}
+
+/// Parse a list of references from the given field; CIPs extending CIP-100 use their own IRI for the list,
+/// but share the shape of each reference
+pub(crate) fn parse_references(object: &Node, field: &str) -> Result<Vec<Reference>> {
+    unordered(object, field)?;
+    parse_nodes(object, field, "reference")
+}
+
+/// Reject an ordered @list in a field of the body: the body is hashed as a set of triples, with no order
+/// to its entries, so a list would hash differently than the document it was parsed from
+fn unordered(object: &Node, field: &str) -> Result<()> {
+    match object
+        .get(&Iri::new(field)?)
+        .any(|entry| entry.as_list().is_some())
+    {
+        true => Err(MetadataError::WrongType {
+            field: path_segment(field).to_string(),
+            expected: "a set, rather than an ordered @list",
+            path: String::new(),
+        }),
+        false => Ok(()),
+    }
+}
+
+/// Parse every entry of a list field as a T, failing on any entry that isn't an object, such as a bare string;
+/// errors are located at the entry, such as `authors[2]`
+pub(crate) fn parse_nodes<T>(object: &Node, field: &str, name: &str) -> Result<Vec<T>>
+where
+    T: for<'a> TryFrom<&'a Node, Error = MetadataError>,
+{
+    entries(object, Iri::new(field)?)
+        .enumerate()
+        .map(|(index, entry)| {
+            entry
+                .as_node()
+                .wrong_type(name, "an object")
+                .and_then(T::try_from)
+                .map_err(|err| err.within(format!("{}[{index}]", path_segment(field))))
+        })
+        .collect()
+}
+
+/// How a field is named in the path of an error, which is the term documents usually write it with; for the
+/// CIP fields, that's the fragment of their IRI, such as `authors`
+pub(crate) fn path_segment(field: &str) -> &str {
+    field.rsplit_once('#').map_or(field, |(_, term)| term)
+}
+
+/// Every value of the field, whether it was given as a lone value, a set, or an ordered @list
+fn entries<'a>(
+    object: &'a Node,
+    field: &'a Iri,
+) -> impl Iterator<Item = &'a json_ld::Indexed<Object>> {
+    object.get(&field).flat_map(|entry| match entry.as_list() {
+        Some(list) => list.as_slice(),
+        None => std::slice::from_ref(entry),
+    })
+}
+
+impl TryFrom<&Node> for Document {
+    type Error = MetadataError;
+
+    fn try_from(object: &Node) -> Result<Self, Self::Error> {
+        let base = Cip100Base::try_from(object)?;
+        let body = Body::with_references(body_node(object, CIP100_FIELDS.body)?, base.references)
+            .map_err(|err| err.within(path_segment(CIP100_FIELDS.body)))?;
+        let extensions = parse_extensions(
+            object,
+            &[
+                CIP100_FIELDS.hash_algorithm,
+                CIP100_FIELDS.authors,
+                CIP100_FIELDS.body,
+            ],
+        );
+
+        Ok(Self {
+            id: object.id.as_ref().and_then(|id| id.as_iri()).cloned(),
+            hash_algorithm: base.hash_algorithm,
+            authors: base.authors,
             body,
+            extensions,
         })
     }
 }
 
 impl TryFrom<&Node> for Author {
-    type Error = anyhow::Error;
+    type Error = MetadataError;
 
-    fn try_from(node: &Node) -> std::prelude::v1::Result<Self, Self::Error> {
+    fn try_from(node: &Node) -> Result<Self, Self::Error> {
         let name = node
             .get_any(&Iri::new(CIP100_FIELDS.author_name)?)
-            .context("no author name field")?
-            .as_str()
-            .context("author name is not a string")?
-            .to_string();
+            .missing("author name")?;
+        let name = LangString::from_object(name).wrong_type("author name", "a string")?;
         let witness = node
             .get_any(&Iri::new(CIP100_FIELDS.author_witness)?)
-            .context("no author witness field")?
-            .as_node()
-            .context("witness field isn't an object")?
-            .try_into()?;
+            .map(|witness| {
+                let witness = witness.as_node().wrong_type("witness", "an object")?;
+                Witness::try_from(witness).map_err(|err| err.within("witness"))
+            })
+            .transpose()?;
         Ok(Self { name, witness })
     }
 }
 
 impl TryFrom<&Node> for Witness {
-    type Error = anyhow::Error;
-
-    fn try_from(node: &Node) -> std::prelude::v1::Result<Self, Self::Error> {
-        let algorithm = node
-            .get_any(&Iri::new(CIP100_FIELDS.witness_algorithm)?)
-            .context("no witness algorithm field")?
-            .as_str()
-            .context("witness algorithm is not a string")?
-            .to_string();
-        let public_key = node
-            .get_any(&Iri::new(CIP100_FIELDS.witness_public_key)?)
-            .context("no witness public key field")?
-            .as_str()
-            .context("witness public key is not a string")?
-            .to_string();
-        let signature = node
-            .get_any(&Iri::new(CIP100_FIELDS.witness_signature)?)
-            .context("no witness signature field")?
-            .as_str()
-            .context("witness signature is not a string")?
-            .to_string();
+    type Error = MetadataError;
+
+    fn try_from(node: &Node) -> Result<Self, Self::Error> {
+        let algorithm =
+            get_named_string(node, CIP100_FIELDS.witness_algorithm, "witness algorithm")?
+                .missing("witness algorithm")?
+                .into();
+        let public_key =
+            get_named_string(node, CIP100_FIELDS.witness_public_key, "witness public key")?
+                .missing("witness public key")?;
+        let (key_length, signature_length) = witness_lengths(&algorithm).unzip();
+        let public_key = normalize_hex(&public_key, "witness public key", key_length)?;
+        let signature =
+            get_named_string(node, CIP100_FIELDS.witness_signature, "witness signature")?
+                .missing("witness signature")?;
+        let signature = normalize_hex(&signature, "witness signature", signature_length)?;
         Ok(Self {
             algorithm,
             public_key,
@@ -198,84 +1326,1463 @@ impl TryFrom<&Node> for Witness {
     }
 }
 
+/// The lengths in bytes of the public key and signature for a witness algorithm, if it's one this library knows
+fn witness_lengths(algorithm: &WitnessAlgorithm) -> Option<(usize, usize)> {
+    match algorithm {
+        WitnessAlgorithm::Ed25519 => Some((32, 64)),
+        _ => None,
+    }
+}
+
+/// Strip any 0x prefix and lowercase a hex string, so keys and signatures written by different tools
+/// compare equal; checks it decodes to the given number of bytes, when the algorithm fixes one
+fn normalize_hex(value: &str, field: &str, bytes: Option<usize>) -> Result<String> {
+    let invalid = |reason: String| MetadataError::InvalidValue {
+        field: field.to_string(),
+        reason,
+    };
+    let hex = hex_digits(value).to_ascii_lowercase();
+    let decoded = hex::decode(&hex).map_err(|_| invalid("is not valid hex".to_string()))?;
+    match bytes {
+        Some(bytes) if decoded.len() != bytes => Err(invalid(format!("must be {bytes} bytes"))),
+        _ => Ok(hex),
+    }
+}
+
 impl TryFrom<&Node> for Reference {
-    type Error = anyhow::Error;
-
-    fn try_from(value: &Node) -> std::prelude::v1::Result<Self, Self::Error> {
-        let reference_type = value.types();
-        if reference_type.len() != 1 {
-            bail!("Reference type must have exactly one type");
-        }
-        let reference_type = reference_type[0].as_str();
-        let reference_type = if reference_type == CIP100_FIELDS.reference_type_governance_metadata {
-            ReferenceType::GovernanceMetadata
-        } else if reference_type == CIP100_FIELDS.reference_type_other {
-            ReferenceType::Other
-        } else {
-            bail!("Invalid reference type");
+    type Error = MetadataError;
+
+    fn try_from(value: &Node) -> Result<Self, Self::Error> {
+        // A reference may carry extra types alongside its CIP-100 one, such as a generic
+        // Reference; use the first we recognize, and keep the rest. A lone unrecognized type is kept as Unknown
+        let types = value.types();
+        let recognized = types
+            .iter()
+            .find_map(|reference_type| match reference_type.as_str() {
+                t if t == CIP100_FIELDS.reference_type_governance_metadata => {
+                    Some(ReferenceType::GovernanceMetadata)
+                }
+                t if t == CIP100_FIELDS.reference_type_other => Some(ReferenceType::Other),
+                _ => None,
+            });
+        let reference_type = match (recognized, types) {
+            (Some(reference_type), _) => reference_type,
+            (None, [reference_type]) => match reference_type.as_iri() {
+                Some(iri) => ReferenceType::Unknown(iri.clone()),
+                None => {
+                    return Err(MetadataError::InvalidValue {
+                        field: "reference type".to_string(),
+                        reason: format!("{} is not an IRI", reference_type.as_str()),
+                    })
+                }
+            },
+            (None, []) => {
+                return Err(MetadataError::MissingField {
+                    field: "reference type".to_string(),
+                    path: String::new(),
+                })
+            }
+            (None, _) => {
+                return Err(MetadataError::InvalidValue {
+                    field: "reference type".to_string(),
+                    reason: "does not include a recognized reference type".to_string(),
+                })
+            }
         };
         let label = value
             .get_any(&Iri::new(CIP100_FIELDS.reference_label)?)
-            .context("no reference label field")?
-            .as_str()
-            .context("reference label is not a string")?
-            .to_string();
+            .missing("reference label")?;
+        let label = LangString::from_object(label).wrong_type("reference label", "a string")?;
         let iri = value
             .get_any(&Iri::new(CIP100_FIELDS.reference_uri)?)
-            .context("no reference uri field")?
-            .as_str()
-            .context("reference uri is not a string")?;
-        let iri = IriBuf::new(iri.to_string()).context("reference uri is not a valid IRI")?;
+            .missing("reference uri")?;
+        let iri = iri_value(iri).wrong_type("reference uri", "a string or an @id")?;
+        let iri = IriBuf::new(iri.to_string()).map_err(|_| MetadataError::InvalidIri {
+            field: "reference uri".to_string(),
+            iri: iri.to_string(),
+        })?;
+        let other_types = types
+            .iter()
+            .filter(|other| other.as_str() != reference_type.as_iri().as_str())
+            .map(|other| {
+                other
+                    .as_iri()
+                    .cloned()
+                    .ok_or_else(|| MetadataError::InvalidValue {
+                        field: "reference type".to_string(),
+                        reason: format!("{} is not an IRI", other.as_str()),
+                    })
+            })
+            .collect::<Result<_>>()?;
+        let extensions = parse_extensions(
+            value,
+            &[CIP100_FIELDS.reference_label, CIP100_FIELDS.reference_uri],
+        );
         Ok(Self {
             reference_type,
             label,
             uri: iri,
+            other_types,
+            extensions,
         })
     }
 }
 
 impl TryFrom<&Node> for Update {
-    type Error = anyhow::Error;
+    type Error = MetadataError;
 
-    fn try_from(value: &Node) -> std::prelude::v1::Result<Self, Self::Error> {
+    fn try_from(value: &Node) -> Result<Self, Self::Error> {
         let title = value
             .get_any(&Iri::new(CIP100_FIELDS.update_title)?)
-            .context("no update title field")?
-            .as_str()
-            .context("update title is not a string")?
-            .to_string();
+            .missing("update title")?;
+        let title = LangString::from_object(title).wrong_type("update title", "a string")?;
         let iri = value
             .get_any(&Iri::new(CIP100_FIELDS.update_uri)?)
-            .context("no update uri field")?
-            .as_str()
-            .context("update uri is not a string")?;
-        let iri = IriBuf::new(iri.to_string()).context("update uri is not a valid IRI")?;
-        Ok(Self { title, uri: iri })
+            .missing("update uri")?;
+        let iri = iri_value(iri).wrong_type("update uri", "a string or an @id")?;
+        let iri = IriBuf::new(iri.to_string()).map_err(|_| MetadataError::InvalidIri {
+            field: "update uri".to_string(),
+            iri: iri.to_string(),
+        })?;
+        let extensions = parse_extensions(
+            value,
+            &[CIP100_FIELDS.update_title, CIP100_FIELDS.update_uri],
+        );
+        Ok(Self {
+            title,
+            uri: iri,
+            extensions,
+        })
     }
 }
 
 impl TryFrom<&Node> for Body {
-    type Error = anyhow::Error;
-
-    fn try_from(value: &Node) -> std::prelude::v1::Result<Self, Self::Error> {
-        let references = value
-            .get(&Iri::new(CIP100_FIELDS.body_references)?)
-            .map(|reference| reference.inner().as_node().unwrap().try_into())
-            .collect::<Result<Vec<Reference>>>()?;
-        let comment = value
-            .get_any(&Iri::new(CIP100_FIELDS.body_comment)?)
-            .context("no body comment field")?
-            .as_str()
-            .context("body comment is not a string")?
-            .to_string();
-        let external_updates = value
-            .get(&Iri::new(CIP100_FIELDS.body_external_updates)?)
-            .map(|update| update.inner().as_node().unwrap().try_into())
-            .collect::<Result<Vec<Update>>>()?;
+    type Error = MetadataError;
+
+    fn try_from(value: &Node) -> Result<Self, Self::Error> {
+        Self::with_references(
+            value,
+            parse_references(value, CIP100_FIELDS.body_references)?,
+        )
+    }
+}
+
+impl Body {
+    /// Parse the rest of the body, given references that have already been parsed by [Cip100Base]
+    pub(crate) fn with_references(value: &Node, references: Vec<Reference>) -> Result<Self> {
+        let comment = Localized::parse(value, CIP100_FIELDS.body_comment, "body comment")?;
+        // URIs are plain strings, so they're only ever tagged with the default language, while
+        // text may carry its own; only fall back on the comment if there are no URIs to go by
+        let uri_language = |list: &str, uri: &str| -> Option<String> {
+            let uri = Iri::new(uri).ok()?;
+            entries(value, Iri::new(list).ok()?)
+                .filter_map(|item| item.as_node()?.get_any(&uri))
+                // A uri given as an @id is an IRI, not text, so has no language to go by
+                .filter(|uri| uri.as_value().is_some())
+                .map(|uri| uri.language())
+                .next()?
+                .map(|language| language.as_str().to_string())
+        };
+        let language = uri_language(CIP100_FIELDS.body_references, CIP100_FIELDS.reference_uri)
+            .or_else(|| {
+                uri_language(
+                    CIP100_FIELDS.body_external_updates,
+                    CIP100_FIELDS.update_uri,
+                )
+            })
+            .or_else(|| comment.as_ref()?.first()?.language.clone());
+        unordered(value, CIP100_FIELDS.body_external_updates)?;
+        let external_updates = parse_nodes(
+            value,
+            CIP100_FIELDS.body_external_updates,
+            "external update",
+        )?;
+        let extensions = parse_extensions(
+            value,
+            &[
+                CIP100_FIELDS.body_references,
+                CIP100_FIELDS.body_comment,
+                CIP100_FIELDS.body_external_updates,
+            ],
+        );
         Ok(Self {
             references,
             comment,
             external_updates,
+            language,
+            extensions,
         })
     }
 }
+
+// The Display implementations below are short, human readable summaries for logs and CLIs; they're
+// best effort, and may change between versions, so don't parse them
+
+/// How much of the comment to show when displaying a document
+const COMMENT_PREVIEW_CHARS: usize = 60;
+
+impl fmt::Display for ReferenceType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReferenceType::GovernanceMetadata => f.write_str("governance metadata"),
+            ReferenceType::Other => f.write_str("other"),
+            ReferenceType::Unknown(iri) => f.write_str(iri.as_str()),
+        }
+    }
+}
+
+/// The author's name, followed by the algorithm and the start of the public key they signed with
+impl fmt::Display for Author {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.witness {
+            Some(witness) => {
+                let key: String = witness.public_key.chars().take(8).collect();
+                write!(f, "{} ({} {})", self.name, witness.algorithm, key)
+            }
+            None => write!(f, "{} (unsigned)", self.name),
+        }
+    }
+}
+
+impl fmt::Display for Reference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}] <{}>", self.label, self.reference_type, self.uri)
+    }
+}
+
+impl fmt::Display for Update {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} <{}>", self.title, self.uri)
+    }
+}
+
+/// The start of the comment, who signed the document, and how many references and updates it has
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let comment = self
+            .body
+            .comment
+            .as_ref()
+            .map(Localized::to_string)
+            .unwrap_or_default();
+        let mut preview: String = comment.chars().take(COMMENT_PREVIEW_CHARS).collect();
+        if preview.len() < comment.len() {
+            preview.push('…');
+        }
+        write!(f, "\"{}\"", preview)?;
+        let authors: Vec<_> = self
+            .authors
+            .iter()
+            .map(|author| author.name.value())
+            .collect();
+        match authors.len() {
+            0 => f.write_str(", unsigned")?,
+            _ => write!(f, ", by {}", authors.join(", "))?,
+        }
+        let plural = |count: usize, noun: &str| match count {
+            1 => format!("1 {}", noun),
+            count => format!("{} {}s", count, noun),
+        };
+        write!(
+            f,
+            "; {}, {}",
+            plural(self.body.references.len(), "reference"),
+            plural(self.body.external_updates.len(), "update")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::{en, example_document, reference, EXAMPLE};
+    use anyhow::Result;
+
+    // Signed with the ed25519 key whose seed is the bytes 0..32, over blake2b-256("cardano-governance-metadata")
+    const TEST_HASH: &str = "b322a34a7ebe77678e586eb55fe301955e15ab0b27f1f6c736f4d698f6534f03";
+
+    fn test_witness() -> Witness {
+        Witness {
+            algorithm: WitnessAlgorithm::Ed25519,
+            public_key: "03a107bff3ce10be1d70dd18e74bc09967e4d6309ba50d5f1ddc8664125531b8".to_string(),
+            signature: "6baa2823c7f2e1397bc735854fe34ca72804752eb85198472b195efd3fe4630bb5bc448bc992f20a4ca22cb0c4284a5119018b8545cf3928b7d660e74ac4fe05".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_witness_verify() -> Result<()> {
+        let hash = hex::decode(TEST_HASH)?;
+        assert!(test_witness().verify(&hash)?);
+
+        let mut mutated = test_witness();
+        mutated.signature.replace_range(0..2, "6c");
+        assert!(!mutated.verify(&hash)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_witness_sign() -> Result<()> {
+        let signing_key = SigningKey::from_bytes(&std::array::from_fn(|i| i as u8));
+        let hash = hex::decode(TEST_HASH)?;
+        assert_eq!(Witness::sign(&signing_key, &hash), test_witness());
+
+        // Author and sign a document end to end
+        let draft = Document::builder()
+            .comment("Signed in Rust")
+            .language("en-us")
+            .build();
+        let witness = Witness::sign(&signing_key, &draft.canonical_hash()?);
+        let document = Document::builder()
+            .comment("Signed in Rust")
+            .language("en-us")
+            .add_author(Author {
+                name: en("Alice"),
+                witness: Some(witness),
+            })
+            .build();
+        let results = document.verify_all()?;
+        assert_eq!(results.len(), 1);
+        assert!(matches!(results[0].1, Ok(true)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_canonical_hash() -> Result<()> {
+        let document = example_document();
+        let hash = document.canonical_hash()?;
+        assert_eq!(
+            hex::encode(hash),
+            "cc4ab8ead604ddb498ed4b2916af7b454c65ac783b5d836fddf388e72a40eccb"
+        );
+
+        let author = &document.authors[0];
+        assert!(author.verify(&hash)?);
+        assert_eq!(
+            document.hash_with(HashAlgorithm::Blake2b256)?,
+            hash.to_vec()
+        );
+        assert_eq!(document.hash_with(HashAlgorithm::Sha256)?.len(), 32);
+        assert!(document.hash_with("sha-1".into()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_document_builder() -> Result<()> {
+        let expected = example_document();
+        let mut references = expected.body.references.into_iter();
+        let mut updates = expected.body.external_updates.into_iter();
+        let mut authors = expected.authors.into_iter();
+        let document = Document::builder()
+            .comment("This is a test vector for CIP-100")
+            .add_reference(references.next().unwrap())
+            .add_external_update(updates.next().unwrap())
+            .add_author(authors.next().unwrap())
+            .language("en-us")
+            .build();
+        assert_eq!(document, example_document());
+        assert!(document.authors[0].verify(&document.canonical_hash()?)?);
+
+        // A copy hashes the same, so it can be handed on and verified independently
+        let copy = document.clone();
+        assert_eq!(copy, document);
+        assert_eq!(copy.canonical_hash()?, document.canonical_hash()?);
+
+        let document = Document::builder().hash_algorithm("sha256").build();
+        assert_eq!(document.hash_algorithm, HashAlgorithm::Sha256);
+        assert!(document.authors.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_to_jsonld_round_trip() -> Result<()> {
+        let base = url::Url::parse("https://example.com/metadata.jsonld")?;
+
+        let document = example_document();
+        let jsonld = document.to_jsonld()?;
+        let parsed: Document = crate::parse_str(&jsonld, base.clone()).await?;
+        assert_eq!(parsed, document);
+        assert!(parsed.authors[0].verify(&parsed.canonical_hash()?)?);
+
+        let untagged = Document::builder()
+            .comment("No language here")
+            .add_reference(reference(
+                ReferenceType::GovernanceMetadata,
+                "CIP-100",
+                "https://example.com/cip-100.jsonld",
+            ))
+            .build();
+        let parsed: Document = crate::parse_str(&untagged.to_jsonld()?, base.clone()).await?;
+        assert_eq!(parsed, untagged);
+
+        let mut mixed = example_document();
+        mixed.body.comment =
+            Some(LangString::new("Ceci est un vecteur de test", Some("fr".to_string())).into());
+        mixed.authors[0].name = "Pi Lanningham".into();
+        let parsed: Document = crate::parse_str(&mixed.to_jsonld()?, base).await?;
+        assert_eq!(parsed, mixed);
+        assert_eq!(parsed.body.comment.unwrap()[0].language(), Some("fr"));
+        assert_eq!(parsed.authors[0].name.language(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_canonicalization_methods() -> Result<()> {
+        let document = Document::builder()
+            .comment("Ünïcödé \"quoted\"")
+            .add_reference(reference(
+                ReferenceType::Other,
+                "Spec",
+                "https://example.com/spec",
+            ))
+            .build();
+
+        assert_eq!(
+            document.canonical_body_with(CanonicalizationMethod::default())?,
+            document.canonical_body()?
+        );
+        assert_eq!(
+            document.canonical_hash_with(
+                CanonicalizationMethod::Urdna2015,
+                HashAlgorithm::Blake2b256
+            )?,
+            document.canonical_hash()?
+        );
+
+        // Keys are sorted, and there's no whitespace between tokens
+        let jcs = r#"{"comment":"Ünïcödé \"quoted\"","externalUpdates":[],"references":[{"@type":"Other","label":"Spec","uri":"https://example.com/spec"}]}"#;
+        assert_eq!(
+            document.canonical_body_with(CanonicalizationMethod::Jcs)?,
+            jcs
+        );
+        assert_eq!(
+            document.canonical_hash_with(CanonicalizationMethod::Jcs, HashAlgorithm::Blake2b256)?,
+            HashAlgorithm::Blake2b256.digest(jcs.as_bytes())?
+        );
+        assert_ne!(
+            document.canonical_hash_with(CanonicalizationMethod::Jcs, HashAlgorithm::Blake2b256)?,
+            document.canonical_hash()?
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_jsonld_output_modes() -> Result<()> {
+        let base = url::Url::parse("https://example.com/metadata.jsonld")?;
+        let document = example_document();
+
+        let compact = document.to_jsonld()?;
+        assert!(!compact.contains('\n'));
+        assert_eq!(compact, document.clone().to_jsonld()?);
+        let parsed: Document = crate::parse_str(&compact, base.clone()).await?;
+        assert_eq!(parsed, document);
+        assert_eq!(parsed.to_jsonld()?, compact);
+
+        let pretty = document.to_jsonld_pretty()?;
+        assert!(pretty.lines().count() > 1);
+        let parsed: Document = crate::parse_str(&pretty, base).await?;
+        assert_eq!(parsed, document);
+
+        // Extensions are held in a HashMap, but are written in the same order however they were added
+        let extension = |n: i32| {
+            (
+                IriBuf::new(format!("https://example.com/vocab#extension{n}")).unwrap(),
+                json!([{ "@value": n }]),
+            )
+        };
+        let mut forwards = document.clone();
+        let mut backwards = document.clone();
+        forwards.extensions = (0..16).map(extension).collect();
+        backwards.extensions = (0..16).rev().map(extension).collect();
+        assert_eq!(forwards.to_jsonld()?, backwards.to_jsonld()?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unknown_reference_type() -> Result<()> {
+        let base = url::Url::parse("https://example.com/metadata.jsonld")?;
+
+        let mut document = example_document();
+        let unknown = IriBuf::new("https://example.com/vocab#VideoReference".to_string()).unwrap();
+        document.body.references[0].reference_type = ReferenceType::Unknown(unknown.clone());
+        let parsed: Document = crate::parse_str(&document.to_jsonld()?, base).await?;
+        assert_eq!(
+            parsed.body.references[0].reference_type,
+            ReferenceType::Unknown(unknown.clone())
+        );
+        assert_eq!(parsed.body.references[0].reference_type.as_iri(), &unknown);
+        assert_eq!(
+            ReferenceType::Other.as_iri().as_str(),
+            CIP100_FIELDS.reference_type_other
+        );
+        Ok(())
+    }
+
+    /// A document, along with the canonical body of the expanded JSON-LD it was parsed from, to check the
+    /// parsed document hashes to the same thing
+    struct WithExpandedBody(Document, String);
+
+    impl TryFrom<&Node> for WithExpandedBody {
+        type Error = MetadataError;
+
+        fn try_from(node: &Node) -> Result<Self, Self::Error> {
+            Ok(Self(
+                node.try_into()?,
+                canonical_body_of(node, CIP100_FIELDS.body)?,
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_multiple_reference_types() -> Result<()> {
+        let base = url::Url::parse("https://example.com/metadata.jsonld")?;
+
+        let extra = EXAMPLE.replace(
+            r#""@type": "Other""#,
+            r#""@type": ["https://example.com/vocab#Reference", "Other"]"#,
+        );
+        let WithExpandedBody(parsed, expanded_body) =
+            crate::parse_str(&extra, base.clone()).await?;
+        let reference = &parsed.body.references[0];
+        assert_eq!(reference.reference_type, ReferenceType::Other);
+        assert_eq!(
+            reference.other_types,
+            vec![IriBuf::new("https://example.com/vocab#Reference".to_string()).unwrap()]
+        );
+        // The extra type is part of what was signed, so it changes the hash, just as it does the expanded body's
+        assert_eq!(parsed.canonical_body()?, expanded_body);
+        assert_ne!(
+            parsed.canonical_hash()?,
+            example_document().canonical_hash()?
+        );
+        let round_tripped: Document = crate::parse_str(&parsed.to_jsonld()?, base.clone()).await?;
+        assert_eq!(round_tripped, parsed);
+
+        let unrecognized = EXAMPLE.replace(
+            r#""@type": "Other""#,
+            r#""@type": ["https://example.com/vocab#Reference", "https://example.com/vocab#Video"]"#,
+        );
+        let result: crate::Result<Document> = crate::parse_str(&unrecognized, base).await;
+        assert!(matches!(
+            result,
+            Err(MetadataError::InvalidValue { field, .. }) if field == "reference type"
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reference_and_update_extensions() -> Result<()> {
+        let base = url::Url::parse("https://example.com/metadata.jsonld")?;
+
+        let extra = EXAMPLE
+            .replace(
+                r#""label": "CIP-100","#,
+                r#""label": "CIP-100", "https://example.com/vocab#section": "Specification","#,
+            )
+            .replace(
+                r#""title": "Blog","#,
+                r#""title": "Blog", "https://example.com/vocab#weekly": true,"#,
+            );
+        let WithExpandedBody(parsed, expanded_body) =
+            crate::parse_str(&extra, base.clone()).await?;
+        let section = IriBuf::new("https://example.com/vocab#section".to_string()).unwrap();
+        assert_eq!(
+            parsed.body.references[0].extensions.get(&section),
+            Some(&json!([{ "@language": "en-us", "@value": "Specification" }]))
+        );
+        let weekly = IriBuf::new("https://example.com/vocab#weekly".to_string()).unwrap();
+        assert_eq!(
+            parsed.body.external_updates[0].extensions.get(&weekly),
+            Some(&json!([{ "@value": true }]))
+        );
+        // Both properties are part of what was signed, so they change the hash, just as they do the expanded body's
+        assert_eq!(parsed.canonical_body()?, expanded_body);
+        assert_ne!(
+            parsed.canonical_hash()?,
+            example_document().canonical_hash()?
+        );
+        let round_tripped: Document = crate::parse_str(&parsed.to_jsonld()?, base).await?;
+        assert_eq!(round_tripped, parsed);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_optional_comment() -> Result<()> {
+        let base = url::Url::parse("https://example.com/metadata.jsonld")?;
+        let document: Document = crate::parse_str(EXAMPLE, base.clone()).await?;
+        assert_eq!(
+            document.body.comment,
+            Some(en("This is a test vector for CIP-100").into())
+        );
+
+        // Parsing a body without a comment is left to validation to reject, so extensions can reuse it
+        let content = EXAMPLE.replace(r#""comment": "This is a test vector for CIP-100","#, "");
+        let document: Document = crate::parse_str(&content, base.clone()).await?;
+        assert_eq!(document.body.comment, None);
+        assert_eq!(document.body.language.as_deref(), Some("en-us"));
+        let round_tripped: Document = crate::parse_str(&document.to_jsonld()?, base).await?;
+        assert_eq!(round_tripped, document);
+        assert_eq!(Document::builder().build().body.comment, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_localized_comment() -> Result<()> {
+        let base = url::Url::parse("https://example.com/metadata.jsonld")?;
+        let content = EXAMPLE
+            .replace(
+                r#""comment": "CIP100:comment""#,
+                r#""comment": { "@id": "CIP100:comment", "@container": "@language" }"#,
+            )
+            .replace(
+                r#""comment": "This is a test vector for CIP-100""#,
+                r#""comment": { "en-us": "This is a test vector", "fr": "Ceci est un vecteur de test" }"#,
+            );
+        let document: Document = crate::parse_str(&content, base.clone()).await?;
+        let comment = document.body.comment.as_ref().unwrap();
+        assert_eq!(comment.len(), 2);
+        let best = |tag| comment.best_for_language(tag).map(LangString::value);
+        assert_eq!(best("fr-CA"), Some("Ceci est un vecteur de test"));
+        assert_eq!(best("en"), Some("This is a test vector"));
+
+        let parsed: Document = crate::parse_str(&document.to_jsonld()?, base).await?;
+        assert_eq!(parsed, document);
+        assert_eq!(parsed.canonical_hash()?, document.canonical_hash()?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_all() -> Result<()> {
+        let mut document = example_document();
+        let mut forged = document.authors[0].clone();
+        forged.name = "Forger".into();
+        forged
+            .witness
+            .as_mut()
+            .unwrap()
+            .signature
+            .replace_range(0..2, "00");
+        let mut unknown = document.authors[0].clone();
+        unknown.name = "Future".into();
+        unknown.witness.as_mut().unwrap().algorithm = "dilithium".into();
+        document.authors.extend([forged, unknown]);
+
+        let results = document.verify_all()?;
+        let names: Vec<_> = results.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["Pi Lanningham", "Forger", "Future"]);
+        assert!(matches!(results[0].1, Ok(true)));
+        assert!(matches!(results[1].1, Ok(false)));
+        assert!(matches!(
+            results[2].1,
+            Err(MetadataError::UnsupportedAlgorithm(_))
+        ));
+
+        document.hash_algorithm = HashAlgorithm::from("md5");
+        assert!(document.verify_all().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_authors_verified() -> Result<()> {
+        let mut document = example_document();
+        let mut forged = document.authors[0].clone();
+        forged.name = en("Mallory");
+        if let Some(witness) = &mut forged.witness {
+            witness.signature = "00".repeat(64);
+        }
+        document.authors.push(forged);
+
+        let mut verified = document.authors_verified()?;
+        let (author, result) = verified.next().unwrap();
+        assert_eq!(author.name.value(), "Pi Lanningham");
+        assert!(result?);
+        let (author, result) = verified.next().unwrap();
+        assert_eq!(author.name.value(), "Mallory");
+        assert!(!result?);
+        assert!(verified.next().is_none());
+
+        // Stopping at the first valid signature never checks the rest
+        let key = &document.authors[0].witness.as_ref().unwrap().public_key;
+        let signer = document.authors_verified()?.find(|(author, result)| {
+            matches!(result, Ok(true))
+                && author
+                    .witness
+                    .as_ref()
+                    .is_some_and(|w| &w.public_key == key)
+        });
+        assert_eq!(signer.map(|(author, _)| author), document.authors.first());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unsigned_author() -> Result<()> {
+        let base = url::Url::parse("https://example.com/draft.jsonld")?;
+
+        let mut draft = example_document();
+        draft.authors[0].witness = None;
+        let jsonld = draft.to_jsonld()?;
+        let parsed: Document = crate::parse_str(&jsonld, base).await?;
+        assert_eq!(parsed, draft);
+        assert!(!parsed.authors[0].verify(&parsed.canonical_hash()?)?);
+        assert!(matches!(parsed.verify_all()?[0].1, Ok(false)));
+        assert_eq!(parsed.authors[0].to_string(), "Pi Lanningham (unsigned)");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_witness_hex_normalized() -> Result<()> {
+        let base = url::Url::parse("https://example.com/metadata.jsonld")?;
+        let document = example_document();
+        let witness = document.authors[0].witness.clone().unwrap();
+        let jsonld = document.to_jsonld()?;
+
+        let shouted = jsonld
+            .replace(
+                &witness.public_key,
+                &format!("0x{}", witness.public_key.to_uppercase()),
+            )
+            .replace(
+                &witness.signature,
+                &format!(
+                    "0X{}{}",
+                    witness.signature[..64].to_uppercase(),
+                    &witness.signature[64..]
+                ),
+            );
+        assert_ne!(shouted, jsonld);
+        let parsed: Document = crate::parse_str(&shouted, base.clone()).await?;
+        assert_eq!(parsed, document);
+        assert!(parsed.authors[0].verify(&parsed.canonical_hash()?)?);
+
+        let not_hex = jsonld.replace(&witness.public_key, "0xnot-hex");
+        let short = jsonld.replace(&witness.signature, &witness.signature[..126]);
+        for (content, field) in [
+            (not_hex, "witness public key"),
+            (short, "witness signature"),
+        ] {
+            let result = crate::parse_str::<Document>(&content, base.clone()).await;
+            assert!(
+                matches!(&result, Err(MetadataError::InvalidValue { field: f, .. }) if f == field),
+                "{result:?}"
+            );
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_string_and_id_values() -> Result<()> {
+        // A second term mapping to the same field, as an @id, leaves the field with both an @id and a string
+        // once expanded; the string is the field's value, wherever the @id lands among them
+        let base = url::Url::parse("https://example.com/metadata.jsonld")?;
+        let mut example: serde_json::Value = serde_json::from_str(EXAMPLE)?;
+        example["@context"]["algorithm"] =
+            serde_json::json!({ "@id": "CIP100:hashAlgorithm", "@type": "@id" });
+        example["algorithm"] = serde_json::json!("https://example.com/blake2b-256");
+        example["@context"]["authors"]["@context"]["witness"]["@context"]["key"] =
+            serde_json::json!({ "@id": "CIP100:publicKey", "@type": "@id" });
+        example["authors"][0]["witness"]["key"] = serde_json::json!("https://example.com/key");
+        let parsed: Document = crate::parse_str(&example.to_string(), base).await?;
+        assert_eq!(parsed, example_document());
+        assert!(parsed.authors[0].verify(&parsed.canonical_hash()?)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_display() {
+        let document = example_document();
+        assert_eq!(
+            document.to_string(),
+            "\"This is a test vector for CIP-100\", by Pi Lanningham; 1 reference, 1 update"
+        );
+        assert_eq!(
+            document.authors[0].to_string(),
+            "Pi Lanningham (ed25519 7ea09a34)"
+        );
+        assert_eq!(
+            document.body.references[0].to_string(),
+            "CIP-100 [other] <https://github.com/cardano-foundation/CIPs/blob/master/CIP-0100/README.md>"
+        );
+        assert_eq!(
+            document.body.external_updates[0].to_string(),
+            "Blog <https://314pool.com>"
+        );
+
+        let long = Document::builder().comment("a".repeat(100)).build();
+        assert_eq!(
+            long.to_string(),
+            format!("\"{}…\", unsigned; 0 references, 0 updates", "a".repeat(60))
+        );
+    }
+
+    #[test]
+    fn test_find_references() {
+        let document = Document::builder()
+            .comment("References")
+            .add_reference(reference(
+                ReferenceType::Other,
+                "Spec",
+                "https://example.com/spec",
+            ))
+            .add_reference(reference(
+                ReferenceType::GovernanceMetadata,
+                "Prior",
+                "https://example.com/prior.jsonld",
+            ))
+            .add_reference(reference(
+                ReferenceType::Other,
+                "Spec",
+                "https://example.com/spec-v2",
+            ))
+            .build();
+        let body = &document.body;
+
+        // Duplicate labels return the first match
+        assert_eq!(
+            body.reference_by_label("Spec").map(|r| r.uri.as_str()),
+            Some("https://example.com/spec")
+        );
+        assert!(body.reference_by_label("Missing").is_none());
+
+        let others: Vec<_> = body
+            .references_of_type(ReferenceType::Other)
+            .map(|r| r.uri.as_str())
+            .collect();
+        assert_eq!(
+            others,
+            vec!["https://example.com/spec", "https://example.com/spec-v2"]
+        );
+        assert_eq!(
+            body.references_of_type(ReferenceType::GovernanceMetadata)
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_all_uris() {
+        let uris: Vec<_> = example_document()
+            .all_uris()
+            .into_iter()
+            .map(|uri| uri.to_string())
+            .collect();
+        assert_eq!(
+            uris,
+            vec![
+                "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0100/README.md",
+                "https://314pool.com",
+            ]
+        );
+        assert!(Document::builder().build().all_uris().is_empty());
+    }
+
+    #[test]
+    fn test_normalized_uri() {
+        let reference = |uri: &str| reference(ReferenceType::Other, "Spec", uri);
+        let references = [
+            reference("https://example.com/spec/v1"),
+            reference("HTTPS://EXAMPLE.com/spec/v1/"),
+            reference("https://example.com:443/spec/./draft/../v%31"),
+            reference("https://example.com/spec/v2"),
+        ];
+        let unique: std::collections::BTreeSet<_> =
+            references.iter().map(Reference::normalized_uri).collect();
+        assert_eq!(unique.len(), 2);
+        // The uris themselves are kept as written
+        assert_eq!(references[1].uri.as_str(), "HTTPS://EXAMPLE.com/spec/v1/");
+
+        let update = Update {
+            title: "Blog".into(),
+            uri: IriBuf::new("https://Example.com/%7eblog".to_string()).unwrap(),
+            extensions: Default::default(),
+        };
+        assert_eq!(
+            update.normalized_uri().as_str(),
+            "https://example.com/~blog"
+        );
+    }
+
+    #[test]
+    fn test_semantically_eq() {
+        let reference = |label: &str| {
+            reference(
+                ReferenceType::Other,
+                label,
+                &format!("https://example.com/{label}"),
+            )
+        };
+        let update = |title: &str| Update {
+            title: title.into(),
+            uri: IriBuf::new(format!("https://example.com/{title}")).unwrap(),
+            extensions: Default::default(),
+        };
+        let document = |references: [&str; 2], updates: [&str; 2]| {
+            let mut builder = Document::builder().comment("Reordered");
+            for label in references {
+                builder = builder.add_reference(reference(label));
+            }
+            for title in updates {
+                builder = builder.add_external_update(update(title));
+            }
+            builder.build()
+        };
+
+        let original = document(["a", "b"], ["blog", "feed"]);
+        let reordered = document(["b", "a"], ["feed", "blog"]);
+        assert_ne!(original, reordered);
+        assert!(original.semantically_eq(&reordered));
+        assert!(reordered.semantically_eq(&original));
+        assert_eq!(
+            original.canonical_hash().unwrap(),
+            reordered.canonical_hash().unwrap()
+        );
+
+        assert!(!original.semantically_eq(&document(["a", "c"], ["blog", "feed"])));
+        assert!(!original.semantically_eq(&document(["a", "b"], ["blog", "news"])));
+        let mut commented = reordered.clone();
+        commented.body.comment = Some("Something else".into());
+        assert!(!original.semantically_eq(&commented));
+    }
+
+    #[test]
+    fn test_reference_sets() {
+        let references = [
+            reference(ReferenceType::Other, "Spec", "https://example.com/spec"),
+            reference(
+                ReferenceType::GovernanceMetadata,
+                "Prior",
+                "https://example.com/prior.jsonld",
+            ),
+            reference(ReferenceType::Other, "Blog", "https://example.com/blog"),
+            reference(ReferenceType::Other, "Spec", "https://example.com/spec"),
+        ];
+
+        let hashed: std::collections::HashSet<_> = references.iter().cloned().collect();
+        assert_eq!(hashed.len(), 3);
+
+        // Ordered by type, then label, then uri
+        let ordered: std::collections::BTreeSet<_> = references.into_iter().collect();
+        let labels: Vec<_> = ordered.iter().map(|r| r.label.value()).collect();
+        assert_eq!(labels, vec!["Prior", "Blog", "Spec"]);
+    }
+
+    #[tokio::test]
+    async fn test_cip100_base() -> Result<()> {
+        let base = url::Url::parse("https://example.com/metadata.jsonld")?;
+        let parsed: Cip100Base = crate::parse_str(EXAMPLE, base.clone()).await?;
+        assert_eq!(parsed, example_document().base().into_owned());
+        assert_eq!(Cip100BaseRef::from(&parsed), example_document().base());
+
+        // Other CIPs keep their references under their own IRIs
+        let document: crate::GovernanceActionDocument =
+            crate::parse_str(include_str!("../tests/fixtures/cip108-example.json"), base).await?;
+        let base = document.base();
+        assert_eq!(*base.hash_algorithm, HashAlgorithm::Blake2b256);
+        assert_eq!(base.references, document.body.references);
+        assert_eq!(base.references.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_extensions() -> Result<()> {
+        let base = url::Url::parse("https://example.com/metadata.jsonld")?;
+        let content = EXAMPLE
+            .replace(
+                r#""comment": "This is a test vector for CIP-100","#,
+                r#""comment": "This is a test vector for CIP-100",
+                "https://example.com/vendor#rating": 5,
+                "https://example.com/vendor#reviewer": { "https://schema.org/name": "Alice" },"#,
+            )
+            .replace(
+                r#""hashAlgorithm": "blake2b-256","#,
+                r#""hashAlgorithm": "blake2b-256", "https://example.com/vendor#revision": "3","#,
+            );
+        let document: Document = crate::parse_str(&content, base.clone()).await?;
+        let iri = |iri: &str| IriBuf::new(iri.to_string()).unwrap();
+        assert_eq!(document.body.extensions.len(), 2);
+        assert_eq!(
+            document.body.extensions[&iri("https://example.com/vendor#rating")],
+            json!([{ "@value": 5 }])
+        );
+        assert_eq!(
+            document.extensions[&iri("https://example.com/vendor#revision")],
+            json!([{ "@language": "en-us", "@value": "3" }])
+        );
+
+        // Extensions are part of the body, so they're covered by the authors' signatures
+        let canonical = document.canonical_body()?;
+        assert!(canonical.contains(
+            r#"<https://example.com/vendor#rating> "5"^^<http://www.w3.org/2001/XMLSchema#integer>"#
+        ));
+        assert!(canonical.contains(r#"<https://schema.org/name> "Alice"@en-us"#));
+        assert_ne!(
+            document.canonical_hash()?,
+            example_document().canonical_hash()?
+        );
+
+        let parsed: Document = crate::parse_str(&document.to_jsonld()?, base).await?;
+        assert_eq!(parsed, document);
+        assert_eq!(parsed.canonical_hash()?, document.canonical_hash()?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_document_id() -> Result<()> {
+        let base = url::Url::parse("https://example.com/metadata.jsonld")?;
+        let with_id = |id: &str| {
+            EXAMPLE.replacen(
+                r#""hashAlgorithm": "blake2b-256","#,
+                &format!(r#""@id": "{id}", "hashAlgorithm": "blake2b-256","#),
+                1,
+            )
+        };
+        let document: Document =
+            crate::parse_str(&with_id("https://example.com/drep/alice"), base.clone()).await?;
+        let id = IriBuf::new("https://example.com/drep/alice".to_string()).unwrap();
+        assert_eq!(document.id, Some(id.clone()));
+        // The subject isn't part of the body, so doesn't change what the authors signed
+        assert_eq!(
+            document.canonical_hash()?,
+            example_document().canonical_hash()?
+        );
+        assert_ne!(document, example_document());
+        assert_eq!(
+            Document {
+                id: None,
+                ..document.clone()
+            },
+            example_document()
+        );
+
+        // Relative ids resolve against the document url, and blank nodes have no IRI
+        let relative: Document = crate::parse_str(&with_id("#subject"), base.clone()).await?;
+        assert_eq!(
+            relative.id.as_ref().map(|id| id.as_str()),
+            Some("https://example.com/metadata.jsonld#subject")
+        );
+        let blank: Document = crate::parse_str(&with_id("_:subject"), base.clone()).await?;
+        assert_eq!(blank.id, None);
+
+        let parsed: Document = crate::parse_str(&document.to_jsonld()?, base).await?;
+        assert_eq!(parsed, document);
+        let built = Document::builder().id(id.clone()).comment("Hi").build();
+        assert_eq!(built.id, Some(id));
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_compact_json() -> Result<()> {
+        let mut expected: serde_json::Value = serde_json::from_str(EXAMPLE)?;
+        expected
+            .as_object_mut()
+            .and_then(|object| object.remove("@context"));
+        assert_eq!(example_document().to_compact_json(), expected);
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() -> Result<()> {
+        let document = example_document();
+        let json = serde_json::to_value(&document)?;
+        assert_eq!(
+            json["body"]["references"][0]["reference_type"],
+            "OtherReference"
+        );
+        assert_eq!(
+            json["body"]["external_updates"][0]["uri"],
+            "https://314pool.com"
+        );
+        let round_tripped: Document = serde_json::from_value(json)?;
+        assert_eq!(round_tripped, document);
+        Ok(())
+    }
+
+    #[test]
+    fn test_witness_verify_rejects_malformed() -> Result<()> {
+        let hash = hex::decode(TEST_HASH)?;
+
+        let mut unknown = test_witness();
+        unknown.algorithm = "rsa".into();
+        assert!(unknown.verify(&hash).is_err());
+
+        let mut not_hex = test_witness();
+        not_hex.public_key = "zz".repeat(32);
+        assert!(not_hex.verify(&hash).is_err());
+
+        let mut short = test_witness();
+        short.signature.truncate(64);
+        assert!(short.verify(&hash).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_witness_algorithm() -> Result<()> {
+        assert_eq!(WitnessAlgorithm::from("ed25519"), WitnessAlgorithm::Ed25519);
+        assert_eq!(WitnessAlgorithm::from("CIP-0008"), WitnessAlgorithm::Cip8);
+        assert_eq!(
+            WitnessAlgorithm::from("Ed25519"),
+            WitnessAlgorithm::Unknown("Ed25519".to_string())
+        );
+        for algorithm in WitnessAlgorithm::RECOGNIZED {
+            let parsed = WitnessAlgorithm::from(*algorithm);
+            assert!(parsed.is_recognized());
+            assert_eq!(parsed.to_string(), *algorithm);
+        }
+
+        let hash = hex::decode(TEST_HASH)?;
+        #[cfg(not(feature = "cip8"))]
+        {
+            let mut cip8 = test_witness();
+            cip8.algorithm = WitnessAlgorithm::Cip8;
+            assert_eq!(
+                cip8.verify(&hash).unwrap_err().to_string(),
+                "unsupported algorithm: CIP-0008"
+            );
+        }
+        let mut unknown = test_witness();
+        unknown.algorithm = "dilithium".into();
+        assert!(matches!(
+            unknown.verify(&hash),
+            Err(MetadataError::UnsupportedAlgorithm(algorithm)) if algorithm == "dilithium"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_signed_by() -> Result<()> {
+        let document = example_document();
+        let witness = document.authors[0].witness.clone().unwrap();
+        assert!(document.is_signed_by(&witness.public_key)?);
+        assert!(document.is_signed_by(&format!("0x{}", witness.public_key.to_uppercase()))?);
+        assert!(!document.is_signed_by(&"ab".repeat(32))?);
+        assert!(matches!(
+            document.is_signed_by("not hex"),
+            Err(MetadataError::InvalidValue { .. })
+        ));
+
+        // A matching author whose signature is for some other document
+        let mut tampered = document.clone();
+        tampered.body.comment = Some(en("Tampered").into());
+        assert!(!tampered.is_signed_by(&witness.public_key)?);
+
+        let mut malformed = document.clone();
+        malformed.authors[0].witness.as_mut().unwrap().signature = "zz".to_string();
+        assert!(malformed.is_signed_by(&witness.public_key).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_author_public_keys() -> Result<()> {
+        let document = example_document();
+        let witness = document.authors[0].witness.clone().unwrap();
+        assert_eq!(
+            document.author_public_keys(),
+            vec![witness.public_key.clone()]
+        );
+        assert_eq!(
+            document.author_public_key_bytes()?,
+            vec![witness.public_key_bytes()?]
+        );
+
+        let mut second = witness.clone();
+        second.public_key = "ab".repeat(32);
+        let document = Document::builder()
+            .add_author(Author {
+                name: en("Drafting"),
+                witness: None,
+            })
+            .add_author(Author {
+                name: en("Second"),
+                witness: Some(second),
+            })
+            .add_author(Author {
+                name: en("First"),
+                witness: Some(witness.clone()),
+            })
+            .build();
+        assert_eq!(
+            document.author_public_keys(),
+            vec!["ab".repeat(32), witness.public_key.clone()]
+        );
+        assert_eq!(document.author_public_key_bytes()?[0], [0xab; 32]);
+
+        let mut malformed = witness;
+        malformed.public_key.truncate(10);
+        let document = Document::builder()
+            .add_author(Author {
+                name: en("Malformed"),
+                witness: Some(malformed),
+            })
+            .build();
+        assert!(document.author_public_key_bytes().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_witness_validate() -> Result<()> {
+        test_witness().validate()?;
+
+        let too_long = |hex: &str| format!("{hex}{}", &hex[..64]);
+        let too_short = |hex: &str| hex[..64].to_string();
+        let mut long_key = test_witness();
+        long_key.public_key = too_long(&long_key.public_key);
+        let mut short_key = test_witness();
+        short_key.public_key.truncate(62);
+        let mut short_signature = test_witness();
+        short_signature.signature = too_short(&short_signature.signature);
+        let mut long_signature = test_witness();
+        long_signature.signature = too_long(&long_signature.signature);
+        for (witness, field, reason) in [
+            (long_key, "witness public key", "must be 32 bytes"),
+            (short_key, "witness public key", "must be 32 bytes"),
+            (short_signature, "witness signature", "must be 64 bytes"),
+            (long_signature, "witness signature", "must be 64 bytes"),
+        ] {
+            let result = witness.validate();
+            assert!(
+                matches!(&result, Err(MetadataError::InvalidValue { field: f, reason: r }) if f == field && r == reason),
+                "{result:?}"
+            );
+        }
+
+        // Unknown algorithms have no lengths to check against, but must still be hex
+        let mut unknown = test_witness();
+        unknown.algorithm = "dilithium".into();
+        unknown.public_key.truncate(10);
+        unknown.validate()?;
+        unknown.signature = "not hex".to_string();
+        assert!(unknown.validate().is_err());
+
+        // A witness built by hand, or deserialized, in the forms validate accepts verifies just the same
+        let mut prefixed = test_witness();
+        prefixed.public_key = format!("0X{}", prefixed.public_key.to_uppercase());
+        prefixed.signature = format!("0x{}", prefixed.signature.to_uppercase());
+        prefixed.validate()?;
+        assert!(prefixed.verify(&hex::decode(TEST_HASH)?)?);
+        assert_eq!(
+            prefixed.key_hash_blake2b224()?,
+            test_witness().key_hash_blake2b224()?
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_has_authors_field() -> Result<()> {
+        struct AuthorsField(bool);
+        impl TryFrom<&Node> for AuthorsField {
+            type Error = MetadataError;
+            fn try_from(node: &Node) -> Result<Self, Self::Error> {
+                Ok(Self(Document::has_authors_field(node)))
+            }
+        }
+
+        let base = url::Url::parse("https://example.com/metadata.jsonld")?;
+        let mut example: serde_json::Value = serde_json::from_str(EXAMPLE)?;
+        let has_authors = |document: &serde_json::Value| {
+            let (content, base) = (document.to_string(), base.clone());
+            async move { crate::parse_str::<AuthorsField>(&content, base).await }
+        };
+        assert!(has_authors(&example).await?.0);
+        example["authors"] = serde_json::json!([]);
+        assert!(has_authors(&example).await?.0);
+        example.as_object_mut().unwrap().remove("authors");
+        assert!(!has_authors(&example).await?.0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_error_paths() -> Result<()> {
+        let base = url::Url::parse("https://example.com/metadata.jsonld")?;
+        let example: serde_json::Value = serde_json::from_str(EXAMPLE)?;
+        for (pointer, message) in [
+            (
+                "/authors/0/witness/signature",
+                "no witness signature field at authors[0].witness",
+            ),
+            (
+                "/body/references/0/label",
+                "no reference label field at body.references[0]",
+            ),
+            (
+                "/body/externalUpdates/0/title",
+                "no update title field at body.externalUpdates[0]",
+            ),
+            ("/hashAlgorithm", "no hash_algorithm field"),
+        ] {
+            let mut document = example.clone();
+            let (parent, key) = pointer.rsplit_once('/').unwrap();
+            document
+                .pointer_mut(parent)
+                .and_then(|parent| parent.as_object_mut())
+                .and_then(|parent| parent.remove(key))
+                .unwrap();
+            let err = crate::parse_str::<Document>(&document.to_string(), base.clone())
+                .await
+                .unwrap_err();
+            assert_eq!(err.to_string(), message);
+        }
+
+        let mut document = example.clone();
+        document["authors"][0]["witness"]["publicKey"] = serde_json::json!(42);
+        let err = crate::parse_str::<Document>(&document.to_string(), base)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, MetadataError::WrongType { field, path, .. } if field == "witness public key" && path == "authors[0].witness"),
+            "{err}"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_non_object_list_entries() -> Result<()> {
+        let base = url::Url::parse("https://example.com/metadata.jsonld")?;
+        let example: serde_json::Value = serde_json::from_str(EXAMPLE)?;
+        for (pointer, field) in [
+            ("/authors", "author"),
+            ("/body/references", "reference"),
+            ("/body/externalUpdates", "external update"),
+        ] {
+            let mut document = example.clone();
+            document
+                .pointer_mut(pointer)
+                .unwrap()
+                .as_array_mut()
+                .unwrap()
+                .push("not an object".into());
+            let result = crate::parse_str::<Document>(&document.to_string(), base.clone()).await;
+            assert!(
+                matches!(&result, Err(MetadataError::WrongType { field: f, .. }) if f == field),
+                "{result:?}"
+            );
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_single_and_list_shapes() -> Result<()> {
+        let base = url::Url::parse("https://example.com/metadata.jsonld")?;
+        let example: serde_json::Value = serde_json::from_str(EXAMPLE)?;
+        let expected = example_document();
+        for pointer in ["/authors", "/body/references", "/body/externalUpdates"] {
+            // A lone object rather than a one element array
+            let mut single = example.clone();
+            let field = single.pointer_mut(pointer).unwrap();
+            *field = field.as_array().unwrap()[0].clone();
+            let WithExpandedBody(parsed, expanded_body) =
+                crate::parse_str(&single.to_string(), base.clone()).await?;
+            assert_eq!(parsed, expected, "{pointer}");
+            assert_eq!(parsed.canonical_body()?, expanded_body, "{pointer}");
+
+            // An ordered @list, as when the context declares the field with "@container": "@list"
+            let mut list = example.clone();
+            let field = list.pointer_mut(pointer).unwrap();
+            *field = serde_json::json!({ "@list": field.take() });
+            let result: crate::Result<WithExpandedBody> =
+                crate::parse_str(&list.to_string(), base.clone()).await;
+            if pointer == "/authors" {
+                // Authors aren't part of the body, so their order doesn't affect the hash
+                let WithExpandedBody(parsed, expanded_body) = result?;
+                assert_eq!(parsed, expected, "{pointer}");
+                assert_eq!(parsed.canonical_body()?, expanded_body, "{pointer}");
+            } else {
+                // The list is hashed as an rdf:first/rdf:rest chain, which a set of references can't reproduce
+                let err = result.err().unwrap();
+                assert!(
+                    matches!(
+                        &err,
+                        MetadataError::WrongType {
+                            expected: "a set, rather than an ordered @list",
+                            ..
+                        }
+                    ),
+                    "{pointer}: {err}"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_iri_node_uris() -> Result<()> {
+        let base = url::Url::parse("https://example.com/metadata.jsonld")?;
+        let example: serde_json::Value = serde_json::from_str(EXAMPLE)?;
+        let with_uri = |pointer: &str, uri: serde_json::Value| {
+            let mut document = example.clone();
+            *document.pointer_mut(pointer).unwrap() = uri;
+            document.to_string()
+        };
+        for pointer in ["/body/references/0/uri", "/body/externalUpdates/0/uri"] {
+            let uri = example.pointer(pointer).unwrap().clone();
+            let content = with_uri(pointer, serde_json::json!({ "@id": uri }));
+            let parsed: Document = crate::parse_str(&content, base.clone()).await?;
+            assert_eq!(parsed, example_document(), "{pointer}");
+
+            // Relative @ids resolve against the document url, like any other IRI reference
+            let content = with_uri(pointer, serde_json::json!({ "@id": "other.jsonld" }));
+            let parsed: Document = crate::parse_str(&content, base.clone()).await?;
+            assert_eq!(
+                parsed
+                    .all_uris()
+                    .iter()
+                    .filter(|uri| uri.as_str() == "https://example.com/other.jsonld")
+                    .count(),
+                1,
+                "{pointer}"
+            );
+
+            // Blank nodes, and nodes with properties of their own, aren't uris
+            for value in [
+                serde_json::json!({ "@id": "_:uri" }),
+                serde_json::json!({ "@id": "https://example.com", "https://schema.org/name": "Example" }),
+            ] {
+                let content = with_uri(pointer, value);
+                let err = crate::parse_str::<Document>(&content, base.clone())
+                    .await
+                    .unwrap_err();
+                assert!(
+                    matches!(&err, MetadataError::WrongType { field, expected: "a string or an @id", .. } if field.ends_with("uri")),
+                    "{err}"
+                );
+            }
+        }
+
+        // @ids are held to the same schemes as strings
+        let content = with_uri(
+            "/body/references/0/uri",
+            serde_json::json!({ "@id": "javascript:alert(1)" }),
+        );
+        let err = crate::parse_str::<Document>(&content, base)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, MetadataError::DisallowedScheme { .. }),
+            "{err}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_public_key_bytes() -> Result<()> {
+        let witness = example_document().authors[0].witness.clone().unwrap();
+        assert_eq!(hex::encode(witness.public_key_bytes()?), witness.public_key);
+        assert_eq!(
+            hex::encode(witness.key_hash_blake2b224()?),
+            "0fdc780023d8be7c9ff3a6bdc0d8d3b263bd0cc12448c40948efbf42"
+        );
+
+        let mut short = witness.clone();
+        short.public_key.truncate(62);
+        assert!(matches!(
+            short.key_hash_blake2b224(),
+            Err(MetadataError::InvalidValue { ref reason, .. }) if reason == "must be 32 bytes"
+        ));
+        let mut not_hex = witness;
+        not_hex.public_key = "zz".repeat(32);
+        assert!(matches!(
+            not_hex.public_key_bytes(),
+            Err(MetadataError::InvalidValue { ref reason, .. }) if reason == "is not valid hex"
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+use json_ld::Node;
+
+use crate::cip100::{
+    body_node, parse_references, path_segment, Author, Cip100Base, Cip100BaseRef, Reference,
+};
+use crate::error::{MetadataError, OptionExt, Result};
+use crate::hash::HashAlgorithm;
+use crate::lang::Localized;
+
+// The context fields used in the context of CIP-108 documents
+pub struct CIP108Fields {
+    pub body: &'static str,
+    pub body_references: &'static str,
+    pub body_title: &'static str,
+    pub body_abstract: &'static str,
+    pub body_motivation: &'static str,
+    pub body_rationale: &'static str,
+}
+
+pub const CIP108_FIELDS: CIP108Fields = CIP108Fields {
+    body: "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0108/README.md#body",
+    body_references:
+        "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0108/README.md#references",
+    body_title: "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0108/README.md#title",
+    body_abstract:
+        "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0108/README.md#abstract",
+    body_motivation:
+        "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0108/README.md#motivation",
+    body_rationale:
+        "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0108/README.md#rationale",
+};
+
+/// The body of a governance action metadata document
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GovernanceActionBody {
+    /// A short, descriptive title for the governance action
+    pub title: Localized,
+    /// A short summary of the governance action
+    pub r#abstract: Localized,
+    /// The problem the governance action is trying to solve
+    pub motivation: Option<Localized>,
+    /// Why the governance action is the right way to solve that problem
+    pub rationale: Option<Localized>,
+    /// Any references included in the document
+    pub references: Vec<Reference>,
+}
+
+/// A CIP-108 governance action metadata document
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GovernanceActionDocument {
+    /// The hash algorithm used to hash the document when signing
+    pub hash_algorithm: HashAlgorithm,
+    /// The authors who cosign / attest to this document
+    pub authors: Vec<Author>,
+    /// The body of the document
+    pub body: GovernanceActionBody,
+}
+
+impl GovernanceActionDocument {
+    /// See [Document::base](crate::Document::base)
+    pub fn base(&self) -> Cip100BaseRef<'_> {
+        Cip100BaseRef::new(&self.hash_algorithm, &self.authors, &self.body.references)
+    }
+}
+
+impl TryFrom<&Node> for GovernanceActionDocument {
+    type Error = MetadataError;
+
+    fn try_from(object: &Node) -> Result<Self, Self::Error> {
+        let base = Cip100Base::parse(object, CIP108_FIELDS.body, CIP108_FIELDS.body_references)?;
+        let body = GovernanceActionBody::with_references(
+            body_node(object, CIP108_FIELDS.body)?,
+            base.references,
+        )
+        .map_err(|err| err.within(path_segment(CIP108_FIELDS.body)))?;
+        Ok(Self {
+            hash_algorithm: base.hash_algorithm,
+            authors: base.authors,
+            body,
+        })
+    }
+}
+
+impl TryFrom<&Node> for GovernanceActionBody {
+    type Error = MetadataError;
+
+    fn try_from(value: &Node) -> Result<Self, Self::Error> {
+        Self::with_references(
+            value,
+            parse_references(value, CIP108_FIELDS.body_references)?,
+        )
+    }
+}
+
+impl GovernanceActionBody {
+    /// See [Body::with_references](crate::cip100::Body::with_references)
+    pub(crate) fn with_references(value: &Node, references: Vec<Reference>) -> Result<Self> {
+        let title = Localized::parse(value, CIP108_FIELDS.body_title, "body title")?
+            .missing("body title")?;
+        let r#abstract = Localized::parse(value, CIP108_FIELDS.body_abstract, "body abstract")?
+            .missing("body abstract")?;
+        let motivation = Localized::parse(value, CIP108_FIELDS.body_motivation, "body motivation")?;
+        let rationale = Localized::parse(value, CIP108_FIELDS.body_rationale, "body rationale")?;
+        Ok(Self {
+            title,
+            r#abstract,
+            motivation,
+            rationale,
+            references,
+        })
+    }
+}
@@ -0,0 +1,201 @@
+use iref::{Iri, IriBuf};
+use json_ld::Node;
+
+use crate::cip100::{
+    body_node, parse_references, path_segment, Author, Cip100Base, Cip100BaseRef, Reference,
+};
+use crate::error::{MetadataError, OptionExt, Result};
+use crate::hash::HashAlgorithm;
+use crate::lang::{LangString, Localized};
+use crate::util::get_named_string;
+
+// The context fields used in the context of CIP-119 documents
+pub struct CIP119Fields {
+    pub body: &'static str,
+    pub body_references: &'static str,
+    pub body_payment_address: &'static str,
+    pub body_given_name: &'static str,
+    pub body_image: &'static str,
+    pub body_objectives: &'static str,
+    pub body_motivations: &'static str,
+    pub body_qualifications: &'static str,
+    pub image_content_url: &'static str,
+    pub image_sha256: &'static str,
+}
+
+pub const CIP119_FIELDS: CIP119Fields = CIP119Fields {
+    body: "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0119/README.md#body",
+    body_references:
+        "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0119/README.md#references",
+    body_payment_address:
+        "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0119/README.md#paymentAddress",
+    body_given_name:
+        "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0119/README.md#givenName",
+    body_image: "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0119/README.md#image",
+    body_objectives:
+        "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0119/README.md#objectives",
+    body_motivations:
+        "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0119/README.md#motivations",
+    body_qualifications:
+        "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0119/README.md#qualifications",
+    image_content_url: "https://schema.org/contentUrl",
+    image_sha256: "https://schema.org/sha256",
+};
+
+/// A profile picture for the DRep, as a schema.org ImageObject
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Image {
+    /// Where to find the image; may also be a base64 encoded data URI
+    pub content_url: IriBuf,
+    /// The sha256 hash of the image, if it is hosted at a URL rather than inlined
+    pub sha256: Option<String>,
+}
+
+/// The body of a DRep metadata document
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DRepBody {
+    /// The name the DRep wishes to be known by
+    pub given_name: Localized,
+    /// An address the DRep would like to receive payments at
+    pub payment_address: Option<String>,
+    /// A profile picture for the DRep
+    pub image: Option<Image>,
+    /// What the DRep believes and wants to achieve as a DRep
+    pub objectives: Option<Localized>,
+    /// Why the DRep wishes to be a DRep
+    pub motivations: Option<Localized>,
+    /// Any qualifications, experience, or credentials the DRep wishes to share
+    pub qualifications: Option<Localized>,
+    /// Any references included in the document, such as social media profiles
+    pub references: Vec<Reference>,
+}
+
+/// A CIP-119 DRep registration metadata document
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DRepDocument {
+    /// The hash algorithm used to hash the document when signing
+    pub hash_algorithm: HashAlgorithm,
+    /// The authors who cosign / attest to this document
+    pub authors: Vec<Author>,
+    /// The body of the document
+    pub body: DRepBody,
+}
+
+impl DRepDocument {
+    /// See [Document::base](crate::Document::base)
+    pub fn base(&self) -> Cip100BaseRef<'_> {
+        Cip100BaseRef::new(&self.hash_algorithm, &self.authors, &self.body.references)
+    }
+
+    /// The DRep's objectives, in the language the document gives first, keeping its language tag; use
+    /// [Localized::best_for_language] on the body's field to pick a translation instead
+    pub fn objectives(&self) -> Option<&LangString> {
+        self.body.objectives.as_ref().and_then(|text| text.first())
+    }
+
+    /// The DRep's motivations, in the language the document gives first, keeping its language tag
+    pub fn motivations(&self) -> Option<&LangString> {
+        self.body.motivations.as_ref().and_then(|text| text.first())
+    }
+
+    /// The DRep's qualifications, in the language the document gives first, keeping its language tag
+    pub fn qualifications(&self) -> Option<&LangString> {
+        self.body
+            .qualifications
+            .as_ref()
+            .and_then(|text| text.first())
+    }
+
+    /// The references the DRep lists, such as social media profiles, kept apart from the profile fields above
+    pub fn references(&self) -> &[Reference] {
+        &self.body.references
+    }
+}
+
+impl TryFrom<&Node> for DRepDocument {
+    type Error = MetadataError;
+
+    fn try_from(object: &Node) -> Result<Self, Self::Error> {
+        let base = Cip100Base::parse(object, CIP119_FIELDS.body, CIP119_FIELDS.body_references)?;
+        let body =
+            DRepBody::with_references(body_node(object, CIP119_FIELDS.body)?, base.references)
+                .map_err(|err| err.within(path_segment(CIP119_FIELDS.body)))?;
+        Ok(Self {
+            hash_algorithm: base.hash_algorithm,
+            authors: base.authors,
+            body,
+        })
+    }
+}
+
+impl TryFrom<&Node> for DRepBody {
+    type Error = MetadataError;
+
+    fn try_from(value: &Node) -> Result<Self, Self::Error> {
+        Self::with_references(
+            value,
+            parse_references(value, CIP119_FIELDS.body_references)?,
+        )
+    }
+}
+
+impl DRepBody {
+    /// See [Body::with_references](crate::cip100::Body::with_references)
+    pub(crate) fn with_references(value: &Node, references: Vec<Reference>) -> Result<Self> {
+        let given_name = Localized::parse(value, CIP119_FIELDS.body_given_name, "body given name")?
+            .missing("body given name")?;
+        let payment_address = get_named_string(
+            value,
+            CIP119_FIELDS.body_payment_address,
+            "body payment address",
+        )?;
+        let objectives = Localized::parse(value, CIP119_FIELDS.body_objectives, "body objectives")?;
+        let motivations =
+            Localized::parse(value, CIP119_FIELDS.body_motivations, "body motivations")?;
+        let qualifications = Localized::parse(
+            value,
+            CIP119_FIELDS.body_qualifications,
+            "body qualifications",
+        )?;
+        let image = value
+            .get_any(&Iri::new(CIP119_FIELDS.body_image)?)
+            .map(|image| {
+                image
+                    .as_node()
+                    .wrong_type("body image", "an object")?
+                    .try_into()
+            })
+            .transpose()?;
+        Ok(Self {
+            given_name,
+            payment_address,
+            image,
+            objectives,
+            motivations,
+            qualifications,
+            references,
+        })
+    }
+}
+
+impl TryFrom<&Node> for Image {
+    type Error = MetadataError;
+
+    fn try_from(value: &Node) -> Result<Self, Self::Error> {
+        let content_url =
+            get_named_string(value, CIP119_FIELDS.image_content_url, "image content url")?
+                .missing("image content url")?;
+        let content_url = IriBuf::new(content_url).map_err(|err| MetadataError::InvalidIri {
+            field: "image content url".to_string(),
+            iri: err.0,
+        })?;
+        let sha256 = get_named_string(value, CIP119_FIELDS.image_sha256, "image sha256")?;
+        Ok(Self {
+            content_url,
+            sha256,
+        })
+    }
+}
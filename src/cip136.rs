@@ -0,0 +1,233 @@
+use iref::Iri;
+use json_ld::{object::Literal, Node, Object};
+
+use crate::cip100::{
+    body_node, parse_references, path_segment, Author, Cip100Base, Cip100BaseRef, Reference,
+};
+use crate::error::{MetadataError, OptionExt, Result};
+use crate::hash::HashAlgorithm;
+use crate::lang::Localized;
+
+// The context fields used in the context of CIP-136 documents
+pub struct CIP136Fields {
+    pub body: &'static str,
+    pub body_references: &'static str,
+    pub body_summary: &'static str,
+    pub body_rationale_statement: &'static str,
+    pub body_precedent_discussion: &'static str,
+    pub body_counterargument_discussion: &'static str,
+    pub body_conclusion: &'static str,
+    pub body_internal_vote: &'static str,
+    pub internal_vote_constitutional: &'static str,
+    pub internal_vote_unconstitutional: &'static str,
+    pub internal_vote_abstain: &'static str,
+    pub internal_vote_did_not_vote: &'static str,
+}
+
+pub const CIP136_FIELDS: CIP136Fields = CIP136Fields {
+    body: "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0136/README.md#body",
+    body_references:
+        "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0100/README.md#references",
+    body_summary: "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0136/README.md#summary",
+    body_rationale_statement:
+        "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0136/README.md#rationaleStatement",
+    body_precedent_discussion:
+        "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0136/README.md#precedentDiscussion",
+    body_counterargument_discussion:
+        "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0136/README.md#counterargumentDiscussion",
+    body_conclusion:
+        "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0136/README.md#conclusion",
+    body_internal_vote:
+        "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0136/README.md#internalVote",
+    internal_vote_constitutional:
+        "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0136/README.md#constitutional",
+    internal_vote_unconstitutional:
+        "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0136/README.md#unconstitutional",
+    internal_vote_abstain:
+        "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0136/README.md#abstain",
+    internal_vote_did_not_vote:
+        "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0136/README.md#didNotVote",
+};
+
+/// How the members of a constitutional committee body voted internally on the governance action
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InternalVote {
+    /// The number of members who voted that the action is constitutional
+    pub constitutional: u64,
+    /// The number of members who voted that the action is unconstitutional
+    pub unconstitutional: u64,
+    /// The number of members who abstained
+    pub abstain: u64,
+    /// The number of members who didn't vote
+    pub did_not_vote: u64,
+}
+
+impl InternalVote {
+    /// The number of members of the committee body, whether or not they voted
+    pub fn total(&self) -> u64 {
+        [
+            self.constitutional,
+            self.unconstitutional,
+            self.abstain,
+            self.did_not_vote,
+        ]
+        .into_iter()
+        .fold(0, u64::saturating_add)
+    }
+}
+
+/// The XML schema integer types a count may be given as, in the string form of a typed value
+const COUNT_TYPES: &[&str] = &[
+    "http://www.w3.org/2001/XMLSchema#integer",
+    "http://www.w3.org/2001/XMLSchema#nonNegativeInteger",
+];
+
+/// The body of a constitutional committee vote rationale document
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VoteRationaleBody {
+    /// A short summary of the rationale
+    pub summary: Localized,
+    /// The full rationale for the vote, with reference to the constitution
+    pub rationale_statement: Localized,
+    /// A discussion of any precedent the vote relies on or sets
+    pub precedent_discussion: Option<Localized>,
+    /// A discussion of the arguments against the vote
+    pub counterargument_discussion: Option<Localized>,
+    /// The conclusion of the rationale
+    pub conclusion: Option<Localized>,
+    /// How the members of the committee body voted, if it voted as a group
+    pub internal_vote: Option<InternalVote>,
+    /// Any references included in the document, such as the relevant articles of the constitution
+    pub references: Vec<Reference>,
+}
+
+/// A CIP-136 constitutional committee vote rationale metadata document
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VoteRationaleDocument {
+    /// The hash algorithm used to hash the document when signing
+    pub hash_algorithm: HashAlgorithm,
+    /// The authors who cosign / attest to this document
+    pub authors: Vec<Author>,
+    /// The body of the document
+    pub body: VoteRationaleBody,
+}
+
+impl VoteRationaleDocument {
+    /// See [Document::base](crate::Document::base)
+    pub fn base(&self) -> Cip100BaseRef<'_> {
+        Cip100BaseRef::new(&self.hash_algorithm, &self.authors, &self.body.references)
+    }
+}
+
+impl TryFrom<&Node> for VoteRationaleDocument {
+    type Error = MetadataError;
+
+    fn try_from(object: &Node) -> Result<Self, Self::Error> {
+        let base = Cip100Base::parse(object, CIP136_FIELDS.body, CIP136_FIELDS.body_references)?;
+        let body = VoteRationaleBody::with_references(
+            body_node(object, CIP136_FIELDS.body)?,
+            base.references,
+        )
+        .map_err(|err| err.within(path_segment(CIP136_FIELDS.body)))?;
+        Ok(Self {
+            hash_algorithm: base.hash_algorithm,
+            authors: base.authors,
+            body,
+        })
+    }
+}
+
+impl TryFrom<&Node> for VoteRationaleBody {
+    type Error = MetadataError;
+
+    fn try_from(value: &Node) -> Result<Self, Self::Error> {
+        Self::with_references(
+            value,
+            parse_references(value, CIP136_FIELDS.body_references)?,
+        )
+    }
+}
+
+impl VoteRationaleBody {
+    /// See [Body::with_references](crate::cip100::Body::with_references)
+    pub(crate) fn with_references(value: &Node, references: Vec<Reference>) -> Result<Self> {
+        let summary = Localized::parse(value, CIP136_FIELDS.body_summary, "body summary")?
+            .missing("body summary")?;
+        let rationale_statement = Localized::parse(
+            value,
+            CIP136_FIELDS.body_rationale_statement,
+            "body rationale statement",
+        )?
+        .missing("body rationale statement")?;
+        let precedent_discussion = Localized::parse(
+            value,
+            CIP136_FIELDS.body_precedent_discussion,
+            "body precedent discussion",
+        )?;
+        let counterargument_discussion = Localized::parse(
+            value,
+            CIP136_FIELDS.body_counterargument_discussion,
+            "body counterargument discussion",
+        )?;
+        let conclusion = Localized::parse(value, CIP136_FIELDS.body_conclusion, "body conclusion")?;
+        let internal_vote = value
+            .get_any(&Iri::new(CIP136_FIELDS.body_internal_vote)?)
+            .map(|vote| {
+                vote.as_node()
+                    .wrong_type("body internal vote", "an object")?
+                    .try_into()
+            })
+            .transpose()?;
+        Ok(Self {
+            summary,
+            rationale_statement,
+            precedent_discussion,
+            counterargument_discussion,
+            conclusion,
+            internal_vote,
+            references,
+        })
+    }
+}
+
+impl TryFrom<&Node> for InternalVote {
+    type Error = MetadataError;
+
+    fn try_from(value: &Node) -> Result<Self, Self::Error> {
+        let count = |field: &str, name: &str| -> Result<u64> {
+            parse_count(value.get_any(&Iri::new(field)?).missing(name)?)
+                .wrong_type(name, "a non-negative integer")
+        };
+        Ok(Self {
+            constitutional: count(
+                CIP136_FIELDS.internal_vote_constitutional,
+                "internal vote constitutional",
+            )?,
+            unconstitutional: count(
+                CIP136_FIELDS.internal_vote_unconstitutional,
+                "internal vote unconstitutional",
+            )?,
+            abstain: count(CIP136_FIELDS.internal_vote_abstain, "internal vote abstain")?,
+            did_not_vote: count(
+                CIP136_FIELDS.internal_vote_did_not_vote,
+                "internal vote did not vote",
+            )?,
+        })
+    }
+}
+
+/// A count, given either as a JSON number, such as 5 or { "@value": 5 }, or as an xsd integer typed string,
+/// such as { "@value": "5", "@type": "xsd:integer" }; anything else, including fractions and plain strings,
+/// isn't a count
+fn parse_count(value: &Object) -> Option<u64> {
+    match value.as_value()?.as_literal()? {
+        (Literal::Number(number), _) => number.as_u64(),
+        (Literal::String(count), Some(typ)) if COUNT_TYPES.contains(&typ.as_str()) => {
+            count.as_str().parse().ok()
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,562 @@
+use std::collections::HashMap;
+
+use iref::IriBuf;
+use json_ld::{
+    loader::map::EntryNotFound,
+    syntax::{Parse, Value},
+    Loader, LoadingResult, RemoteDocument,
+};
+use rdf_types::vocabulary::IriVocabularyMut;
+
+use crate::error::{MetadataError, Result};
+#[cfg(feature = "client")]
+use crate::MetadataClient;
+
+/// Where the canonical CIP-100 JSON-LD context is published
+pub const CIP100_CONTEXT: &str =
+    "https://raw.githubusercontent.com/cardano-foundation/CIPs/master/CIP-0100/cip-0100.common.jsonld";
+
+/// Where the canonical CIP-108 JSON-LD context is published
+pub const CIP108_CONTEXT: &str =
+    "https://raw.githubusercontent.com/cardano-foundation/CIPs/master/CIP-0108/cip-0108.common.jsonld";
+
+/// Where the canonical CIP-119 JSON-LD context is published
+pub const CIP119_CONTEXT: &str =
+    "https://raw.githubusercontent.com/cardano-foundation/CIPs/master/CIP-0119/cip-0119.common.jsonld";
+
+/// Where the canonical CIP-136 JSON-LD context is published
+pub const CIP136_CONTEXT: &str =
+    "https://raw.githubusercontent.com/cardano-foundation/CIPs/master/CIP-0136/cip-0136.common.jsonld";
+
+/// Copies of the well known contexts, compiled into the crate; each is also registered under the url
+/// of its page in the CIPs repository on github, since some documents reference that instead
+const BUNDLED_CONTEXTS: &[(&str, &str, &str)] = &[
+    (
+        CIP100_CONTEXT,
+        "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0100/cip-0100.common.jsonld",
+        include_str!("../contexts/cip-0100.common.jsonld"),
+    ),
+    (
+        CIP108_CONTEXT,
+        "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0108/cip-0108.common.jsonld",
+        include_str!("../contexts/cip-0108.common.jsonld"),
+    ),
+    (
+        CIP119_CONTEXT,
+        "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0119/cip-0119.common.jsonld",
+        include_str!("../contexts/cip-0119.common.jsonld"),
+    ),
+    (
+        CIP136_CONTEXT,
+        "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0136/cip-0136.common.jsonld",
+        include_str!("../contexts/cip-0136.common.jsonld"),
+    ),
+];
+
+/// A context a document declares in its top level @context, as written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeclaredContext {
+    /// A reference to a remote context, such as [CIP100_CONTEXT]
+    Remote(String),
+    /// A context defined in the document itself
+    Inline(Value),
+}
+
+impl DeclaredContext {
+    /// Whether this references one of the contexts bundled with this crate, such as CIP-100's
+    pub fn is_bundled(&self) -> bool {
+        match self {
+            DeclaredContext::Remote(iri) => BUNDLED_CONTEXTS
+                .iter()
+                .any(|(canonical, github, _)| iri == canonical || iri == github),
+            DeclaredContext::Inline(_) => false,
+        }
+    }
+}
+
+/// The contexts the JSON-LD content declares at its top level, in order, such as to flag documents that aren't
+/// really JSON-LD; plain JSON with no @context declares none, and means nothing once expanded, even if some of
+/// it happens to parse
+pub fn declared_contexts(content: &str) -> Result<Vec<DeclaredContext>> {
+    let (value, _) = Value::parse_str(content).map_err(|err| MetadataError::Parse {
+        position: err.position(),
+        message: err.to_string(),
+    })?;
+    // A top level array is several documents, each of which may declare its own
+    let objects = match &value {
+        Value::Array(values) => values.iter().filter_map(Value::as_object).collect(),
+        value => value.as_object().into_iter().collect::<Vec<_>>(),
+    };
+    let contexts = objects
+        .into_iter()
+        .flat_map(|object| object.get("@context"))
+        .flat_map(|context| match context {
+            Value::Array(contexts) => contexts.iter().collect(),
+            context => vec![context],
+        })
+        .filter_map(|context| match context {
+            Value::String(iri) => Some(DeclaredContext::Remote(iri.to_string())),
+            Value::Null => None,
+            context => Some(DeclaredContext::Inline(context.clone())),
+        })
+        .collect();
+    Ok(contexts)
+}
+
+/// A JSON-LD loader that resolves contexts from a fixed set of copies, and never touches the network
+///
+/// By default this knows the CIP-100, CIP-108, CIP-119 and CIP-136 contexts, so documents referencing them by
+/// url expand identically whether or not the network is available
+#[derive(Debug, Clone)]
+pub struct StaticContextLoader {
+    contexts: HashMap<IriBuf, RemoteDocument>,
+}
+
+impl Default for StaticContextLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StaticContextLoader {
+    /// A loader that knows the contexts bundled with this crate
+    pub fn new() -> Self {
+        let mut loader = Self::empty();
+        for (iri, github, content) in BUNDLED_CONTEXTS {
+            let (context, _) = Value::parse_str(content).expect("bundled contexts are valid JSON");
+            for iri in [iri, github] {
+                let iri = IriBuf::new(iri.to_string()).expect("bundled context IRIs are valid");
+                loader.insert(iri, context.clone());
+            }
+        }
+        loader
+    }
+
+    /// A loader that knows no contexts at all
+    pub fn empty() -> Self {
+        Self {
+            contexts: HashMap::new(),
+        }
+    }
+
+    /// Register a context document, such as one for some other CIP, to be served for the given IRI
+    pub fn insert(&mut self, iri: IriBuf, context: Value) {
+        let document = RemoteDocument::new(Some(iri.clone()), None, context);
+        self.contexts.insert(iri, document);
+    }
+
+    /// Register the JSON content of a context document to be served for the given IRI
+    pub fn with_context(mut self, iri: IriBuf, content: &str) -> Result<Self> {
+        let (context, _) = Value::parse_str(content).map_err(|err| MetadataError::Parse {
+            position: err.position(),
+            message: err.to_string(),
+        })?;
+        self.insert(iri, context);
+        Ok(self)
+    }
+}
+
+impl Loader<IriBuf> for StaticContextLoader {
+    type Error = EntryNotFound<IriBuf>;
+
+    async fn load_with<V>(
+        &mut self,
+        vocabulary: &mut V,
+        url: IriBuf,
+    ) -> LoadingResult<IriBuf, Self::Error>
+    where
+        V: IriVocabularyMut<Iri = IriBuf>,
+    {
+        (&*self).load_with(vocabulary, url).await
+    }
+}
+
+/// Loading never modifies the set of contexts, so a shared loader can be used for many documents at once
+impl Loader<IriBuf> for &StaticContextLoader {
+    type Error = EntryNotFound<IriBuf>;
+
+    async fn load_with<V>(
+        &mut self,
+        _vocabulary: &mut V,
+        url: IriBuf,
+    ) -> LoadingResult<IriBuf, Self::Error>
+    where
+        V: IriVocabularyMut<Iri = IriBuf>,
+    {
+        match self.contexts.get(&url) {
+            Some(document) => Ok(document.clone()),
+            None => Err(EntryNotFound(url)),
+        }
+    }
+}
+
+/// Wraps another loader, remembering the first context it failed to load, so that a failed expansion can
+/// name the context responsible rather than just reporting that loading failed
+pub(crate) struct RecordingLoader<'a, L> {
+    loader: &'a mut L,
+    pub(crate) failed: Option<(IriBuf, String)>,
+}
+
+impl<'a, L> RecordingLoader<'a, L> {
+    pub(crate) fn new(loader: &'a mut L) -> Self {
+        Self {
+            loader,
+            failed: None,
+        }
+    }
+}
+
+impl<L> Loader<IriBuf> for RecordingLoader<'_, L>
+where
+    L: Loader<IriBuf>,
+    L::Error: std::fmt::Display,
+{
+    type Error = L::Error;
+
+    async fn load_with<V>(
+        &mut self,
+        vocabulary: &mut V,
+        url: IriBuf,
+    ) -> LoadingResult<IriBuf, Self::Error>
+    where
+        V: IriVocabularyMut<Iri = IriBuf>,
+    {
+        let result = self.loader.load_with(vocabulary, url.clone()).await;
+        if let Err(err) = &result {
+            self.failed.get_or_insert((url, err.to_string()));
+        }
+        result
+    }
+}
+
+/// How a [MetadataClient] expands documents: from its static contexts, which never need locking, then
+/// through any loader it was given, which it locks only while each context is loaded rather than for the
+/// whole expansion, so loads through clones of the client don't queue behind each other; if context fetching
+/// is on, contexts neither can provide are then fetched without holding the loader
+#[cfg(feature = "client")]
+pub(crate) struct ClientLoader<'a, L>(pub(crate) &'a MetadataClient<L>);
+
+#[cfg(feature = "client")]
+impl<L> Loader<IriBuf> for ClientLoader<'_, L>
+where
+    L: Loader<IriBuf>,
+    L::Error: std::fmt::Display,
+{
+    type Error = MetadataError;
+
+    async fn load_with<V>(
+        &mut self,
+        vocabulary: &mut V,
+        url: IriBuf,
+    ) -> LoadingResult<IriBuf, Self::Error>
+    where
+        V: IriVocabularyMut<Iri = IriBuf>,
+    {
+        let mut loaded = (&*self.0.contexts)
+            .load_with(vocabulary, url.clone())
+            .await
+            .map_err(|err| err.to_string());
+        if let (Err(_), Some(loader)) = (&loaded, &self.0.loader) {
+            loaded = loader
+                .lock()
+                .await
+                .load_with(vocabulary, url.clone())
+                .await
+                .map_err(|err| err.to_string());
+        }
+        match loaded {
+            Ok(document) => Ok(document),
+            Err(_) if self.0.fetch_contexts => self.0.fetch_context(url).await,
+            Err(err) => Err(anyhow::anyhow!("{err}").into()),
+        }
+    }
+}
+
+/// A JSON-LD loader that resolves the bundled contexts, and any others registered with it, without touching
+/// the network, and fetches any other context over HTTP
+///
+/// The well known CIP contexts always expand identically, while documents referencing novel contexts still
+/// load; the network fallback can be turned off, such as for air-gapped validators, leaving a loader that
+/// behaves exactly like a [StaticContextLoader]
+///
+/// Contexts are fetched as a [MetadataClient] fetches documents, within its timeout, size limit, redirect
+/// checks and address policy; [MetadataClient::context_loader] builds one from a configured client. To
+/// load documents through a client, [MetadataClient::with_context_fetching] does the same without holding
+/// up other loads while a context downloads, so prefer it to handing this loader to
+/// [MetadataClient::with_loader]
+#[cfg(feature = "client")]
+#[derive(Clone)]
+pub struct FallbackContextLoader {
+    bundled: StaticContextLoader,
+    client: Option<MetadataClient>,
+}
+
+#[cfg(feature = "client")]
+impl std::fmt::Debug for FallbackContextLoader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FallbackContextLoader")
+            .field("bundled", &self.bundled)
+            .field("network", &self.client.is_some())
+            .finish()
+    }
+}
+
+#[cfg(feature = "client")]
+impl Default for FallbackContextLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "client")]
+impl FallbackContextLoader {
+    /// A loader that knows the contexts bundled with this crate, and fetches any other with the settings of
+    /// [MetadataClient::new]
+    pub fn new() -> Self {
+        MetadataClient::new().context_loader()
+    }
+
+    /// A loader that knows the contexts bundled with this crate, and fetches any other with the given
+    /// reqwest client, for example one configured with a proxy
+    ///
+    /// As with [MetadataClient::with_client], build it with `redirect(reqwest::redirect::Policy::none())` to
+    /// have redirects checked; otherwise the client follows redirects by its own policy
+    pub fn with_client(client: reqwest::Client) -> Self {
+        MetadataClient::with_client(client).context_loader()
+    }
+
+    /// A loader that fetches contexts with the given client
+    pub(crate) fn from_client(client: MetadataClient) -> Self {
+        Self {
+            bundled: StaticContextLoader::new(),
+            client: Some(client),
+        }
+    }
+
+    /// Never fetch contexts, failing to expand any document that references one that isn't bundled
+    pub fn without_network(mut self) -> Self {
+        self.client = None;
+        self
+    }
+
+    /// Register the JSON content of a context document to be served for the given IRI, without fetching it
+    pub fn with_context(mut self, iri: IriBuf, content: &str) -> Result<Self> {
+        self.bundled = self.bundled.with_context(iri, content)?;
+        Ok(self)
+    }
+}
+
+#[cfg(feature = "client")]
+impl Loader<IriBuf> for FallbackContextLoader {
+    type Error = MetadataError;
+
+    async fn load_with<V>(
+        &mut self,
+        vocabulary: &mut V,
+        url: IriBuf,
+    ) -> LoadingResult<IriBuf, Self::Error>
+    where
+        V: IriVocabularyMut<Iri = IriBuf>,
+    {
+        match (&self.bundled).load_with(vocabulary, url).await {
+            Ok(document) => Ok(document),
+            Err(EntryNotFound(url)) => match &self.client {
+                Some(client) => client.fetch_context(url).await,
+                None => {
+                    Err(anyhow::anyhow!("not bundled, and fetching contexts is disabled").into())
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[tokio::test]
+    async fn test_static_context_loader() -> Result<()> {
+        let mut loader = StaticContextLoader::new();
+        for context in [
+            CIP100_CONTEXT,
+            CIP108_CONTEXT,
+            CIP119_CONTEXT,
+            CIP136_CONTEXT,
+        ] {
+            let document = loader
+                .load(IriBuf::new(context.to_string()).unwrap())
+                .await?;
+            assert!(document.document().as_object().is_some());
+        }
+
+        let custom = IriBuf::new("https://example.com/context.jsonld".to_string()).unwrap();
+        assert!(loader.load(custom.clone()).await.is_err());
+        let mut loader = loader.with_context(custom.clone(), r#"{"@context": {}}"#)?;
+        assert!(loader.load(custom.clone()).await.is_ok());
+        assert!(StaticContextLoader::empty()
+            .with_context(custom, "{ not json")
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_declared_contexts() -> Result<()> {
+        let remote = include_str!("../tests/fixtures/remote-context.json");
+        assert_eq!(
+            declared_contexts(remote)?,
+            vec![DeclaredContext::Remote(CIP100_CONTEXT.to_string())]
+        );
+        assert!(declared_contexts(remote)?[0].is_bundled());
+
+        let mixed = declared_contexts(
+            r#"{"@context": ["https://example.com/other.jsonld", {"name": "https://schema.org/name"}, null]}"#,
+        )?;
+        assert_eq!(mixed.len(), 2);
+        assert!(!mixed[0].is_bundled());
+        assert!(matches!(mixed[1], DeclaredContext::Inline(_)));
+
+        let contextless = r#"{"hashAlgorithm": "blake2b-256", "body": {"comment": "Plain JSON"}}"#;
+        assert_eq!(declared_contexts(contextless)?, vec![]);
+        assert!(declared_contexts("{ not json").is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "client")]
+    #[tokio::test]
+    async fn test_fallback_context_loader() -> Result<()> {
+        use crate::test_server::TestServer;
+        use crate::{Document, MetadataClient};
+        use wiremock::ResponseTemplate;
+
+        let server = TestServer::start().await;
+        server
+            .mount(
+                TestServer::get("/context.jsonld")
+                    .respond_with(
+                        ResponseTemplate::new(200)
+                            .set_body_string(include_str!("../contexts/cip-0100.common.jsonld")),
+                    )
+                    .expect(1),
+            )
+            .await;
+        let content = include_str!("../tests/fixtures/remote-context.json");
+        let url = url::Url::parse("https://example.com/metadata.jsonld")?;
+
+        // The CIP-100 context is bundled, so resolves even with the network turned off
+        let offline =
+            MetadataClient::new().with_loader(FallbackContextLoader::new().without_network());
+        let bundled: Document = offline.load_str(content, url.clone()).await?;
+
+        // Any other context is fetched, unless the network is turned off
+        let novel = content.replace(CIP100_CONTEXT, server.url("/context.jsonld").as_str());
+        let online = MetadataClient::new().with_loader(FallbackContextLoader::new());
+        let fetched: Document = online.load_str(&novel, url.clone()).await?;
+        assert_eq!(fetched, bundled);
+        let err = offline
+            .load_str::<Document>(&novel, url.clone())
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, MetadataError::UnresolvableContext { ref iri, .. } if iri.ends_with("/context.jsonld")),
+            "{}",
+            err
+        );
+
+        let missing = content.replace(CIP100_CONTEXT, server.url("/missing.jsonld").as_str());
+        let err = online
+            .load_str::<Document>(&missing, url)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("404"), "{}", err);
+        server.verify().await;
+        Ok(())
+    }
+
+    #[cfg(feature = "client")]
+    #[tokio::test]
+    async fn test_context_fetching() -> Result<()> {
+        use crate::test_server::TestServer;
+        use crate::{AddressPolicy, Document, MetadataClient};
+        use std::time::Duration;
+        use wiremock::ResponseTemplate;
+
+        let server = TestServer::start().await;
+        let context = include_str!("../contexts/cip-0100.common.jsonld");
+        server
+            .respond(
+                "/context.jsonld",
+                ResponseTemplate::new(200).set_body_string(context),
+            )
+            .await;
+        server
+            .respond(
+                "/slow.jsonld",
+                ResponseTemplate::new(200)
+                    .set_body_string(context)
+                    .set_delay(Duration::from_secs(2)),
+            )
+            .await;
+        let content = include_str!("../tests/fixtures/remote-context.json");
+        let url = url::Url::parse("https://example.com/metadata.jsonld")?;
+        let novel = content.replace(CIP100_CONTEXT, server.url("/context.jsonld").as_str());
+
+        let client = MetadataClient::new().with_context_fetching();
+        let bundled: Document = client.load_str(content, url.clone()).await?;
+        let fetched: Document = client.load_str(&novel, url.clone()).await?;
+        assert_eq!(fetched, bundled);
+
+        // Contexts are held to the client's limits, as documents are
+        let limited = MetadataClient::new().with_max_size(64);
+        let err = limited
+            .clone()
+            .with_context_fetching()
+            .load_str::<Document>(&novel, url.clone())
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("maximum size of 64 bytes"),
+            "{err}"
+        );
+        let err = MetadataClient::new()
+            .with_loader(limited.context_loader())
+            .load_str::<Document>(&novel, url.clone())
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("maximum size of 64 bytes"),
+            "{err}"
+        );
+
+        // And to its address policy, so a document can't have the client probe its network
+        let guarded = MetadataClient::new().with_address_policy(AddressPolicy::new());
+        let err = guarded
+            .clone()
+            .with_context_fetching()
+            .load_str::<Document>(&novel, url.clone())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("address policy denies"), "{err}");
+        let err = MetadataClient::new()
+            .with_loader(guarded.context_loader())
+            .load_str::<Document>(&novel, url.clone())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("address policy denies"), "{err}");
+
+        // A slow context doesn't hold up other loads through the client
+        let slow = content.replace(CIP100_CONTEXT, server.url("/slow.jsonld").as_str());
+        let (waiting, quick) =
+            tokio::join!(client.load_str::<Document>(&slow, url.clone()), async {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                tokio::time::timeout(
+                    Duration::from_secs(1),
+                    client.load_str::<Document>(content, url.clone()),
+                )
+                .await
+            });
+        assert_eq!(quick??, bundled);
+        assert_eq!(waiting?, bundled);
+        Ok(())
+    }
+}
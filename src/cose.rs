@@ -0,0 +1,291 @@
+//! CIP-8 message signing, as wallets sign data for CIP-30's signData: a COSE_Sign1 structure, signing a
+//! payload with an ed25519 key, and naming the address it was signed for in its protected header
+
+use blake2::{digest::consts::U28, Blake2b, Digest};
+use ciborium::Value;
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+use crate::{
+    cip100::hex_digits,
+    error::{MetadataError, Result},
+};
+
+/// The COSE tag a COSE_Sign1 structure may be wrapped in
+const COSE_SIGN1_TAG: u64 = 18;
+/// The COSE header label for the signing algorithm
+const ALGORITHM_LABEL: i128 = 1;
+/// The COSE algorithm identifier for EdDSA, the only algorithm CIP-8 uses
+const EDDSA: i128 = -8;
+/// The COSE_Key label holding an OKP key's public key
+const KEY_X_LABEL: i128 = -2;
+
+/// A decoded COSE_Sign1 structure, as found in the signature of a CIP-0008 witness
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoseSign1 {
+    /// The serialized protected header, exactly as signed
+    protected: Vec<u8>,
+    /// The address the data was signed for, from the protected header, if it names one
+    pub address: Option<Vec<u8>>,
+    /// Whether the payload is the blake2b-224 hash of the signed data, rather than the data itself
+    pub hashed: bool,
+    /// The signed data, or None if it's detached from the structure
+    pub payload: Option<Vec<u8>>,
+    signature: [u8; 64],
+}
+
+impl CoseSign1 {
+    /// Decode a COSE_Sign1 structure from its CBOR, tagged or not
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let value: Value =
+            ciborium::from_reader(bytes).map_err(|e| invalid(format!("is not valid CBOR: {e}")))?;
+        let value = match value {
+            Value::Tag(COSE_SIGN1_TAG, value) => *value,
+            value => value,
+        };
+        let [protected, unprotected, payload, signature] = <[Value; 4]>::try_from(
+            value
+                .into_array()
+                .map_err(|_| invalid("is not a COSE_Sign1 array".to_string()))?,
+        )
+        .map_err(|_| invalid("must have 4 elements".to_string()))?;
+
+        let protected = protected
+            .into_bytes()
+            .map_err(|_| invalid("has a protected header that isn't bytes".to_string()))?;
+        let protected_header = if protected.is_empty() {
+            vec![]
+        } else {
+            ciborium::from_reader::<Value, _>(protected.as_slice())
+                .ok()
+                .and_then(|header| header.into_map().ok())
+                .ok_or_else(|| invalid("has a protected header that isn't a map".to_string()))?
+        };
+        if let Some(algorithm) = header_int(&protected_header, ALGORITHM_LABEL) {
+            if algorithm != EDDSA {
+                return Err(MetadataError::UnsupportedAlgorithm(format!(
+                    "COSE algorithm {algorithm}"
+                )));
+            }
+        }
+        let address = header_text(&protected_header, "address")
+            .and_then(Value::as_bytes)
+            .cloned();
+
+        let unprotected = unprotected
+            .into_map()
+            .map_err(|_| invalid("has an unprotected header that isn't a map".to_string()))?;
+        let hashed = header_text(&unprotected, "hashed")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+
+        let payload = match payload {
+            Value::Null => None,
+            Value::Bytes(payload) => Some(payload),
+            _ => return Err(invalid("has a payload that isn't bytes".to_string())),
+        };
+        let signature = signature
+            .into_bytes()
+            .ok()
+            .and_then(|signature| signature.try_into().ok())
+            .ok_or_else(|| invalid("must have a 64 byte signature".to_string()))?;
+
+        Ok(Self {
+            protected,
+            address,
+            hashed,
+            payload,
+            signature,
+        })
+    }
+
+    /// Decode a COSE_Sign1 structure from hex, as it's written in a witness
+    pub fn from_hex(value: &str) -> Result<Self> {
+        Self::from_bytes(
+            &hex::decode(hex_digits(value)).map_err(|_| invalid("is not valid hex".to_string()))?,
+        )
+    }
+
+    /// Verify that the structure signs the given document hash with the given key
+    ///
+    /// The payload may be the hash itself, or its hex, which is what wallets are handed when asked to sign
+    /// it; if it's marked as hashed, it must be the blake2b-224 hash of one of those. A detached payload is
+    /// taken to be the hash. Returns Ok(false) if the payload is for some other document, or the signature
+    /// doesn't match
+    pub fn verify(&self, public_key: &[u8; 32], document_hash: &[u8]) -> Result<bool> {
+        let payload = match &self.payload {
+            Some(payload) => payload.clone(),
+            None if self.hashed => Blake2b::<U28>::digest(document_hash).to_vec(),
+            None => document_hash.to_vec(),
+        };
+        let hex_hash = hex::encode(document_hash);
+        let signs_document = [document_hash, hex_hash.as_bytes()].iter().any(|data| {
+            if self.hashed {
+                payload == Blake2b::<U28>::digest(data).as_slice()
+            } else {
+                payload == *data
+            }
+        });
+        if !signs_document {
+            return Ok(false);
+        }
+
+        let public_key =
+            VerifyingKey::from_bytes(public_key).map_err(|_| MetadataError::InvalidValue {
+                field: "witness public key".to_string(),
+                reason: "is not a valid ed25519 key".to_string(),
+            })?;
+        let signature = Signature::from_bytes(&self.signature);
+        Ok(public_key
+            .verify(&self.signed_data(payload), &signature)
+            .is_ok())
+    }
+
+    /// The Sig_structure COSE has the signer sign, binding the protected header to the payload
+    fn signed_data(&self, payload: Vec<u8>) -> Vec<u8> {
+        let structure = Value::Array(vec![
+            Value::Text("Signature1".to_string()),
+            Value::Bytes(self.protected.clone()),
+            Value::Bytes(vec![]),
+            Value::Bytes(payload),
+        ]);
+        let mut bytes = vec![];
+        ciborium::into_writer(&structure, &mut bytes).expect("writing to a vec can't fail");
+        bytes
+    }
+}
+
+/// The ed25519 key of a CIP-0008 witness, given either as the raw 32 byte key, or as the COSE_Key that
+/// CIP-30's signData returns alongside the signature
+pub(crate) fn witness_public_key(value: &str) -> Result<[u8; 32]> {
+    let invalid_key = |reason: &str| MetadataError::InvalidValue {
+        field: "witness public key".to_string(),
+        reason: reason.to_string(),
+    };
+    let bytes = hex::decode(hex_digits(value)).map_err(|_| invalid_key("is not valid hex"))?;
+    if let Ok(key) = <[u8; 32]>::try_from(bytes.as_slice()) {
+        return Ok(key);
+    }
+    ciborium::from_reader::<Value, _>(bytes.as_slice())
+        .ok()
+        .and_then(|key| key.into_map().ok())
+        .and_then(|key| header_int_value(&key, KEY_X_LABEL)?.as_bytes().cloned())
+        .and_then(|key| key.try_into().ok())
+        .ok_or_else(|| invalid_key("must be a 32 byte ed25519 key, or a COSE_Key holding one"))
+}
+
+fn invalid(reason: String) -> MetadataError {
+    MetadataError::InvalidValue {
+        field: "witness signature".to_string(),
+        reason,
+    }
+}
+
+fn header_int_value(header: &[(Value, Value)], label: i128) -> Option<&Value> {
+    header
+        .iter()
+        .find(|(key, _)| key.as_integer().map(i128::from) == Some(label))
+        .map(|(_, value)| value)
+}
+
+fn header_int(header: &[(Value, Value)], label: i128) -> Option<i128> {
+    header_int_value(header, label)?
+        .as_integer()
+        .map(i128::from)
+}
+
+fn header_text<'a>(header: &'a [(Value, Value)], label: &str) -> Option<&'a Value> {
+    header
+        .iter()
+        .find(|(key, _)| key.as_text() == Some(label))
+        .map(|(_, value)| value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cip100::{Witness, WitnessAlgorithm};
+    use anyhow::Result;
+
+    const TEST_HASH: &str = "b322a34a7ebe77678e586eb55fe301955e15ab0b27f1f6c736f4d698f6534f03";
+    /// The key whose secret is the bytes 0 to 31, as in the ed25519 witness tests
+    const TEST_KEY: &str = "03a107bff3ce10be1d70dd18e74bc09967e4d6309ba50d5f1ddc8664125531b8";
+    /// The COSE_Key for TEST_KEY, as signData returns it
+    const TEST_COSE_KEY: &str =
+        "a401010327200621582003a107bff3ce10be1d70dd18e74bc09967e4d6309ba50d5f1ddc8664125531b8";
+    /// TEST_KEY signing the hex of TEST_HASH for an enterprise address of the key, as signData does when
+    /// handed the hash
+    const TEST_COSE_SIGN1: &str = "84582aa201276761646472657373581d6127e38d0e19e3434e33fbd001d3fe04b5b76763f88acd625e0d770b43a166686173686564f458406233323261333461376562653737363738653538366562353566653330313935356531356162306232376631663663373336663464363938663635333466303358409402ebe31e7ecaa331a31be3259376cd6bfa4e32ba692a3a9bffadb30853ba62d0346509decd1bd3344545d5e38fcb001f6eb50ef02153cf239ac857db13df01";
+
+    fn test_witness() -> Witness {
+        Witness {
+            algorithm: WitnessAlgorithm::Cip8,
+            public_key: TEST_COSE_KEY.to_string(),
+            signature: TEST_COSE_SIGN1.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_cose_sign1() -> Result<()> {
+        let hash = hex::decode(TEST_HASH)?;
+        let message = CoseSign1::from_hex(TEST_COSE_SIGN1)?;
+        assert_eq!(message.payload.as_deref(), Some(TEST_HASH.as_bytes()));
+        assert!(!message.hashed);
+        let address = message.address.clone().unwrap();
+        assert_eq!(address[0], 0x61);
+        assert_eq!(
+            address[1..],
+            Blake2b::<U28>::digest(hex::decode(TEST_KEY)?)[..]
+        );
+
+        let key = witness_public_key(TEST_KEY)?;
+        assert_eq!(witness_public_key(TEST_COSE_KEY)?, key);
+        assert!(message.verify(&key, &hash)?);
+
+        // A signature for some other document, or from some other key, doesn't verify
+        let mut other_hash = hash.clone();
+        other_hash[0] ^= 1;
+        assert!(!message.verify(&key, &other_hash)?);
+        let mut tampered = message.clone();
+        tampered.signature[0] ^= 1;
+        assert!(!tampered.verify(&key, &hash)?);
+        // The protected header is signed too, so the address can't be swapped out
+        let mut moved = message.clone();
+        moved.protected[20] ^= 1;
+        assert!(!moved.verify(&key, &hash)?);
+
+        assert!(matches!(
+            CoseSign1::from_hex("a0"),
+            Err(MetadataError::InvalidValue { .. })
+        ));
+        assert!(CoseSign1::from_hex(&TEST_COSE_SIGN1[..20]).is_err());
+        assert!(witness_public_key("a0").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_cip8_witness() -> Result<()> {
+        let hash = hex::decode(TEST_HASH)?;
+        assert!(test_witness().verify(&hash)?);
+        assert_eq!(
+            test_witness().cose_sign1()?,
+            CoseSign1::from_hex(TEST_COSE_SIGN1)?
+        );
+
+        let mut raw_key = test_witness();
+        raw_key.public_key = TEST_KEY.to_string();
+        assert!(raw_key.verify(&hash)?);
+        raw_key.validate()?;
+
+        let mut prefixed = test_witness();
+        prefixed.public_key = format!("0X{}", TEST_COSE_KEY.to_uppercase());
+        prefixed.signature = format!("0x{TEST_COSE_SIGN1}");
+        prefixed.validate()?;
+        assert!(prefixed.verify(&hash)?);
+
+        let mut wrong_key = test_witness();
+        wrong_key.public_key =
+            "7ea09a34aebb13c9841c71397b1cabfec5ddf950405293dee496cac2f437480a".to_string();
+        assert!(!wrong_key.verify(&hash)?);
+        Ok(())
+    }
+}
@@ -0,0 +1,231 @@
+use std::{fmt::Display, time::Duration};
+
+use thiserror::Error;
+
+/// The ways loading or interpreting a governance metadata document can fail
+///
+/// This implements std::error::Error, so it converts into an anyhow::Error with `?` as usual
+#[derive(Debug, Error)]
+pub enum MetadataError {
+    /// The document couldn't be fetched, such as because the server couldn't be reached
+    #[cfg(feature = "client")]
+    #[error("unable to fetch document: {0}")]
+    Fetch(#[from] reqwest::Error),
+    /// The server responded with something other than a 2xx status; url is where the response came
+    /// from, after following any redirects
+    #[cfg(feature = "client")]
+    #[error(
+        "{url} responded with {status}{}",
+        content_type.as_ref().map(|t| format!(" ({t})")).unwrap_or_default()
+    )]
+    Status {
+        status: reqwest::StatusCode,
+        url: url::Url,
+        content_type: Option<String>,
+    },
+    /// The server redirected somewhere the client won't follow, such as a file:// url or a private address
+    #[cfg(feature = "client")]
+    #[error("refusing to follow redirect from {from} to {to}: {reason}")]
+    Redirect {
+        from: String,
+        to: String,
+        reason: &'static str,
+    },
+    /// The host, or a host redirected to, resolved to an address the client's [crate::AddressPolicy] denies
+    #[cfg(feature = "client")]
+    #[error("refusing to connect to {host} at {address}, which the address policy denies")]
+    DeniedAddress {
+        host: String,
+        address: std::net::IpAddr,
+    },
+    /// The server redirected more times than the configured maximum
+    #[cfg(feature = "client")]
+    #[error("redirected more than {0} times")]
+    TooManyRedirects(usize),
+    /// The server took too long to respond with the document
+    #[error("timed out fetching document after {0:?}")]
+    Timeout(Duration),
+    /// The document is larger than the configured maximum size
+    #[error("document exceeds the maximum size of {limit} bytes")]
+    TooLarge { limit: usize },
+    /// The document doesn't match the hash it was expected to have, such as the one recorded in an on-chain anchor
+    #[error(
+        "document hash {} does not match expected hash {}",
+        hex::encode(actual),
+        hex::encode(expected)
+    )]
+    HashMismatch {
+        expected: [u8; 32],
+        actual: [u8; 32],
+    },
+    /// The document couldn't be read from disk
+    #[error("unable to read document: {0}")]
+    Io(#[from] std::io::Error),
+    /// A data: url is malformed, such as having a payload that isn't valid base64
+    #[error("invalid data url: {0}")]
+    InvalidDataUrl(String),
+    /// A data: url holds something other than a JSON document
+    #[error("unsupported media type: {0}")]
+    UnsupportedMediaType(String),
+    /// The document isn't valid JSON
+    #[error("unable to parse document at byte {position}: {message}")]
+    Parse { position: usize, message: String },
+    /// The document isn't valid JSON-LD
+    #[error("unable to expand document: {0}")]
+    Expand(String),
+    /// The document references a remote @context the loader couldn't provide, such as one that isn't bundled
+    /// when fetching contexts is disabled; without it, none of the document's terms could be expanded
+    #[error("unable to resolve context {iri}: {reason}")]
+    UnresolvableContext { iri: String, reason: String },
+    /// The document contains several nodes that could be parsed, so it's ambiguous which one was meant; objects
+    /// counts every top level object the document expanded to, parseable or not
+    #[error("document contains {parseable} parseable nodes among {objects} objects, expected exactly one")]
+    MultipleDocuments { parseable: usize, objects: usize },
+    /// The document expanded to nothing at all, usually because none of its keys are defined by its @context
+    #[error("document expanded to no objects, expected exactly one; is its @context missing?")]
+    NoObjects,
+    /// A required field is missing from the document; the path locates the object it's missing from, such as
+    /// `authors[2].witness`, and is empty for the top level of the document
+    #[error("no {field} field{}", located(.path))]
+    MissingField { field: String, path: String },
+    /// A field is present, but holds the wrong kind of value; the path locates the object holding it, as above
+    #[error("{field} is not {expected}{}", located(.path))]
+    WrongType {
+        field: String,
+        expected: &'static str,
+        path: String,
+    },
+    /// A field holds the right kind of value, but the value itself is invalid
+    #[error("{field} {reason}")]
+    InvalidValue { field: String, reason: String },
+    /// A field that should hold an IRI holds something else
+    #[error("{field} is not a valid IRI: {iri}")]
+    InvalidIri { field: String, iri: String },
+    /// A uri uses a scheme that isn't allowed, such as javascript:, or isn't an absolute IRI at all
+    #[error("{field} {iri} does not use an allowed scheme")]
+    DisallowedScheme { field: String, iri: String },
+    /// The IRI given for a field to read isn't a valid IRI, such as a typo in the fields of a custom CIP type
+    #[error("{0} is not a valid field IRI")]
+    InvalidFieldIri(String),
+    /// A hash or signature algorithm that this library doesn't know how to use
+    #[error("unsupported algorithm: {0}")]
+    UnsupportedAlgorithm(String),
+    /// The document couldn't be canonicalized for hashing
+    #[error("unable to canonicalize document: {0}")]
+    Canonicalize(String),
+    /// A referenced document leads back to one that is already being loaded
+    #[error("reference cycle back to {0}")]
+    ReferenceCycle(String),
+    /// A referenced document is nested more deeply than the configured maximum
+    #[error("references are nested more than {0} levels deep")]
+    MaxDepth(usize),
+    /// Any other error, such as one raised by a downstream TryFrom<&Node> implementation
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+pub type Result<T, E = MetadataError> = std::result::Result<T, E>;
+
+impl MetadataError {
+    /// Whether fetching the document again might succeed: true for network errors, timeouts, and
+    /// 5xx or 429 responses, but not for other error responses or problems with the document itself
+    pub fn is_transient(&self) -> bool {
+        match self {
+            #[cfg(feature = "client")]
+            MetadataError::Fetch(err) => {
+                err.is_connect() || err.is_timeout() || err.is_request() || err.is_body()
+            }
+            #[cfg(feature = "client")]
+            MetadataError::Status { status, .. } => {
+                status.is_server_error() || *status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            }
+            MetadataError::Timeout(_) => true,
+            _ => false,
+        }
+    }
+}
+
+/// The error for a response that wasn't successful, if it wasn't
+#[cfg(feature = "client")]
+pub(crate) fn check_status(
+    status: reqwest::StatusCode,
+    url: &url::Url,
+    headers: &reqwest::header::HeaderMap,
+) -> Result<()> {
+    if status.is_success() {
+        return Ok(());
+    }
+    let content_type = headers
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    Err(MetadataError::Status {
+        status,
+        url: url.clone(),
+        content_type,
+    })
+}
+
+impl<T: Display> From<iref::InvalidIri<T>> for MetadataError {
+    fn from(err: iref::InvalidIri<T>) -> Self {
+        MetadataError::InvalidFieldIri(err.0.to_string())
+    }
+}
+
+impl MetadataError {
+    /// Locate an error about a field within the part of the document named by the segment, such as `body` or
+    /// `authors[2]`, as parsing returns from it; other errors are left as they are
+    pub(crate) fn within(self, segment: impl Display) -> Self {
+        let nest = |path: String| match path.is_empty() {
+            true => segment.to_string(),
+            false => format!("{segment}.{path}"),
+        };
+        match self {
+            MetadataError::MissingField { field, path } => MetadataError::MissingField {
+                field,
+                path: nest(path),
+            },
+            MetadataError::WrongType {
+                field,
+                expected,
+                path,
+            } => MetadataError::WrongType {
+                field,
+                expected,
+                path: nest(path),
+            },
+            other => other,
+        }
+    }
+}
+
+/// Where in the document an error is, for appending to its message
+fn located(path: &str) -> String {
+    match path.is_empty() {
+        true => String::new(),
+        false => format!(" at {path}"),
+    }
+}
+
+/// Shorthands for turning a missing or mistyped value into the appropriate error
+pub(crate) trait OptionExt<T> {
+    fn missing(self, field: &str) -> Result<T>;
+    fn wrong_type(self, field: &str, expected: &'static str) -> Result<T>;
+}
+
+impl<T> OptionExt<T> for Option<T> {
+    fn missing(self, field: &str) -> Result<T> {
+        self.ok_or_else(|| MetadataError::MissingField {
+            field: field.to_string(),
+            path: String::new(),
+        })
+    }
+
+    fn wrong_type(self, field: &str, expected: &'static str) -> Result<T> {
+        self.ok_or_else(|| MetadataError::WrongType {
+            field: field.to_string(),
+            expected,
+            path: String::new(),
+        })
+    }
+}
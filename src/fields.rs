@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+
+use iref::{Iri, IriBuf};
+
+use crate::cip100::{CIP100Fields, CIP100_FIELDS};
+use crate::error::{MetadataError, Result};
+
+/// The IRIs of the fields of some CIP profile, looked up by name
+///
+/// This lets crates defining their own CIP profiles describe its fields once, extending the CIP-100 fields
+/// where the profile builds on them, and read values with consistent error messages
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldRegistry {
+    fields: HashMap<String, IriBuf>,
+}
+
+impl FieldRegistry {
+    /// A registry with no fields at all
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry of every CIP-100 field, named as in [CIP100Fields], such as "body_comment"
+    pub fn cip100() -> Self {
+        CIP100_FIELDS.registry()
+    }
+
+    /// Register the IRI of the named field, replacing any IRI already registered under that name
+    pub fn register(&mut self, name: impl Into<String>, iri: &str) -> Result<()> {
+        let name = name.into();
+        let iri = IriBuf::new(iri.to_string()).map_err(|_| MetadataError::InvalidIri {
+            field: name.clone(),
+            iri: iri.to_string(),
+        })?;
+        self.fields.insert(name, iri);
+        Ok(())
+    }
+
+    /// Register the IRI of the named field, for building up a registry in a single expression
+    pub fn with_field(mut self, name: impl Into<String>, iri: &str) -> Result<Self> {
+        self.register(name, iri)?;
+        Ok(self)
+    }
+
+    /// The IRI of the named field, if one has been registered
+    pub fn get(&self, name: &str) -> Option<&Iri> {
+        self.fields.get(name).map(|iri| iri.as_iri())
+    }
+
+    /// The IRI of the named field, or an error if none has been registered
+    pub fn iri(&self, name: &str) -> Result<&Iri> {
+        self.get(name).ok_or_else(|| MetadataError::InvalidValue {
+            field: name.to_string(),
+            reason: "is not a registered field".to_string(),
+        })
+    }
+
+    /// The names of every registered field, in no particular order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.fields.keys().map(String::as_str)
+    }
+}
+
+impl CIP100Fields {
+    /// These fields as a registry, to extend with the fields of a profile building on CIP-100
+    pub fn registry(&self) -> FieldRegistry {
+        let fields = [
+            ("hash_algorithm", self.hash_algorithm),
+            ("authors", self.authors),
+            ("body", self.body),
+            ("body_references", self.body_references),
+            ("body_comment", self.body_comment),
+            ("body_external_updates", self.body_external_updates),
+            ("update_title", self.update_title),
+            ("update_uri", self.update_uri),
+            ("reference_type", self.reference_type),
+            (
+                "reference_type_governance_metadata",
+                self.reference_type_governance_metadata,
+            ),
+            ("reference_type_other", self.reference_type_other),
+            ("reference_label", self.reference_label),
+            ("reference_uri", self.reference_uri),
+            ("author_name", self.author_name),
+            ("author_witness", self.author_witness),
+            ("witness_algorithm", self.witness_algorithm),
+            ("witness_public_key", self.witness_public_key),
+            ("witness_signature", self.witness_signature),
+        ];
+        let mut registry = FieldRegistry::new();
+        for (name, iri) in fields {
+            registry
+                .register(name, iri)
+                .expect("the CIP-100 field IRIs are valid");
+        }
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::OptionExt;
+    use crate::util::{get_optional_string, get_required_string};
+    use anyhow::Result;
+    use json_ld::Node;
+    use url::Url;
+
+    /// A made up profile that adds a tagline to the CIP-100 body
+    struct Tagged {
+        hash_algorithm: String,
+        tagline: Option<String>,
+    }
+
+    impl TryFrom<&Node> for Tagged {
+        type Error = MetadataError;
+
+        fn try_from(node: &Node) -> crate::Result<Self> {
+            let fields = FieldRegistry::cip100()
+                .with_field("tagline", "https://example.com/vocab#tagline")?;
+            let body = node
+                .get_any(&fields.iri("body")?)
+                .and_then(|body| body.as_node())
+                .missing("body")?;
+            Ok(Self {
+                hash_algorithm: get_required_string(node, fields.iri("hash_algorithm")?.as_str())?,
+                tagline: get_optional_string(body, fields.iri("tagline")?.as_str())?,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_field_registry() -> Result<()> {
+        let registry = FieldRegistry::cip100();
+        assert_eq!(registry.names().count(), 18);
+        assert_eq!(
+            registry.get("body_comment").map(Iri::as_str),
+            Some(CIP100_FIELDS.body_comment)
+        );
+        assert!(registry.get("tagline").is_none());
+        assert!(registry.iri("tagline").is_err());
+        assert!(FieldRegistry::new().register("bad", "not an iri").is_err());
+
+        let base = Url::parse("https://example.com/metadata.jsonld")?;
+        let content = r#"{
+            "@context": {
+                "CIP100": "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0100/README.md#",
+                "hashAlgorithm": "CIP100:hashAlgorithm",
+                "body": "CIP100:body",
+                "tagline": "https://example.com/vocab#tagline"
+            },
+            "hashAlgorithm": "blake2b-256",
+            "body": { "tagline": "Governance, but fun" }
+        }"#;
+        let tagged: Tagged = crate::parse_str(content, base.clone()).await?;
+        assert_eq!(tagged.hash_algorithm, "blake2b-256");
+        assert_eq!(tagged.tagline.as_deref(), Some("Governance, but fun"));
+
+        let wrong_type = content.replace(r#""Governance, but fun""#, r#"{ "text": "nope" }"#);
+        let result = crate::parse_str::<Tagged>(&wrong_type, base).await;
+        assert!(matches!(
+            result,
+            Err(MetadataError::WrongType { ref field, .. }) if field == "https://example.com/vocab#tagline"
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,209 @@
+use std::{fmt, str::FromStr};
+
+use blake2::{
+    digest::consts::{U28, U32},
+    Blake2b, Digest,
+};
+use sha2::Sha256;
+
+use crate::error::{MetadataError, Result};
+
+/// The algorithm a document declares was used to hash it for signing
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "String", into = "String")
+)]
+pub enum HashAlgorithm {
+    /// blake2b with a 256 bit digest; the algorithm CIP-100 specifies
+    Blake2b256,
+    /// blake2b with a 224 bit digest, as used for Cardano credentials
+    Blake2b224,
+    /// sha256
+    Sha256,
+    /// Some algorithm this library doesn't recognize; kept so that the document still parses
+    Unknown(String),
+}
+
+impl HashAlgorithm {
+    /// The identifiers of every algorithm this library recognizes, as used in the hashAlgorithm field
+    pub const RECOGNIZED: &'static [&'static str] = &["blake2b-256", "blake2b-224", "sha256"];
+
+    /// Whether this is an algorithm this library recognizes, rather than some other string, such as a typo
+    /// like "blake2b256"
+    pub fn is_recognized(&self) -> bool {
+        !matches!(self, HashAlgorithm::Unknown(_))
+    }
+
+    /// The identifier used for this algorithm in the hashAlgorithm field of a document
+    pub fn as_str(&self) -> &str {
+        match self {
+            HashAlgorithm::Blake2b256 => "blake2b-256",
+            HashAlgorithm::Blake2b224 => "blake2b-224",
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Unknown(algorithm) => algorithm,
+        }
+    }
+
+    /// Hash the given bytes with this algorithm
+    pub fn digest(&self, bytes: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            HashAlgorithm::Blake2b256 => Ok(Blake2b::<U32>::digest(bytes).to_vec()),
+            HashAlgorithm::Blake2b224 => Ok(Blake2b::<U28>::digest(bytes).to_vec()),
+            HashAlgorithm::Sha256 => Ok(Sha256::digest(bytes).to_vec()),
+            HashAlgorithm::Unknown(algorithm) => {
+                Err(MetadataError::UnsupportedAlgorithm(algorithm.clone()))
+            }
+        }
+    }
+}
+
+impl From<&str> for HashAlgorithm {
+    fn from(value: &str) -> Self {
+        match value {
+            "blake2b-256" => HashAlgorithm::Blake2b256,
+            "blake2b-224" => HashAlgorithm::Blake2b224,
+            "sha256" => HashAlgorithm::Sha256,
+            algorithm => HashAlgorithm::Unknown(algorithm.to_string()),
+        }
+    }
+}
+
+impl From<String> for HashAlgorithm {
+    fn from(value: String) -> Self {
+        value.as_str().into()
+    }
+}
+
+impl From<HashAlgorithm> for String {
+    fn from(value: HashAlgorithm) -> Self {
+        value.as_str().to_string()
+    }
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(s.into())
+    }
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Checks the blake2b-256 hash of content fed to it a chunk at a time, so a download can be verified as it
+/// arrives rather than hashed again once it's all in memory
+#[cfg(feature = "client")]
+pub(crate) struct HashCheck {
+    expected: [u8; 32],
+    hasher: Blake2b<U32>,
+}
+
+#[cfg(feature = "client")]
+impl HashCheck {
+    pub(crate) fn new(expected: [u8; 32]) -> Self {
+        Self {
+            expected,
+            hasher: Blake2b::new(),
+        }
+    }
+
+    pub(crate) fn update(&mut self, chunk: &[u8]) {
+        self.hasher.update(chunk);
+    }
+
+    /// Fail if the content fed in so far doesn't have the expected hash
+    pub(crate) fn finish(self) -> Result<()> {
+        let actual: [u8; 32] = self.hasher.finalize().into();
+        if actual != self.expected {
+            return Err(MetadataError::HashMismatch {
+                expected: self.expected,
+                actual,
+            });
+        }
+        Ok(())
+    }
+
+    /// Check content that's already been loaded in full, such as from a cache, if a hash is expected at all
+    pub(crate) fn check(expected: Option<[u8; 32]>, content: Vec<u8>) -> Result<Vec<u8>> {
+        if let Some(expected) = expected {
+            let mut check = Self::new(expected);
+            check.update(&content);
+            check.finish()?;
+        }
+        Ok(content)
+    }
+}
+
+/// How a document body is turned into bytes to hash for signing
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CanonicalizationMethod {
+    /// The body is converted to RDF and canonicalized with URDNA2015, as CIP-100 mandates
+    #[default]
+    Urdna2015,
+    /// The body, as [crate::Document::to_jsonld] writes it, is canonicalized as JSON per RFC 8785 (JCS); not
+    /// CIP-100, but used by some tooling, so documents they signed can still be checked
+    Jcs,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "client")]
+    #[test]
+    fn test_hash_check() {
+        let content = include_bytes!("../tests/fixtures/example.json");
+        let expected: [u8; 32] = Blake2b::<U32>::digest(content).into();
+        let mut check = HashCheck::new(expected);
+        for chunk in content.chunks(7) {
+            check.update(chunk);
+        }
+        assert!(check.finish().is_ok());
+
+        let mut truncated = HashCheck::new(expected);
+        truncated.update(&content[1..]);
+        assert!(matches!(
+            truncated.finish(),
+            Err(MetadataError::HashMismatch { actual, .. }) if actual != expected
+        ));
+        assert!(HashCheck::check(None, vec![1, 2, 3]).is_ok());
+        assert!(HashCheck::check(Some(expected), content.to_vec()).is_ok());
+    }
+
+    #[test]
+    fn test_parse_hash_algorithm() {
+        assert_eq!(
+            HashAlgorithm::from("blake2b-256"),
+            HashAlgorithm::Blake2b256
+        );
+        assert_eq!(
+            HashAlgorithm::from("blake2b-224"),
+            HashAlgorithm::Blake2b224
+        );
+        assert_eq!(HashAlgorithm::from("sha256"), HashAlgorithm::Sha256);
+        assert_eq!(
+            HashAlgorithm::from("blake2b256"),
+            HashAlgorithm::Unknown("blake2b256".to_string())
+        );
+        assert_eq!(HashAlgorithm::Blake2b224.to_string(), "blake2b-224");
+    }
+
+    #[test]
+    fn test_digest_lengths() -> Result<()> {
+        assert_eq!(HashAlgorithm::Blake2b256.digest(b"")?.len(), 32);
+        assert_eq!(HashAlgorithm::Blake2b224.digest(b"")?.len(), 28);
+        assert_eq!(
+            hex::encode(HashAlgorithm::Sha256.digest(b"")?),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert!(HashAlgorithm::from("md5").digest(b"").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,281 @@
+use std::{fmt, ops::Deref};
+
+use iref::Iri;
+use json_ld::{
+    object::{Literal, Value},
+    ExpandedDocument, Node, Object,
+};
+
+use crate::error::{OptionExt, Result};
+
+/// A piece of text, along with the language it is written in, if the document says
+///
+/// The language usually comes from `@language` in the document context, but may be set on
+/// individual values as well
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LangString {
+    /// The text itself
+    pub value: String,
+    /// The language tag of the text, such as "en-us"
+    pub language: Option<String>,
+}
+
+impl LangString {
+    /// Text in the given language, or with no language tag if None
+    pub fn new(value: impl Into<String>, language: Option<String>) -> Self {
+        Self {
+            value: value.into(),
+            language,
+        }
+    }
+
+    /// The plain text, without its language tag, exactly as the document gives it, which may differ from
+    /// [LangString::normalized]
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// The language tag of the text, if any
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// The text without leading or trailing whitespace, control characters other than newlines and tabs, or
+    /// zero-width characters, for display and exact matching; see [crate::MetadataClient::with_text_normalization]
+    /// to parse documents this way
+    pub fn normalized(&self) -> String {
+        normalize(&self.value)
+    }
+
+    /// Read a string value and its language from an expanded JSON-LD object, if it is a string
+    pub(crate) fn from_object(object: &Object) -> Option<Self> {
+        let value = object.as_str()?;
+        let language = object
+            .language()
+            .map(|language| language.as_str().to_string());
+        Some(Self::new(value, language))
+    }
+}
+
+impl From<&str> for LangString {
+    fn from(value: &str) -> Self {
+        Self::new(value, None)
+    }
+}
+
+impl From<String> for LangString {
+    fn from(value: String) -> Self {
+        Self::new(value, None)
+    }
+}
+
+impl fmt::Display for LangString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.value)
+    }
+}
+
+/// Every language variant given for a single text field, such as from a JSON-LD language map
+///
+/// Most documents only provide one, but a document may offer the same text in several languages
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Localized(pub Vec<LangString>);
+
+impl Localized {
+    /// Pick the variant best suited to the given language tag, such as a user's locale
+    ///
+    /// Prefers an exact match, then a variant sharing the primary language ("en" for "en-us"),
+    /// then an untagged variant, and finally whichever variant came first
+    pub fn best_for_language(&self, tag: &str) -> Option<&LangString> {
+        let primary = |tag: &str| tag.split('-').next().unwrap_or(tag).to_ascii_lowercase();
+        let exact = self.iter().find(|text| {
+            text.language()
+                .is_some_and(|language| language.eq_ignore_ascii_case(tag))
+        });
+        let similar = || {
+            self.iter().find(|text| {
+                text.language()
+                    .is_some_and(|language| primary(language) == primary(tag))
+            })
+        };
+        let untagged = || self.iter().find(|text| text.language().is_none());
+        exact
+            .or_else(similar)
+            .or_else(untagged)
+            .or_else(|| self.first())
+    }
+
+    /// Read every variant of a text field from an expanded JSON-LD node, or None if the field is
+    /// absent; `name` is used to describe the field in errors
+    pub(crate) fn parse(node: &Node, field: &str, name: &str) -> Result<Option<Self>> {
+        let variants = node
+            .get(&Iri::new(field)?)
+            .map(|object| LangString::from_object(object).wrong_type(name, "a string"))
+            .collect::<Result<Vec<_>>>()?;
+        Ok((!variants.is_empty()).then_some(Self(variants)))
+    }
+}
+
+impl Deref for Localized {
+    type Target = [LangString];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl IntoIterator for Localized {
+    type Item = LangString;
+    type IntoIter = std::vec::IntoIter<LangString>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl From<LangString> for Localized {
+    fn from(value: LangString) -> Self {
+        Self(vec![value])
+    }
+}
+
+impl From<Vec<LangString>> for Localized {
+    fn from(value: Vec<LangString>) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for Localized {
+    fn from(value: &str) -> Self {
+        LangString::from(value).into()
+    }
+}
+
+impl From<String> for Localized {
+    fn from(value: String) -> Self {
+        LangString::from(value).into()
+    }
+}
+
+/// Shows the first variant, which is usually the only one
+impl fmt::Display for Localized {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.first() {
+            Some(text) => text.fmt(f),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Zero-width characters, which are invisible but break exact matching, such as of an author's name; the
+/// zero-width joiner and non-joiner are kept, since Persian and Indic scripts and emoji sequences need them
+const ZERO_WIDTH: &[char] = &['\u{200B}', '\u{2060}', '\u{FEFF}'];
+
+/// Strip disallowed characters from the text, then trim it
+pub(crate) fn normalize(text: &str) -> String {
+    let stripped: String = text.chars().filter(|c| !is_disallowed(*c)).collect();
+    stripped.trim().to_string()
+}
+
+fn is_disallowed(c: char) -> bool {
+    ZERO_WIDTH.contains(&c) || (c.is_control() && c != '\n' && c != '\t')
+}
+
+/// Normalize every string in an expanded document, as if the document had been written without the stray
+/// characters; IRIs are left alone, since expansion has already resolved them
+pub(crate) fn normalize_expanded(expanded: ExpandedDocument) -> ExpandedDocument {
+    expanded
+        .into_objects()
+        .into_iter()
+        .map(|mut object| {
+            normalize_object(&mut object);
+            object
+        })
+        .collect()
+}
+
+fn normalize_object(object: &mut Object) {
+    match object {
+        Object::Value(Value::Literal(Literal::String(text), _)) => {
+            *text = normalize(text).as_str().into();
+        }
+        Object::Value(Value::LangString(text)) => {
+            let (value, language, direction) = text.clone().into_parts();
+            *text =
+                json_ld::LangString::new(normalize(&value).as_str().into(), language, direction)
+                    .expect("a language string keeps its language or direction");
+        }
+        Object::Value(_) => {}
+        Object::Node(node) => {
+            for (_, objects) in node.properties_mut().iter_mut() {
+                objects
+                    .iter_mut()
+                    .for_each(|object| normalize_object(object));
+            }
+        }
+        Object::List(list) => list.iter_mut().for_each(|object| normalize_object(object)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lang_string() {
+        let text = LangString::new("Bonjour", Some("fr".to_string()));
+        assert_eq!(text.value(), "Bonjour");
+        assert_eq!(text.language(), Some("fr"));
+        assert_eq!(text.to_string(), "Bonjour");
+
+        let untagged = LangString::from("Hello");
+        assert_eq!(untagged.language(), None);
+        assert_ne!(untagged, LangString::new("Hello", Some("en".to_string())));
+    }
+
+    #[test]
+    fn test_best_for_language() {
+        let localized = Localized(vec![
+            LangString::new("Hello", Some("en-US".to_string())),
+            LangString::new("Bonjour", Some("fr".to_string())),
+            LangString::new("Hallo", None),
+        ]);
+        let best = |tag| localized.best_for_language(tag).map(LangString::value);
+        assert_eq!(best("en-us"), Some("Hello"));
+        assert_eq!(best("en-GB"), Some("Hello"));
+        assert_eq!(best("fr-CA"), Some("Bonjour"));
+        assert_eq!(best("ja"), Some("Hallo"));
+
+        let tagged = Localized(localized[..2].to_vec());
+        assert_eq!(
+            tagged.best_for_language("ja").map(LangString::value),
+            Some("Hello")
+        );
+        assert_eq!(Localized::default().best_for_language("en"), None);
+    }
+
+    #[test]
+    fn test_normalized() {
+        let padded = LangString::new("  Pi\u{2060} Lanningham\u{FEFF}\r\n", None);
+        assert_eq!(padded.value(), "  Pi\u{2060} Lanningham\u{FEFF}\r\n");
+        assert_eq!(padded.normalized(), "Pi Lanningham");
+        assert_eq!(
+            normalize("\u{200B}line one\n\tline two\u{7}"),
+            "line one\n\tline two"
+        );
+        assert_eq!(normalize("Ünïcödé ✓"), "Ünïcödé ✓");
+        assert_eq!(normalize(" \u{200B} "), "");
+        // Joiners change how text renders, and what it means, so they're kept
+        assert_eq!(normalize("می\u{200C}خواهم"), "می\u{200C}خواهم");
+        assert_eq!(
+            normalize("\u{1F469}\u{200D}\u{1F467}"),
+            "\u{1F469}\u{200D}\u{1F467}"
+        );
+    }
+}
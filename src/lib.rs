@@ -1,91 +1,3101 @@
+/// A debug level tracing event when the tracing feature is on, and nothing at all when it's off
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)*);
+    };
+}
+
+#[cfg(feature = "client")]
+mod address;
+#[cfg(feature = "anchor")]
+mod anchor;
+mod any;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(feature = "client")]
+mod bulk;
+#[cfg(feature = "client")]
+mod cache;
 mod cip100;
+mod cip108;
+mod cip119;
+mod cip136;
+mod context;
+#[cfg(feature = "cip8")]
+mod cose;
+mod error;
+mod fields;
+mod hash;
+mod lang;
+mod metrics;
+#[cfg(feature = "client")]
+mod redirect;
+mod references;
+#[cfg(feature = "schema")]
+mod schema;
+#[cfg(feature = "client")]
+mod scheme;
+#[cfg(test)]
+mod test_server;
+pub mod util;
+mod validation;
+mod warning;
 
-use anyhow::*;
-use iref::IriBuf;
+use iref::{Iri, IriBuf};
 use json_ld::{
     syntax::{Parse, Value},
-    JsonLdProcessor, Node, RemoteDocument,
+    ExpandedDocument, JsonLdProcessor, Loader, Node, RemoteDocument,
 };
+use std::fmt::Display;
 use url::Url;
+#[cfg(feature = "client")]
+use {
+    cache::{ResponseCache, DEFAULT_CACHE_SIZE},
+    context::ClientLoader,
+    futures::{stream, Stream, StreamExt},
+    hash::HashCheck,
+    references::DEFAULT_MAX_DEPTH,
+    std::{collections::HashSet, path::Path, sync::Arc, time::Duration},
+    tokio::{
+        io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt},
+        sync::Mutex,
+    },
+};
 
+#[cfg(feature = "client")]
+pub use address::AddressPolicy;
+#[cfg(feature = "anchor")]
+pub use anchor::Anchor;
+pub use any::AnyDocument;
+#[cfg(feature = "client")]
+pub use bulk::AnchorCheck;
 pub use cip100::*;
+pub use cip108::*;
+pub use cip119::*;
+pub use cip136::*;
+#[cfg(feature = "client")]
+pub use context::FallbackContextLoader;
+pub use context::{
+    declared_contexts, DeclaredContext, StaticContextLoader, CIP100_CONTEXT, CIP108_CONTEXT,
+    CIP119_CONTEXT, CIP136_CONTEXT,
+};
+#[cfg(feature = "cip8")]
+pub use cose::CoseSign1;
+pub use error::{MetadataError, Result};
+pub use fields::FieldRegistry;
+pub use hash::{CanonicalizationMethod, HashAlgorithm};
+pub use lang::{LangString, Localized};
+pub use metrics::DocumentMetrics;
+pub use references::{DocumentTree, ReferencedDocument};
+#[cfg(feature = "schema")]
+pub use schema::schema;
+#[cfg(feature = "client")]
+pub use scheme::{Resolved, SchemeHandler};
+pub use validation::ValidationIssue;
+pub use warning::Warning;
+
+use error::OptionExt;
+
+#[cfg(feature = "client")]
+/// How long to wait for a document to be fetched, unless configured otherwise
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The largest document to download, unless configured otherwise; governance metadata is
+/// typically a few kilobytes, so anything near this is either bloated or malicious
+#[cfg(feature = "client")]
+const DEFAULT_MAX_SIZE: usize = 512 * 1024;
+
+/// The schemes that reference and update uris may use, unless configured otherwise; anything else, such
+/// as javascript: or file:, is rejected when a document is loaded
+pub const DEFAULT_URI_SCHEMES: &[&str] = &["https", "http", "ipfs", "ar"];
+
+/// The content types a document may be served with, once content type validation is turned on
+#[cfg(feature = "client")]
+pub const DEFAULT_CONTENT_TYPES: &[&str] =
+    &["application/json", "application/ld+json", "text/plain"];
+
+/// Where ipfs:// urls are fetched from, unless configured otherwise
+#[cfg(feature = "client")]
+const DEFAULT_IPFS_GATEWAY: &str = "https://ipfs.io/ipfs/";
+
+/// Where ar:// urls are fetched from, unless configured otherwise
+#[cfg(feature = "client")]
+const DEFAULT_ARWEAVE_GATEWAY: &str = "https://arweave.net/";
+
+/// How many redirects to follow when fetching a document, unless configured otherwise
+#[cfg(feature = "client")]
+const DEFAULT_MAX_REDIRECTS: usize = 5;
+
+/// The User-Agent sent with every request, unless configured otherwise
+#[cfg(feature = "client")]
+const DEFAULT_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// How many times to try fetching a document, unless configured otherwise; by default failures aren't retried
+#[cfg(feature = "client")]
+const DEFAULT_MAX_ATTEMPTS: u32 = 1;
+
+/// How long to wait before the first retry, unless configured otherwise; each later retry waits twice as long
+#[cfg(feature = "client")]
+const DEFAULT_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// The longest the wait between retries grows to, however many attempts are allowed
+#[cfg(feature = "client")]
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// The wait before the retry after one that waited delay: twice as long, up to [MAX_RETRY_DELAY]
+#[cfg(feature = "client")]
+pub(crate) fn next_retry_delay(delay: Duration) -> Duration {
+    delay.saturating_mul(2).min(MAX_RETRY_DELAY)
+}
 
 /// A client for fetching governance metadata from the web
-pub struct MetadataClient {}
+///
+/// Remote contexts referenced by documents are resolved with the JSON-LD loader L; by default that's a
+/// [StaticContextLoader], which knows the well known CIP contexts and never touches the network
+///
+/// Cloning a client is cheap, and the clones share its connection pool, contexts, loader and cache, so a
+/// configured client can be handed to as many tasks as needed
+#[cfg(feature = "client")]
+pub struct MetadataClient<L = StaticContextLoader> {
+    client: reqwest::Client,
+    timeout: Duration,
+    max_size: usize,
+    max_depth: usize,
+    max_attempts: u32,
+    retry_delay: Duration,
+    max_redirects: usize,
+    private_redirects: bool,
+    address_policy: Option<Arc<AddressPolicy>>,
+    ipfs_gateway: Url,
+    arweave_gateway: Url,
+    schemes: scheme::SchemeHandlers,
+    headers: reqwest::header::HeaderMap,
+    options: ParseOptions,
+    content_types: Option<Vec<String>>,
+    cache: Option<Arc<ResponseCache>>,
+    contexts: Arc<StaticContextLoader>,
+    loader: Option<Arc<Mutex<L>>>,
+    fetch_contexts: bool,
+}
+
+#[cfg(feature = "client")]
+impl<L> Clone for MetadataClient<L> {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone(),
+            timeout: self.timeout,
+            max_size: self.max_size,
+            max_depth: self.max_depth,
+            max_attempts: self.max_attempts,
+            retry_delay: self.retry_delay,
+            max_redirects: self.max_redirects,
+            private_redirects: self.private_redirects,
+            address_policy: self.address_policy.clone(),
+            ipfs_gateway: self.ipfs_gateway.clone(),
+            arweave_gateway: self.arweave_gateway.clone(),
+            schemes: self.schemes.clone(),
+            headers: self.headers.clone(),
+            options: self.options.clone(),
+            content_types: self.content_types.clone(),
+            cache: self.cache.clone(),
+            contexts: self.contexts.clone(),
+            loader: self.loader.clone(),
+            fetch_contexts: self.fetch_contexts,
+        }
+    }
+}
+
+#[cfg(feature = "client")]
+impl Default for MetadataClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
+#[cfg(feature = "client")]
 impl MetadataClient {
     pub fn new() -> MetadataClient {
-        MetadataClient {}
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .user_agent(DEFAULT_USER_AGENT)
+            .build()
+            .expect("the default reqwest client can be built");
+        Self::with_client(client)
+    }
+
+    /// Create a metadata client that fetches documents with the given reqwest client,
+    /// for example one configured with a proxy or user agent
+    ///
+    /// Build it with `redirect(reqwest::redirect::Policy::none())` to have redirects checked as described on
+    /// [MetadataClient::with_max_redirects]; otherwise the client follows redirects by its own policy
+    pub fn with_client(client: reqwest::Client) -> MetadataClient {
+        MetadataClient {
+            client,
+            timeout: DEFAULT_TIMEOUT,
+            max_size: DEFAULT_MAX_SIZE,
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            retry_delay: DEFAULT_RETRY_DELAY,
+            max_redirects: DEFAULT_MAX_REDIRECTS,
+            private_redirects: false,
+            address_policy: None,
+            ipfs_gateway: Url::parse(DEFAULT_IPFS_GATEWAY)
+                .expect("the default gateway is a valid url"),
+            arweave_gateway: Url::parse(DEFAULT_ARWEAVE_GATEWAY)
+                .expect("the default gateway is a valid url"),
+            schemes: scheme::SchemeHandlers::default(),
+            headers: reqwest::header::HeaderMap::new(),
+            options: ParseOptions::default(),
+            content_types: None,
+            cache: None,
+            contexts: Arc::new(StaticContextLoader::new()),
+            loader: None,
+            fetch_contexts: false,
+        }
+    }
+
+    /// Resolve references to the given context IRI with the given JSON content, in addition to the
+    /// CIP-100, CIP-108, CIP-119 and CIP-136 contexts bundled with this crate; contexts are never fetched
+    ///
+    /// Any clones of this client keep the contexts they already had
+    pub fn with_context(self, iri: IriBuf, content: &str) -> Result<Self> {
+        let contexts = Arc::unwrap_or_clone(self.contexts).with_context(iri, content)?;
+        Ok(MetadataClient {
+            contexts: Arc::new(contexts),
+            ..self
+        })
+    }
+}
+
+#[cfg(feature = "client")]
+impl<L> MetadataClient<L> {
+    /// Resolve remote contexts and other documents referenced during expansion with the given
+    /// JSON-LD loader, for example to add caching, authentication, or an offline mirror
+    ///
+    /// Loads through this client, and its clones, share the one loader, so they take turns loading contexts
+    /// through it
+    pub fn with_loader<M>(self, loader: M) -> MetadataClient<M> {
+        MetadataClient {
+            client: self.client,
+            timeout: self.timeout,
+            max_size: self.max_size,
+            max_depth: self.max_depth,
+            max_attempts: self.max_attempts,
+            retry_delay: self.retry_delay,
+            max_redirects: self.max_redirects,
+            private_redirects: self.private_redirects,
+            address_policy: self.address_policy,
+            ipfs_gateway: self.ipfs_gateway,
+            arweave_gateway: self.arweave_gateway,
+            schemes: self.schemes,
+            headers: self.headers,
+            options: self.options,
+            content_types: self.content_types,
+            cache: self.cache,
+            contexts: Arc::new(StaticContextLoader::empty()),
+            loader: Some(Arc::new(Mutex::new(loader))),
+            fetch_contexts: self.fetch_contexts,
+        }
+    }
+
+    /// Refuse to download documents larger than the given number of bytes; defaults to 512KiB
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Give up on fetching a document if it takes longer than the given duration; defaults to 30 seconds
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Try fetching a document up to max_attempts times, waiting base_delay before the first retry and
+    /// doubling the wait each time after, up to a minute; only network errors, timeouts, and 5xx or 429
+    /// responses are retried
+    pub fn with_retries(mut self, max_attempts: u32, base_delay: Duration) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self.retry_delay = base_delay;
+        self
+    }
+
+    /// Fetch ipfs:// urls through the given HTTP gateway; defaults to https://ipfs.io/ipfs/
+    ///
+    /// Only the fetch goes through the gateway; the document is still expanded with its ipfs:// url as the base IRI
+    pub fn with_ipfs_gateway(mut self, gateway: Url) -> Self {
+        self.ipfs_gateway = with_trailing_slash(gateway);
+        self
+    }
+
+    /// Fetch ar:// urls through the given Arweave gateway; defaults to https://arweave.net/
+    ///
+    /// As with IPFS, the document is still expanded with its ar:// url as the base IRI
+    pub fn with_arweave_gateway(mut self, gateway: Url) -> Self {
+        self.arweave_gateway = with_trailing_slash(gateway);
+        self
+    }
+
+    /// Load urls of the given scheme, such as a storage network or an internal document store, with the given
+    /// handler, which resolves each to an http url to fetch or to the document itself; see [Resolved]
+    ///
+    /// A handler for ipfs or ar takes the place of the gateway, and the scheme is also allowed in reference and
+    /// update uris, unless [MetadataClient::without_uri_validation] already allows every scheme
+    pub fn register_scheme<F>(mut self, scheme: &str, handler: F) -> Self
+    where
+        F: Fn(&Url) -> Result<Resolved> + Send + Sync + 'static,
+    {
+        self.schemes.insert(scheme, Arc::new(handler));
+        self.options.allow_scheme(scheme);
+        self
+    }
+
+    /// Send the given User-Agent with every request, rather than this crate's name and version, such as to
+    /// identify your service to the gateways and archives that ask for it
+    ///
+    /// This also replaces the User-Agent of a reqwest client given to [MetadataClient::with_client]
+    pub fn with_user_agent(self, user_agent: &str) -> Result<Self> {
+        self.with_header(reqwest::header::USER_AGENT.as_str(), user_agent)
+    }
+
+    /// Send the given header with every request, such as an API key for an IPFS gateway, replacing any
+    /// value set for it before
+    ///
+    /// Headers are only sent to the origin of the url being loaded; if it redirects elsewhere, only the
+    /// User-Agent is sent on, so a key meant for one host isn't handed to another
+    pub fn with_header(mut self, name: &str, value: &str) -> Result<Self> {
+        let (name, value) = header(name, value)?;
+        self.headers.insert(name, value);
+        Ok(self)
+    }
+
+    /// Follow at most the given number of redirects when fetching a document; defaults to 5
+    ///
+    /// Redirects are only followed to http and https urls, and not to localhost or private network
+    /// addresses, so a malicious host can't point the client at a local file or internal service
+    pub fn with_max_redirects(mut self, max_redirects: usize) -> Self {
+        self.max_redirects = max_redirects;
+        self
+    }
+
+    /// Follow redirects to localhost and private network addresses, such as in tests against a local server
+    pub fn with_private_redirects(mut self) -> Self {
+        self.private_redirects = true;
+        self
+    }
+
+    /// Refuse to connect to the addresses the policy denies, such as loopback, private network and cloud
+    /// metadata addresses; see [AddressPolicy] for the ranges denied by default
+    ///
+    /// Hosts are checked each time they're resolved to connect, including after redirects, so this holds even
+    /// for hosts that change what they resolve to. This replaces the reqwest client, including one given to
+    /// [MetadataClient::with_client], with one that resolves hosts through the policy; to keep settings such
+    /// as TLS roots, use [MetadataClient::with_address_policy_and_builder] instead
+    ///
+    /// Proxies, including any set by the HTTP_PROXY and HTTPS_PROXY environment variables, are never used,
+    /// since a proxy resolves the hosts it connects to itself, out of reach of the policy
+    pub fn with_address_policy(self, policy: AddressPolicy) -> Self {
+        self.with_address_policy_and_builder(
+            policy,
+            reqwest::Client::builder().user_agent(DEFAULT_USER_AGENT),
+        )
+        .expect("the default reqwest client can be built")
+    }
+
+    /// Refuse to connect to the addresses the policy denies, as [MetadataClient::with_address_policy] does,
+    /// fetching documents with a client built from the given builder
+    ///
+    /// The builder's settings are kept, except that redirects are left to this client to check, hosts are
+    /// resolved through the policy, and proxies are turned off, for the same reason as above; fails if the
+    /// builder can't build a client
+    pub fn with_address_policy_and_builder(
+        mut self,
+        policy: AddressPolicy,
+        builder: reqwest::ClientBuilder,
+    ) -> Result<Self> {
+        let policy = Arc::new(policy);
+        self.client = builder
+            .redirect(reqwest::redirect::Policy::none())
+            .no_proxy()
+            .dns_resolver(policy.clone().resolver())
+            .build()?;
+        self.address_policy = Some(policy);
+        Ok(self)
+    }
+
+    /// Fetch any remote context the loader can't provide, such as one that isn't bundled with this crate, as
+    /// documents are fetched: within this client's timeout, size limit, redirect checks and address policy
+    ///
+    /// Contexts are fetched without holding the loader, so other loads through this client and its clones
+    /// carry on meanwhile; configure the client before calling this, or use [MetadataClient::context_loader]
+    /// for a loader that fetches the same way outside a client
+    pub fn with_context_fetching(mut self) -> Self {
+        self.fetch_contexts = true;
+        self
+    }
+
+    /// A [FallbackContextLoader] that fetches contexts with this client's settings, as
+    /// [MetadataClient::with_context_fetching] does
+    pub fn context_loader(&self) -> FallbackContextLoader {
+        FallbackContextLoader::from_client(self.clone().with_loader(StaticContextLoader::new()))
+    }
+
+    /// Only accept documents whose reference and update uris use one of the given schemes; defaults to
+    /// https, http, ipfs and ar
+    pub fn with_uri_schemes<S: Into<String>>(
+        mut self,
+        schemes: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.options.uri_schemes = Some(schemes.into_iter().map(Into::into).collect());
+        self
     }
 
+    /// Accept reference and update uris with any scheme, for lenient parsing of unusual documents
+    pub fn without_uri_validation(mut self) -> Self {
+        self.options.uri_schemes = None;
+        self
+    }
+
+    /// Reject documents whose hashAlgorithm isn't one of [HashAlgorithm::RECOGNIZED], rather than parsing them
+    /// with an unknown algorithm that fails only once a signature is checked
+    pub fn with_strict_hash_algorithm(mut self) -> Self {
+        self.options.strict_hash_algorithm = true;
+        self
+    }
+
+    /// Trim the text of documents, and strip control and zero-width characters from it, so that stray
+    /// characters don't break exact matching or display; text is kept exactly as written by default
+    ///
+    /// Signatures are over the text as written, so a normalized document that had any stray characters no
+    /// longer verifies; use [LangString::normalized] on a document parsed as is to have both
+    pub fn with_text_normalization(mut self) -> Self {
+        self.options.normalize_text = true;
+        self
+    }
+
+    /// Follow at most the given number of levels of references in load_with_references; defaults to 4
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Reject documents served with a Content-Type other than application/json, application/ld+json or
+    /// text/plain, which usually means a broken link to an HTML error page
+    ///
+    /// Off by default, since many IPFS gateways mislabel content
+    pub fn with_content_type_validation(self) -> Self {
+        self.with_content_types(DEFAULT_CONTENT_TYPES.iter().copied())
+    }
+
+    /// Reject documents served with a Content-Type other than the given media types, ignoring any parameters
+    /// such as charset
+    pub fn with_content_types<S: Into<String>>(
+        mut self,
+        content_types: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.content_types = Some(content_types.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Remember the ETag and Last-Modified of each document fetched, and send them as If-None-Match and
+    /// If-Modified-Since when fetching it again, reusing the body already downloaded on a 304 Not Modified
+    ///
+    /// Only documents served with one of those headers are kept, in memory, up to 16MiB of them; the least
+    /// recently used are dropped to make room for others. Clones of the client share the one cache
+    pub fn with_cache(self) -> Self {
+        self.with_cache_size(DEFAULT_CACHE_SIZE)
+    }
+
+    /// Cache documents as [MetadataClient::with_cache] does, but hold at most the given number of bytes of them
+    pub fn with_cache_size(mut self, max_size: usize) -> Self {
+        self.cache = Some(Arc::new(ResponseCache::new(max_size)));
+        self
+    }
+}
+
+#[cfg(feature = "client")]
+impl<L> MetadataClient<L>
+where
+    L: Loader<IriBuf>,
+    L::Error: Display,
+{
     /// Load a document of type T from the given JSON-LD document
-    pub async fn load<T: for<'a> TryFrom<&'a Node>>(&self, url: Url) -> Result<Document> {
+    ///
+    /// Fails if the document contains more than one node that can be parsed as a T; use load_all for those
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(url = %url))
+    )]
+    pub async fn load<T>(&self, url: Url) -> Result<T>
+    where
+        T: for<'a> TryFrom<&'a Node>,
+        for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+    {
+        let content = self.fetch(url.clone()).await?;
+        parse(&content, url, &mut ClientLoader(self), &self.options).await
+    }
+
+    /// Load a document of whichever supported CIP it follows, for when that isn't known in advance
+    ///
+    /// The CIP is chosen by which body field the document has; see [AnyDocument]
+    pub async fn load_any(&self, url: Url) -> Result<AnyDocument> {
+        self.load(url).await
+    }
+
+    /// Load every node of type T from the given JSON-LD document, such as one with a top level @graph
+    ///
+    /// Nodes that can't be parsed as a T are skipped; if none can, the error from the first is returned
+    pub async fn load_all<T>(&self, url: Url) -> Result<Vec<T>>
+    where
+        T: for<'a> TryFrom<&'a Node>,
+        for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+    {
+        let content = self.fetch(url.clone()).await?;
+        parse_all(&content, url, &mut ClientLoader(self), &self.options).await
+    }
+
+    /// Load a document of type T from each of the given urls, fetching up to concurrency of them at once
+    ///
+    /// Results are yielded as each document finishes loading, not in the order of the urls, so each is
+    /// paired with the url it was loaded from; one document failing doesn't stop the rest
+    pub fn load_many<'a, T, I>(
+        &'a self,
+        urls: I,
+        concurrency: usize,
+    ) -> impl Stream<Item = (Url, Result<T>)> + 'a
+    where
+        T: for<'b> TryFrom<&'b Node> + 'a,
+        for<'b> <T as TryFrom<&'b Node>>::Error: Into<MetadataError>,
+        I: IntoIterator<Item = Url>,
+        I::IntoIter: 'a,
+    {
+        stream::iter(urls)
+            .map(move |url| async move {
+                let result = self.load(url.clone()).await;
+                (url, result)
+            })
+            .buffer_unordered(concurrency.max(1))
+    }
+
+    /// Check every anchor in newline-delimited JSON, such as a chain indexer's export, where each line is an
+    /// object like `{"url": "https://...", "hash": "<hex blake2b-256>"}`; each is loaded as a T with
+    /// [MetadataClient::load_verified], up to concurrency of them at once
+    ///
+    /// One [AnchorCheck] is yielded for each line that isn't blank, in the order of the lines, whether or not
+    /// the line could be parsed; reading stops at the first error reading the input, which is reported as a
+    /// failed check of the line it happened on
+    pub fn verify_anchors<'a, T, R>(
+        &'a self,
+        reader: R,
+        concurrency: usize,
+    ) -> impl Stream<Item = AnchorCheck> + 'a
+    where
+        T: for<'b> TryFrom<&'b Node> + 'a,
+        for<'b> <T as TryFrom<&'b Node>>::Error: Into<MetadataError>,
+        R: AsyncBufRead + Unpin + 'a,
+    {
+        let lines = stream::unfold(Some((reader.lines(), 0)), |state| async move {
+            let (mut lines, number) = state?;
+            let number = number + 1;
+            match lines.next_line().await {
+                Ok(Some(line)) => Some((Ok((number, line)), Some((lines, number)))),
+                Ok(None) => None,
+                Err(err) => Some((Err((number, err)), None)),
+            }
+        });
+        lines
+            .filter(|line| {
+                let blank = matches!(line, Ok((_, line)) if line.trim().is_empty());
+                std::future::ready(!blank)
+            })
+            .map(move |line| async move {
+                let (number, line) = match line {
+                    Ok(line) => line,
+                    Err((number, err)) => {
+                        return AnchorCheck::new(number, String::new(), Err(err.into()))
+                    }
+                };
+                let (url, anchor) = bulk::parse_anchor(&line);
+                let result = match anchor {
+                    Ok((parsed, hash)) => self.load_verified::<T>(parsed, hash).await.map(drop),
+                    Err(err) => Err(err),
+                };
+                AnchorCheck::new(number, url, result)
+            })
+            .buffered(concurrency.max(1))
+    }
+
+    /// How many top level objects the document at the given url expands to, without parsing any of them
+    ///
+    /// Anything other than 1 means [MetadataClient::load] will fail, so this is a cheap pre-flight check for
+    /// documents with a top level @graph, or with no @context to give their keys meaning
+    pub async fn count_objects(&self, url: Url) -> Result<usize> {
+        let content = self.fetch(url.clone()).await?;
+        let expanded = expand(&content, url, &mut ClientLoader(self)).await?;
+        Ok(expanded.len())
+    }
+
+    /// Load a document of type T from the given url, along with the expanded JSON-LD it was parsed from
+    ///
+    /// The expanded document holds every property in the document, including any that T doesn't model,
+    /// such as vendor extensions, so nothing is lost when re-publishing it
+    pub async fn load_raw<T>(&self, url: Url) -> Result<(T, ExpandedDocument)>
+    where
+        T: for<'a> TryFrom<&'a Node>,
+        for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+    {
+        let content = self.fetch(url.clone()).await?;
+        parse_raw(&content, url, &mut ClientLoader(self), &self.options).await
+    }
+
+    /// Load a document of type T from the given url, along with any [Warning]s about parts of it that were
+    /// guessed at or skipped, such as an unrecognized reference type
+    ///
+    /// Documents that don't match T's schema still fail to load, so imperfect documents can be ingested and
+    /// flagged for review, without accepting ones that are simply broken
+    pub async fn load_with_warnings<T>(&self, url: Url) -> Result<(T, Vec<Warning>)>
+    where
+        T: for<'a> TryFrom<&'a Node>,
+        for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+    {
+        let (document, expanded) = self.load_raw(url).await?;
+        Ok((document, warning::collect(&expanded)))
+    }
+
+    /// Load a document of type T from the given url, along with the contexts it declares at its top level, to
+    /// tell proper JSON-LD apart from plain JSON that happens to parse; see [declared_contexts]
+    pub async fn load_with_contexts<T>(&self, url: Url) -> Result<(T, Vec<DeclaredContext>)>
+    where
+        T: for<'a> TryFrom<&'a Node>,
+        for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+    {
+        let content = self.fetch(url.clone()).await?;
+        let contexts = declared_contexts(&content)?;
+        let document = parse(&content, url, &mut ClientLoader(self), &self.options).await?;
+        Ok((document, contexts))
+    }
+
+    /// Load a document of type T from a JSON-LD file on disk
+    ///
+    /// The canonical file:// url of the file is used as the document IRI, so relative IRIs resolve against it
+    pub async fn load_file<T>(&self, path: impl AsRef<Path>) -> Result<T>
+    where
+        T: for<'a> TryFrom<&'a Node>,
+        for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+    {
+        let path = tokio::fs::canonicalize(path).await?;
+        let url = Url::from_file_path(&path).map_err(|_| MetadataError::InvalidIri {
+            field: "document path".to_string(),
+            iri: path.display().to_string(),
+        })?;
+        let content = decode(tokio::fs::read(&path).await?)?;
+        parse(&content, url, &mut ClientLoader(self), &self.options).await
+    }
+
+    /// Load a document of type T from JSON-LD content that has already been fetched
+    ///
+    /// The base url is used as the document IRI, so relative IRIs resolve against it
+    pub async fn load_str<T>(&self, content: &str, base: Url) -> Result<T>
+    where
+        T: for<'a> TryFrom<&'a Node>,
+        for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+    {
+        parse(content, base, &mut ClientLoader(self), &self.options).await
+    }
+
+    /// Load a document of type T from JSON-LD bytes that have already been fetched, such as through your own transport
+    ///
+    /// The bytes must be UTF-8, optionally starting with a byte order mark; the base url is used as the document IRI
+    pub async fn load_bytes<T>(&self, content: &[u8], base: Url) -> Result<T>
+    where
+        T: for<'a> TryFrom<&'a Node>,
+        for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+    {
+        self.load_str(&decode(content.to_vec())?, base).await
+    }
+
+    /// Load a document of type T from an async reader, such as a download from an S3 or IPFS SDK
+    ///
+    /// At most the client's max size is read, as with fetched documents, so an endless stream fails with
+    /// [MetadataError::TooLarge] rather than exhausting memory; the base url is used as the document IRI
+    pub async fn load_reader<T>(&self, reader: impl AsyncRead + Unpin, base: Url) -> Result<T>
+    where
+        T: for<'a> TryFrom<&'a Node>,
+        for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+    {
+        let mut content = vec![];
+        reader
+            .take(self.max_size as u64 + 1)
+            .read_to_end(&mut content)
+            .await?;
+        if content.len() > self.max_size {
+            return Err(MetadataError::TooLarge {
+                limit: self.max_size,
+            });
+        }
+        self.load_str(&decode(content)?, base).await
+    }
+
+    /// Load a document of type T from the given url, but only if the raw bytes served match the expected blake2b-256 hash
+    ///
+    /// This is the hash recorded alongside the url in an on-chain governance anchor, so this guards against the
+    /// document being edited after the anchor was published
+    pub async fn load_verified<T>(&self, url: Url, expected_hash: [u8; 32]) -> Result<T>
+    where
+        T: for<'a> TryFrom<&'a Node>,
+        for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+    {
+        // The hash is computed as the document downloads, so it's never hashed from a second copy
+        let content = self.fetch_checked(url.clone(), Some(expected_hash)).await?;
+        parse(
+            &decode(content)?,
+            url,
+            &mut ClientLoader(self),
+            &self.options,
+        )
+        .await
+    }
+
+    /// Load a document of type T from where the on-chain anchor points, checking it against the anchor's hash;
+    /// see [MetadataClient::load_verified]
+    #[cfg(feature = "anchor")]
+    pub async fn load_anchor<T>(&self, anchor: &Anchor) -> Result<T>
+    where
+        T: for<'a> TryFrom<&'a Node>,
+        for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+    {
+        self.load_verified(anchor.url.clone(), anchor.data_hash)
+            .await
+    }
+
+    /// Load a CIP-100 document from the given url, and recursively load the documents its
+    /// GovernanceMetadata references point at
+    ///
+    /// Only a failure to load the root document is an error; a reference that can't be loaded, that leads
+    /// back to a document it was reached from, or that is nested too deeply records its error in the tree
+    pub async fn load_with_references(&self, url: Url) -> Result<DocumentTree> {
+        self.load_tree(url, 0, &mut HashSet::new()).await
+    }
+
+    /// Load the document at url, at the given depth in the tree; visited holds the urls of the documents
+    /// it was reached from, so that cycles are detected rather than followed
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, visited), fields(url = %url))
+    )]
+    async fn load_tree(
+        &self,
+        url: Url,
+        depth: usize,
+        visited: &mut HashSet<Url>,
+    ) -> Result<DocumentTree> {
+        let document: Document = self.load(url.clone()).await?;
+        visited.insert(url.clone());
+        let mut references = vec![];
+        for reference in document
+            .body
+            .references_of_type(ReferenceType::GovernanceMetadata)
+        {
+            let result = match Url::parse(reference.uri.as_str()) {
+                Err(_) => Err(MetadataError::InvalidIri {
+                    field: "reference uri".to_string(),
+                    iri: reference.uri.to_string(),
+                }),
+                Ok(uri) if visited.contains(&uri) => {
+                    Err(MetadataError::ReferenceCycle(uri.to_string()))
+                }
+                Ok(_) if depth >= self.max_depth => Err(MetadataError::MaxDepth(self.max_depth)),
+                Ok(uri) => Box::pin(self.load_tree(uri, depth + 1, visited)).await,
+            };
+            if let Err(_err) = &result {
+                debug!(reference = %reference.uri, error = %_err, "referenced document not loaded");
+            }
+            references.push(ReferencedDocument {
+                reference: reference.clone(),
+                document: result,
+            });
+        }
+        visited.remove(&url);
+        Ok(DocumentTree {
+            url,
+            document,
+            references,
+        })
+    }
+
+    async fn fetch(&self, url: Url) -> Result<String> {
+        decode(self.fetch_bytes(url).await?)
+    }
+
+    /// Fetch a remote context for a loader to resolve, as documents are fetched, but asking for JSON-LD
+    pub(crate) async fn fetch_context(&self, iri: IriBuf) -> Result<RemoteDocument> {
+        let url = Url::parse(iri.as_str()).map_err(|_| MetadataError::InvalidIri {
+            field: "context".to_string(),
+            iri: iri.to_string(),
+        })?;
+        let client = self.clone().with_header(
+            reqwest::header::ACCEPT.as_str(),
+            "application/ld+json, application/json",
+        )?;
+        let content = decode(client.fetch_bytes(url).await?)?;
+        let (context, _) = Value::parse_str(&content).map_err(|err| MetadataError::Parse {
+            position: err.position(),
+            message: err.to_string(),
+        })?;
+        Ok(RemoteDocument::new(Some(iri), None, context))
+    }
+
+    async fn fetch_bytes(&self, url: Url) -> Result<Vec<u8>> {
+        self.fetch_checked(url, None).await
+    }
+
+    /// Fetch the document, failing if it doesn't have the expected blake2b-256 hash, if one is given
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(url = %url))
+    )]
+    async fn fetch_checked(&self, url: Url, expected_hash: Option<[u8; 32]>) -> Result<Vec<u8>> {
+        let url = match self.schemes.resolve(&url).transpose()? {
+            Some(Resolved::Content(content)) => {
+                return HashCheck::check(expected_hash, within_limit(content, self.max_size)?)
+            }
+            Some(Resolved::Url(resolved)) => resolved,
+            None if url.scheme() == "data" => {
+                return HashCheck::check(expected_hash, decode_data_url(&url, self.max_size)?)
+            }
+            None => gateway_url(url, &self.ipfs_gateway, &self.arweave_gateway)?,
+        };
+        let mut delay = self.retry_delay;
+        for _ in 1..self.max_attempts {
+            match self.fetch_once(url.clone(), expected_hash).await {
+                Err(err) if err.is_transient() => {
+                    debug!(error = %err, ?delay, "retrying after a transient error");
+                    tokio::time::sleep(delay).await;
+                    delay = next_retry_delay(delay);
+                }
+                result => return result,
+            }
+        }
+        self.fetch_once(url, expected_hash).await
+    }
+
+    async fn fetch_once(&self, url: Url, expected_hash: Option<[u8; 32]>) -> Result<Vec<u8>> {
+        #[cfg(feature = "tracing")]
+        let started = std::time::Instant::now();
         // use Reqwest to load the content, since the json_ld reqwest loader is picky about content types for now
-        let content = reqwest::get(url.clone()).await?.text().await?;
-        let iri = IriBuf::new(url.clone().to_string()).context("invalid url")?;
-        let value = Value::parse_str(&content).expect("unable to parse file").0;
+        let error = |err: reqwest::Error| {
+            if err.is_timeout() {
+                MetadataError::Timeout(self.timeout)
+            } else if let Some(denied) = address::denied_address(&err) {
+                denied
+            } else {
+                MetadataError::Fetch(err)
+            }
+        };
+        let mut target = url.clone();
+        let mut redirects = 0;
+        let (mut response, cached) = loop {
+            if let Some(policy) = &self.address_policy {
+                policy.check_url(&target)?;
+            }
+            let mut request = self
+                .client
+                .get(target.clone())
+                .timeout(self.timeout)
+                .headers(redirect::headers_for(&self.headers, &url, &target));
+            // The copy is kept with the validators sent for it, to answer a 304 even if it's evicted meanwhile
+            let cached = self.cache.as_ref().and_then(|cache| cache.lookup(&target));
+            if let Some(cached) = &cached {
+                request = request.headers(cached.validators.clone());
+            }
+            let response = request.send().await.map_err(error)?;
+            if !redirect::is_redirect(response.status()) {
+                break (response, cached);
+            }
+            if redirects == self.max_redirects {
+                return Err(MetadataError::TooManyRedirects(self.max_redirects));
+            }
+            let from = target;
+            target = redirect::redirect_target(&from, response.headers(), self.private_redirects)?;
+            debug!(%from, to = %target, "following redirect");
+            redirects += 1;
+        };
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            if let Some(cached) = cached {
+                let content =
+                    cached.reuse(self.max_size, self.content_types.as_deref(), expected_hash)?;
+                debug!(bytes = content.len(), elapsed = ?started.elapsed(), "document not modified, using cached copy");
+                return Ok(content);
+            }
+        }
+        error::check_status(response.status(), response.url(), response.headers())?;
+        if let Some(content_types) = &self.content_types {
+            check_content_type(response.headers(), content_types)?;
+        }
+        let too_large = MetadataError::TooLarge {
+            limit: self.max_size,
+        };
+        if response.content_length().unwrap_or(0) > self.max_size as u64 {
+            return Err(too_large);
+        }
+        let headers = response.headers().clone();
+        // Stream the body, rather than trusting the content length, so a lying server can't exhaust our memory
+        let mut content = vec![];
+        let mut hash = expected_hash.map(HashCheck::new);
+        while let Some(chunk) = response.chunk().await.map_err(error)? {
+            if content.len() + chunk.len() > self.max_size {
+                return Err(too_large);
+            }
+            if let Some(hash) = &mut hash {
+                hash.update(&chunk);
+            }
+            content.extend_from_slice(&chunk);
+        }
+        // Only a document that matches is cached, so a bad copy isn't revalidated and reused later
+        if let Some(hash) = hash {
+            hash.finish()?;
+        }
+        if let Some(cache) = &self.cache {
+            cache.insert(target, &headers, &content);
+        }
+        debug!(bytes = content.len(), elapsed = ?started.elapsed(), "fetched document");
+        Ok(content)
+    }
+}
+
+/// The checks made on every document once it's expanded, before it's converted to the requested type
+#[derive(Debug, Clone)]
+pub(crate) struct ParseOptions {
+    /// The schemes reference and update uris may use; any scheme is accepted if None
+    pub(crate) uri_schemes: Option<Vec<String>>,
+    /// Whether to reject a hashAlgorithm this library doesn't recognize, rather than keep it as is
+    pub(crate) strict_hash_algorithm: bool,
+    /// Whether to trim text and strip control and zero-width characters from it, rather than keep it as written
+    pub(crate) normalize_text: bool,
+}
 
-        let document = RemoteDocument::new(Some(iri), None, value);
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            uri_schemes: Some(DEFAULT_URI_SCHEMES.iter().map(|s| s.to_string()).collect()),
+            strict_hash_algorithm: false,
+            normalize_text: false,
+        }
+    }
+}
 
-        let expanded = document.expand(&mut json_ld::NoLoader::default()).await?;
+impl ParseOptions {
+    /// Allow reference and update uris with the given scheme, if only some schemes are allowed
+    #[cfg(feature = "client")]
+    fn allow_scheme(&mut self, scheme: &str) {
+        if let Some(schemes) = &mut self.uri_schemes {
+            if !schemes
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(scheme))
+            {
+                schemes.push(scheme.to_ascii_lowercase());
+            }
+        }
+    }
 
-        let first_object = expanded
-            .objects()
-            .iter()
-            .next()
-            .context("no objects in document")?;
-        let node = first_object
+    fn prepare(&self, expanded: ExpandedDocument) -> ExpandedDocument {
+        if self.normalize_text {
+            lang::normalize_expanded(expanded)
+        } else {
+            expanded
+        }
+    }
+
+    fn check(&self, node: &Node) -> Result<()> {
+        if let Some(schemes) = &self.uri_schemes {
+            check_uri_schemes(node, schemes)?;
+        }
+        if self.strict_hash_algorithm {
+            check_hash_algorithm(node)?;
+        }
+        Ok(())
+    }
+}
+
+/// Check that the document's hashAlgorithm, if it has one, is one this library recognizes
+fn check_hash_algorithm(node: &Node) -> Result<()> {
+    let iri = Iri::new(CIP100_FIELDS.hash_algorithm)?;
+    let Some(algorithm) = node.get_any(&iri).and_then(|value| value.as_str()) else {
+        return Ok(());
+    };
+    if HashAlgorithm::from(algorithm).is_recognized() {
+        return Ok(());
+    }
+    Err(MetadataError::InvalidValue {
+        field: "hash algorithm".to_string(),
+        reason: format!(
+            "{algorithm:?} is not one of {}",
+            HashAlgorithm::RECOGNIZED.join(", ")
+        ),
+    })
+}
+
+/// Check that every reference and update uri anywhere in the node uses one of the given schemes
+fn check_uri_schemes(node: &Node, schemes: &[String]) -> Result<()> {
+    let fields = [
+        ("reference uri", Iri::new(CIP100_FIELDS.reference_uri)?),
+        ("update uri", Iri::new(CIP100_FIELDS.update_uri)?),
+    ];
+    for fragment in node.traverse() {
+        let Some(json_ld::object::Ref::Node(node)) = fragment.into_ref() else {
+            continue;
+        };
+        for (field, iri) in &fields {
+            for uri in node.get(iri).filter_map(|object| util::iri_value(object)) {
+                let allowed = Iri::new(uri).is_ok_and(|uri| {
+                    schemes
+                        .iter()
+                        .any(|scheme| scheme.eq_ignore_ascii_case(uri.scheme().as_str()))
+                });
+                if !allowed {
+                    return Err(MetadataError::DisallowedScheme {
+                        field: field.to_string(),
+                        iri: uri.to_string(),
+                    });
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check that a response was served with one of the given media types; a response without a Content-Type is rejected
+#[cfg(feature = "client")]
+pub(crate) fn check_content_type(
+    headers: &reqwest::header::HeaderMap,
+    content_types: &[String],
+) -> Result<()> {
+    let Some(content_type) = headers.get(reqwest::header::CONTENT_TYPE) else {
+        return Err(MetadataError::UnsupportedMediaType(
+            "no content type".to_string(),
+        ));
+    };
+    let content_type = String::from_utf8_lossy(content_type.as_bytes());
+    let essence = content_type.split(';').next().unwrap_or_default().trim();
+    if content_types
+        .iter()
+        .any(|accepted| accepted.eq_ignore_ascii_case(essence))
+    {
+        Ok(())
+    } else {
+        Err(MetadataError::UnsupportedMediaType(
+            content_type.to_string(),
+        ))
+    }
+}
+
+/// Rewrite an ipfs:// or ar:// url to the equivalent url on the given HTTP gateway, keeping the CID or
+/// transaction id and any path after it; other urls are returned as is
+#[cfg(feature = "client")]
+pub(crate) fn gateway_url(url: Url, ipfs_gateway: &Url, arweave_gateway: &Url) -> Result<Url> {
+    let gateway = match url.scheme() {
+        "ipfs" => ipfs_gateway,
+        "ar" => arweave_gateway,
+        _ => return Ok(url),
+    };
+    let path = url.as_str()[url.scheme().len() + 1..].trim_start_matches('/');
+    gateway.join(path).map_err(|_| MetadataError::InvalidIri {
+        field: format!("{} url", url.scheme()),
+        iri: url.to_string(),
+    })
+}
+
+/// Decode the payload of a data: url, such as data:application/json;base64,..., which holds the whole document
+/// rather than pointing at it; fails if it holds more than max_size bytes, without decoding a payload too long
+/// to possibly fit
+#[cfg(feature = "client")]
+pub(crate) fn decode_data_url(url: &Url, max_size: usize) -> Result<Vec<u8>> {
+    // Neither base64 nor percent encoding more than triples the length of what they encode
+    let encoded = url
+        .path()
+        .split_once(',')
+        .map_or(0, |(_, payload)| payload.len());
+    if encoded > max_size.saturating_mul(3) {
+        return Err(MetadataError::TooLarge { limit: max_size });
+    }
+    let data_url = data_url::DataUrl::process(url.as_str())
+        .map_err(|err| MetadataError::InvalidDataUrl(format!("{:?}", err)))?;
+    let mime = data_url.mime_type();
+    if !(mime.type_ == "application" && (mime.subtype == "json" || mime.subtype.ends_with("+json")))
+    {
+        return Err(MetadataError::UnsupportedMediaType(mime.to_string()));
+    }
+    let (content, _) = data_url
+        .decode_to_vec()
+        .map_err(|_| MetadataError::InvalidDataUrl("invalid base64 payload".to_string()))?;
+    within_limit(content, max_size)
+}
+
+/// Fail if content a scheme handler loaded is larger than the client would have downloaded
+#[cfg(feature = "client")]
+pub(crate) fn within_limit(content: Vec<u8>, max_size: usize) -> Result<Vec<u8>> {
+    if content.len() > max_size {
+        return Err(MetadataError::TooLarge { limit: max_size });
+    }
+    Ok(content)
+}
+
+/// Parse a header configured on a client, failing if either its name or value can't be sent
+#[cfg(feature = "client")]
+pub(crate) fn header(
+    name: &str,
+    value: &str,
+) -> Result<(reqwest::header::HeaderName, reqwest::header::HeaderValue)> {
+    let invalid = || MetadataError::InvalidValue {
+        field: format!("{name} header"),
+        reason: "is not a valid HTTP header".to_string(),
+    };
+    let name = reqwest::header::HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid())?;
+    let value = reqwest::header::HeaderValue::from_str(value).map_err(|_| invalid())?;
+    Ok((name, value))
+}
+
+/// Ensure the url ends with a slash, so that joining paths onto it appends rather than replaces the last segment
+#[cfg(feature = "client")]
+pub(crate) fn with_trailing_slash(mut url: Url) -> Url {
+    if !url.path().ends_with('/') {
+        url.set_path(&format!("{}/", url.path()));
+    }
+    url
+}
+
+/// Decode a document as UTF-8, skipping any byte order mark; documents in any other encoding are rejected,
+/// rather than parsed as garbled text
+#[cfg(feature = "client")]
+pub(crate) fn decode(mut content: Vec<u8>) -> Result<String> {
+    const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+    if content.starts_with(b"\xFF\xFE") || content.starts_with(b"\xFE\xFF") {
+        return Err(MetadataError::Parse {
+            position: 0,
+            message: "document is UTF-16, but must be UTF-8".to_string(),
+        });
+    }
+    let bom = match content.starts_with(UTF8_BOM) {
+        true => content.drain(..UTF8_BOM.len()).len(),
+        false => 0,
+    };
+    String::from_utf8(content).map_err(|err| MetadataError::Parse {
+        position: bom + err.utf8_error().valid_up_to(),
+        message: "invalid UTF-8".to_string(),
+    })
+}
+
+/// Parse a document of type T from JSON-LD content, with the given url as the document IRI, rejecting
+/// reference and update uris that don't use one of the [DEFAULT_URI_SCHEMES]
+///
+/// Contexts are resolved with a [StaticContextLoader], so this never touches the network, and is
+/// available without the client feature, such as when targeting WASM
+pub async fn parse_str<T>(content: &str, base: Url) -> Result<T>
+where
+    T: for<'a> TryFrom<&'a Node>,
+    for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+{
+    parse(
+        content,
+        base,
+        &mut &StaticContextLoader::new(),
+        &ParseOptions::default(),
+    )
+    .await
+}
+
+/// Like [parse_str], but also return any [Warning]s about parts of the document that were guessed at or skipped
+pub async fn parse_str_with_warnings<T>(content: &str, base: Url) -> Result<(T, Vec<Warning>)>
+where
+    T: for<'a> TryFrom<&'a Node>,
+    for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+{
+    let (document, expanded) = parse_raw(
+        content,
+        base,
+        &mut &StaticContextLoader::new(),
+        &ParseOptions::default(),
+    )
+    .await?;
+    Ok((document, warning::collect(&expanded)))
+}
+
+/// Expand the JSON-LD content, with the given url as the document IRI, and convert it to a document of type T
+///
+/// Documents that fail any of the checks in options are rejected
+pub(crate) async fn parse<T, L>(
+    content: &str,
+    url: Url,
+    loader: &mut L,
+    options: &ParseOptions,
+) -> Result<T>
+where
+    T: for<'a> TryFrom<&'a Node>,
+    for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+    L: Loader<IriBuf>,
+    L::Error: Display,
+{
+    Ok(parse_raw(content, url, loader, options).await?.0)
+}
+
+/// Like parse, but also return the expanded JSON-LD the document was converted from
+pub(crate) async fn parse_raw<T, L>(
+    content: &str,
+    url: Url,
+    loader: &mut L,
+    options: &ParseOptions,
+) -> Result<(T, ExpandedDocument)>
+where
+    T: for<'a> TryFrom<&'a Node>,
+    for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+    L: Loader<IriBuf>,
+    L::Error: Display,
+{
+    let expanded = options.prepare(expand(content, url, loader).await?);
+    let mut documents = convert_all(&expanded, options)?;
+    if documents.len() > 1 {
+        return Err(MetadataError::MultipleDocuments {
+            parseable: documents.len(),
+            objects: expanded.len(),
+        });
+    }
+    Ok((documents.remove(0), expanded))
+}
+
+/// Expand the JSON-LD content, with the given url as the document IRI, and convert every node it can to type T
+#[cfg(feature = "client")]
+pub(crate) async fn parse_all<T, L>(
+    content: &str,
+    url: Url,
+    loader: &mut L,
+    options: &ParseOptions,
+) -> Result<Vec<T>>
+where
+    T: for<'a> TryFrom<&'a Node>,
+    for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+    L: Loader<IriBuf>,
+    L::Error: Display,
+{
+    let expanded = options.prepare(expand(content, url, loader).await?);
+    convert_all(&expanded, options)
+}
+
+/// Expand the JSON-LD content, with the given url as the document IRI
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(url = %url, bytes = content.len()))
+)]
+pub(crate) async fn expand<L>(content: &str, url: Url, loader: &mut L) -> Result<ExpandedDocument>
+where
+    L: Loader<IriBuf>,
+    L::Error: Display,
+{
+    let iri = IriBuf::new(url.to_string()).map_err(|_| MetadataError::InvalidIri {
+        field: "document url".to_string(),
+        iri: url.to_string(),
+    })?;
+    let value = Value::parse_str(content)
+        .map_err(|err| MetadataError::Parse {
+            position: err.position(),
+            message: err.to_string(),
+        })?
+        .0;
+
+    let document = RemoteDocument::new(Some(iri), None, value);
+
+    #[cfg(feature = "tracing")]
+    let started = std::time::Instant::now();
+    let mut recording = context::RecordingLoader::new(loader);
+    let expanded =
+        document
+            .expand(&mut recording)
+            .await
+            .map_err(|err| match recording.failed.take() {
+                Some((iri, reason)) => MetadataError::UnresolvableContext {
+                    iri: iri.to_string(),
+                    reason,
+                },
+                None => MetadataError::Expand(err.to_string()),
+            })?;
+    debug!(objects = expanded.len(), elapsed = ?started.elapsed(), "expanded document");
+    Ok(expanded)
+}
+
+/// Convert every node in the expanded document that can be converted to type T
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(objects = expanded.len()))
+)]
+fn convert_all<T>(expanded: &ExpandedDocument, options: &ParseOptions) -> Result<Vec<T>>
+where
+    T: for<'a> TryFrom<&'a Node>,
+    for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+{
+    let mut documents = vec![];
+    let mut first_error = None;
+    for object in expanded.objects() {
+        let document = object
             .as_node()
-            .context("object in document isn't a node")?;
-        let r = node.try_into()?;
-        Ok(r)
+            .wrong_type("object in document", "a node")
+            .and_then(|node| options.check(node).map(|_| node))
+            .and_then(|node| node.try_into().map_err(Into::into));
+        match document {
+            Ok(document) => documents.push(document),
+            Err(err) => {
+                first_error.get_or_insert(err);
+            }
+        }
+    }
+    debug!(
+        converted = documents.len(),
+        failed = expanded.len() - documents.len(),
+        "converted document"
+    );
+    if documents.is_empty() {
+        return Err(first_error.unwrap_or(MetadataError::NoObjects));
     }
+    Ok(documents)
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "client"))]
 mod tests {
     use super::*;
-    use anyhow::Result;
+    use crate::test_server::{en, example_document, reference, TestServer, EXAMPLE};
+    use anyhow::{Context, Result};
+    use base64::prelude::*;
+    use blake2::{digest::consts::U32, Blake2b, Digest};
     use url::Url;
+    use wiremock::{
+        matchers::{header, header_exists},
+        ResponseTemplate,
+    };
 
     #[tokio::test]
     async fn test_metadata_client() -> Result<()> {
+        let server = TestServer::start().await;
+        let url = server.serve("/CIP-0100/example.json", EXAMPLE).await;
+        let cip100 = MetadataClient::new()
+            .load::<Document>(url)
+            .await
+            .context("unable to load document")?;
+        assert_eq!(cip100, example_document());
+        Ok(())
+    }
+
+    /// Smoke test against the example as published in the CIPs repository; run with `cargo test -- --ignored`
+    #[tokio::test]
+    #[ignore = "fetches from GitHub"]
+    async fn test_metadata_client_live() -> Result<()> {
         let client = MetadataClient::new();
         let url = Url::parse("https://raw.githubusercontent.com/cardano-foundation/CIPs/master/CIP-0100/example.json").unwrap();
         let cip100 = client
             .load::<Document>(url)
             .await
             .context("unable to load document")?;
-        let expected = Document {
-            hash_algorithm: "blake2b-256".to_string(),
-            authors: vec![
-                Author {
-                    name: "Pi Lanningham".to_string(),
-                    witness: Witness {
-                        algorithm: "ed25519".to_string(),
-                        public_key: "7ea09a34aebb13c9841c71397b1cabfec5ddf950405293dee496cac2f437480a".to_string(),
-                        signature: "340c2ef8d6abda96769844ab9dca2634ae21ef97ddbfad1f8843bea1058e40d656455a2962143adc603d063bbbe27b54b88d002d23d1dff1cd0e05017cd4f506".to_string(),
-                    },
-                },
-            ],
-            body: Body {
-                references: vec![
-                    Reference {
-                        reference_type: ReferenceType::Other,
-                        label: "CIP-100".to_string(),
-                        uri: IriBuf::new("https://github.com/cardano-foundation/CIPs/blob/master/CIP-0100/README.md".to_string()).unwrap(),
-                    },
+        assert_eq!(cip100, example_document());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_load_file() -> Result<()> {
+        let client = MetadataClient::new();
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/example.json");
+        let document = client.load_file::<Document>(path).await?;
+        assert_eq!(document, example_document());
+
+        let err = client
+            .load_file::<Document>("tests/fixtures/missing.json")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, MetadataError::Io(_)), "{}", err);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_remote_context() -> Result<()> {
+        let content = include_str!("../tests/fixtures/remote-context.json");
+        let url = Url::parse("https://example.com/metadata.jsonld")?;
+        let document = MetadataClient::new()
+            .load_str::<Document>(content, url.clone())
+            .await?;
+        assert_eq!(document, example_document());
+
+        let unknown = content.replace(CIP100_CONTEXT, "https://example.com/unknown.jsonld");
+        let err = MetadataClient::new()
+            .load_str::<Document>(&unknown, url.clone())
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, MetadataError::UnresolvableContext { ref iri, .. } if iri == "https://example.com/unknown.jsonld"),
+            "{}",
+            err
+        );
+
+        let document = MetadataClient::new()
+            .with_context(
+                IriBuf::new("https://example.com/unknown.jsonld".to_string()).unwrap(),
+                include_str!("../contexts/cip-0100.common.jsonld"),
+            )?
+            .load_str::<Document>(&unknown, url.clone())
+            .await?;
+        assert_eq!(document, example_document());
+
+        // Adding a context never waits on, or fails because of, loads through clones of the client
+        let iri = IriBuf::new("https://example.com/unknown.jsonld".to_string()).unwrap();
+        let client = MetadataClient::new();
+        let loads = futures::future::join_all(
+            (0..8).map(|_| client.load_str::<Document>(content, url.clone())),
+        );
+        let extend = async {
+            for _ in 0..8 {
+                client.clone().with_context(
+                    iri.clone(),
+                    include_str!("../contexts/cip-0100.common.jsonld"),
+                )?;
+                tokio::task::yield_now().await;
+            }
+            Ok::<_, MetadataError>(())
+        };
+        let (loads, extended) = tokio::join!(loads, extend);
+        extended?;
+        assert!(loads.iter().all(|load| load.is_ok()));
+
+        // Contexts added to a client with a loader of its own are resolved before it's asked
+        let document = MetadataClient::new()
+            .with_loader(StaticContextLoader::empty())
+            .with_context(iri, include_str!("../contexts/cip-0100.common.jsonld"))?
+            .load_str::<Document>(&unknown, url)
+            .await?;
+        assert_eq!(document, example_document());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unresolvable_context() -> Result<()> {
+        // Offline, an unknown context among bundled ones is named, rather than the document failing for
+        // want of the fields it would have defined
+        let content = include_str!("../tests/fixtures/remote-context.json").replace(
+            &format!("\"{CIP100_CONTEXT}\""),
+            &format!("[\"{CIP100_CONTEXT}\", \"https://example.com/offline.jsonld\"]"),
+        );
+        let url = Url::parse("https://example.com/metadata.jsonld")?;
+        let err = parse_str::<Document>(&content, url).await.unwrap_err();
+        assert!(
+            matches!(err, MetadataError::UnresolvableContext { ref iri, .. } if iri == "https://example.com/offline.jsonld"),
+            "{}",
+            err
+        );
+        assert!(err.to_string().contains("not found"), "{}", err);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_with_loader() -> Result<()> {
+        let content = include_str!("../tests/fixtures/remote-context.json");
+        let url = Url::parse("https://example.com/metadata.jsonld")?;
+
+        let err = MetadataClient::new()
+            .with_loader(json_ld::NoLoader)
+            .load_str::<Document>(content, url.clone())
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, MetadataError::UnresolvableContext { ref iri, .. } if iri == CIP100_CONTEXT),
+            "{}",
+            err
+        );
+
+        // Any json_ld loader works, such as a plain map of documents
+        let iri = IriBuf::new(CIP100_CONTEXT.to_string()).unwrap();
+        let context = Value::parse_str(include_str!("../contexts/cip-0100.common.jsonld"))
+            .unwrap()
+            .0;
+        let loader = std::collections::HashMap::from([(
+            iri.clone(),
+            RemoteDocument::new(Some(iri), None, context),
+        )]);
+        let document = MetadataClient::new()
+            .with_loader(loader)
+            .load_str::<Document>(content, url)
+            .await?;
+        assert_eq!(document, example_document());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_load_many() -> Result<()> {
+        let server = TestServer::start().await;
+        let url = |name: &str| server.url(&format!("/{name}.jsonld"));
+        for name in ["a", "b", "c"] {
+            let document = Document::builder().comment(name).build();
+            server
+                .serve(&format!("/{name}.jsonld"), document.to_jsonld()?)
+                .await;
+        }
+
+        let client = MetadataClient::new();
+        let urls = vec![url("a"), url("missing"), url("b"), url("c")];
+        let mut results: Vec<(Url, Result<Document, MetadataError>)> =
+            client.load_many(urls.clone(), 2).collect().await;
+        results.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let loaded: Vec<_> = results.iter().map(|(url, _)| url.clone()).collect();
+        let mut expected = urls;
+        expected.sort();
+        assert_eq!(loaded, expected);
+        for (url, result) in results {
+            let name = url
+                .path()
+                .trim_start_matches('/')
+                .trim_end_matches(".jsonld");
+            match result {
+                Ok(document) => assert_eq!(document.body.comment.unwrap().to_string(), name),
+                Err(err) => {
+                    assert_eq!(name, "missing");
+                    assert!(matches!(err, MetadataError::Status { .. }));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_load_with_references() -> Result<()> {
+        let server = TestServer::start().await;
+        let url = |name: &str| server.url(&format!("/{name}.jsonld")).to_string();
+        let reference = |reference_type, name: &str| reference(reference_type, name, &url(name));
+        let documents = [
+            (
+                "a",
+                vec![
+                    reference(ReferenceType::GovernanceMetadata, "b"),
+                    reference(ReferenceType::Other, "readme"),
+                    reference(ReferenceType::GovernanceMetadata, "missing"),
                 ],
-                comment: "This is a test vector for CIP-100".to_string(),
-                external_updates: vec![
-                    Update {
-                        title: "Blog".to_string(),
-                        uri: IriBuf::new("https://314pool.com".to_string()).unwrap(),
-                    },
+            ),
+            (
+                "b",
+                vec![
+                    reference(ReferenceType::GovernanceMetadata, "a"),
+                    reference(ReferenceType::GovernanceMetadata, "c"),
                 ],
+            ),
+            ("c", vec![]),
+        ];
+        for (name, references) in documents {
+            let document = references
+                .into_iter()
+                .fold(Document::builder(), |builder, reference| {
+                    builder.add_reference(reference)
+                })
+                .comment(name)
+                .build();
+            server
+                .serve(&format!("/{name}.jsonld"), document.to_jsonld()?)
+                .await;
+        }
+
+        let client = MetadataClient::new();
+        let tree = client.load_with_references(Url::parse(&url("a"))?).await?;
+        let comments: Vec<_> = tree
+            .documents()
+            .iter()
+            .map(|d| d.body.comment.as_ref().unwrap().to_string())
+            .collect();
+        assert_eq!(comments, vec!["a", "b", "c"]);
+
+        // The Other reference isn't followed
+        assert_eq!(tree.references.len(), 2);
+        assert!(matches!(
+            tree.references[1].document,
+            Err(MetadataError::Status { .. })
+        ));
+        let b = tree.references[0].document.as_ref().unwrap();
+        assert_eq!(b.references[0].reference.uri.as_str(), url("a"));
+        assert!(
+            matches!(&b.references[0].document, Err(MetadataError::ReferenceCycle(uri)) if *uri == url("a"))
+        );
+        assert!(b.references[1].document.is_ok());
+
+        let tree = client
+            .with_max_depth(1)
+            .load_with_references(Url::parse(&url("a"))?)
+            .await?;
+        let b = tree.references[0].document.as_ref().unwrap();
+        assert!(matches!(
+            b.references[1].document,
+            Err(MetadataError::MaxDepth(1))
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_load_str_resolves_against_base() -> Result<()> {
+        let content = r#"{
+            "@context": {
+                "CIP100": "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0100/README.md#",
+                "hashAlgorithm": "CIP100:hashAlgorithm",
+                "body": "CIP100:body",
+                "comment": "CIP100:comment",
+                "externalUpdates": "CIP100:externalUpdates",
+                "title": "CIP100:update-title",
+                "uri": "CIP100:update-uri"
+            },
+            "hashAlgorithm": "blake2b-256",
+            "body": {
+                "comment": "Relative update",
+                "externalUpdates": [{ "title": "Blog", "uri": { "@id": "blog" } }]
+            }
+        }"#;
+        let base = Url::parse("https://example.com/metadata/document.json")?;
+        let document = MetadataClient::new()
+            .load_str::<Document>(content, base)
+            .await?;
+        assert_eq!(
+            document.body.external_updates[0].uri.as_str(),
+            "https://example.com/metadata/blog"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_with_client() -> Result<()> {
+        let server = TestServer::start().await;
+        server
+            .mount(
+                TestServer::get("/example.json")
+                    .and(header("user-agent", "metadata-indexer"))
+                    .respond_with(ResponseTemplate::new(200).set_body_string(EXAMPLE))
+                    .expect(2),
+            )
+            .await;
+        let client = reqwest::Client::builder()
+            .user_agent("metadata-indexer")
+            .build()?;
+        let client = MetadataClient::with_client(client);
+        let url = server.url("/example.json");
+        for _ in 0..2 {
+            assert_eq!(
+                client.load::<Document>(url.clone()).await?,
+                example_document()
+            );
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_timeout() -> Result<()> {
+        let server = TestServer::start().await;
+        let url = server
+            .respond(
+                "/slow.json",
+                ResponseTemplate::new(200)
+                    .set_body_string(EXAMPLE)
+                    .set_delay(Duration::from_secs(5)),
+            )
+            .await;
+        let client = MetadataClient::new().with_timeout(Duration::from_millis(100));
+        let err = client.load::<Document>(url).await.unwrap_err();
+        assert!(matches!(err, MetadataError::Timeout(_)), "{}", err);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_max_size() -> Result<()> {
+        let server = TestServer::start().await;
+        let large = server.serve("/large.json", " ".repeat(1024 * 1024)).await;
+        let example = server.serve("/example.json", EXAMPLE).await;
+        let client = MetadataClient::new();
+        let err = client.load::<Document>(large).await.unwrap_err();
+        assert!(
+            matches!(err, MetadataError::TooLarge { limit } if limit == 512 * 1024),
+            "{}",
+            err
+        );
+
+        let client = MetadataClient::new().with_max_size(1024);
+        let err = client.load::<Document>(example).await.unwrap_err();
+        assert!(matches!(err, MetadataError::TooLarge { .. }), "{}", err);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retries() -> Result<()> {
+        let server = TestServer::start().await;
+        for (at, response, times) in [
+            ("/flaky.json", ResponseTemplate::new(503), 2),
+            (
+                "/flaky.json",
+                ResponseTemplate::new(200).set_body_string(EXAMPLE),
+                1,
+            ),
+            ("/limited.json", ResponseTemplate::new(429), 3),
+            ("/missing.json", ResponseTemplate::new(404), 1),
+            (
+                "/invalid.json",
+                ResponseTemplate::new(200).set_body_string("{"),
+                1,
+            ),
+        ] {
+            let mock = TestServer::get(at)
+                .respond_with(response)
+                .up_to_n_times(times)
+                .expect(times);
+            server.mount(mock).await;
+        }
+        let client = MetadataClient::new().with_retries(3, Duration::from_millis(10));
+        let url = |name: &str| server.url(name);
+
+        let document = client.load::<Document>(url("/flaky.json")).await?;
+        assert_eq!(document, example_document());
+
+        let err = client
+            .load::<Document>(url("/limited.json"))
+            .await
+            .unwrap_err();
+        assert!(err.is_transient(), "{}", err);
+
+        for name in ["/missing.json", "/invalid.json"] {
+            let err = client.load::<Document>(url(name)).await.unwrap_err();
+            assert!(!err.is_transient(), "{}", err);
+        }
+        server.verify().await;
+
+        // The wait doubles, but stops growing rather than overflowing, however many attempts are allowed
+        assert_eq!(
+            next_retry_delay(Duration::from_millis(10)),
+            Duration::from_millis(20)
+        );
+        assert_eq!(next_retry_delay(Duration::MAX), MAX_RETRY_DELAY);
+        let mut delay = Duration::from_secs(1);
+        for _ in 0..200 {
+            delay = next_retry_delay(delay);
+        }
+        assert_eq!(delay, MAX_RETRY_DELAY);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cache() -> Result<()> {
+        let server = TestServer::start().await;
+        let last_modified = "Wed, 21 Oct 2015 07:28:00 GMT";
+        server
+            .mount(
+                TestServer::get("/tagged.json")
+                    .and(header("If-None-Match", "\"v1\""))
+                    .respond_with(ResponseTemplate::new(304))
+                    .expect(3),
+            )
+            .await;
+        server
+            .mount(
+                TestServer::get("/tagged.json")
+                    .respond_with(
+                        ResponseTemplate::new(200)
+                            .insert_header("ETag", "\"v1\"")
+                            .set_body_string(EXAMPLE),
+                    )
+                    .expect(2),
+            )
+            .await;
+        server
+            .mount(
+                TestServer::get("/dated.json")
+                    // The header matcher would split the date at its comma
+                    .and(header_exists("If-Modified-Since"))
+                    .respond_with(ResponseTemplate::new(304))
+                    .expect(1),
+            )
+            .await;
+        server
+            .mount(
+                TestServer::get("/dated.json")
+                    .respond_with(
+                        ResponseTemplate::new(200)
+                            .insert_header("Last-Modified", last_modified)
+                            .set_body_string(EXAMPLE),
+                    )
+                    .expect(1),
+            )
+            .await;
+        let url = |name: &str| server.url(&format!("/{name}"));
+
+        // The second fetch of each is answered with a 304, and the body from the first is reused
+        let client = MetadataClient::new().with_cache();
+        for name in ["tagged.json", "dated.json"] {
+            for _ in 0..2 {
+                let document = client.load::<Document>(url(name)).await?;
+                assert_eq!(document, example_document());
+            }
+        }
+
+        // Clones share the cache, but a clone with a smaller limit doesn't accept a larger cached copy
+        let err = client
+            .clone()
+            .with_max_size(16)
+            .load::<Document>(url("tagged.json"))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, MetadataError::TooLarge { limit: 16 }),
+            "{err}"
+        );
+
+        // Each hop of a redirect is revalidated with its own validators, so the url redirected from never
+        // gets those of the document it redirects to
+        server
+            .mount(
+                TestServer::get("/hop.json")
+                    .and(header_exists("If-None-Match"))
+                    .respond_with(ResponseTemplate::new(412))
+                    .expect(0),
+            )
+            .await;
+        server
+            .mount(
+                TestServer::get("/hop.json")
+                    .respond_with(
+                        ResponseTemplate::new(302)
+                            .insert_header("Location", server.url("/target.json").to_string()),
+                    )
+                    .expect(2),
+            )
+            .await;
+        server
+            .mount(
+                TestServer::get("/target.json")
+                    .and(header("If-None-Match", "\"v2\""))
+                    .respond_with(ResponseTemplate::new(304))
+                    .expect(1),
+            )
+            .await;
+        server
+            .mount(
+                TestServer::get("/target.json")
+                    .respond_with(
+                        ResponseTemplate::new(200)
+                            .insert_header("ETag", "\"v2\"")
+                            .set_body_string(EXAMPLE),
+                    )
+                    .expect(1),
+            )
+            .await;
+        let client = MetadataClient::new().with_private_redirects().with_cache();
+        for _ in 0..2 {
+            let document = client.load::<Document>(url("hop.json")).await?;
+            assert_eq!(document, example_document());
+        }
+
+        // Without a cache, nothing is revalidated, so the full body is fetched again
+        let document = MetadataClient::new()
+            .load::<Document>(url("tagged.json"))
+            .await?;
+        assert_eq!(document, example_document());
+
+        // A 304 for a document that was never cached is an error, rather than an empty body
+        let client = reqwest::Client::builder()
+            .default_headers(reqwest::header::HeaderMap::from_iter([(
+                reqwest::header::IF_NONE_MATCH,
+                "\"v1\"".parse()?,
+            )]))
+            .build()?;
+        let err = MetadataClient::with_client(client)
+            .with_cache()
+            .load::<Document>(url("tagged.json"))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            MetadataError::Status {
+                status: reqwest::StatusCode::NOT_MODIFIED,
+                ..
+            }
+        ));
+        server.verify().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_clone() -> Result<()> {
+        let server = TestServer::start().await;
+        server
+            .mount(
+                TestServer::get("/example.json")
+                    .and(header("If-None-Match", "\"v1\""))
+                    .respond_with(ResponseTemplate::new(304))
+                    .expect(2),
+            )
+            .await;
+        server
+            .mount(
+                TestServer::get("/example.json")
+                    .respond_with(
+                        ResponseTemplate::new(200)
+                            .insert_header("ETag", "\"v1\"")
+                            .set_body_string(EXAMPLE),
+                    )
+                    .expect(2),
+            )
+            .await;
+        let url = server.url("/example.json");
+
+        // Clones share the cache, so only the first load downloads the document
+        let client = MetadataClient::default().with_cache();
+        for _ in 0..3 {
+            let document = client.clone().load::<Document>(url.clone()).await?;
+            assert_eq!(document, example_document());
+        }
+
+        // ...and the configuration
+        let err = MetadataClient::new()
+            .with_max_size(16)
+            .clone()
+            .load::<Document>(url)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, MetadataError::TooLarge { limit: 16 }),
+            "{}",
+            err
+        );
+
+        // Adding a context to a client that has been cloned leaves the clone as it was
+        let context = IriBuf::new("https://example.com/unknown.jsonld".to_string()).unwrap();
+        let unknown = include_str!("../tests/fixtures/remote-context.json")
+            .replace(CIP100_CONTEXT, context.as_str());
+        let base = Url::parse("https://example.com/metadata.jsonld")?;
+        let original = MetadataClient::new();
+        let clone = original.clone();
+        let extended =
+            original.with_context(context, include_str!("../contexts/cip-0100.common.jsonld"))?;
+        assert!(extended
+            .load_str::<Document>(&unknown, base.clone())
+            .await
+            .is_ok());
+        assert!(clone.load_str::<Document>(&unknown, base).await.is_err());
+        server.verify().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_redirects() -> Result<()> {
+        let server = TestServer::start().await;
+        for (from, to) in [
+            ("/moved.json", "/example.json"),
+            ("/file.json", "file:///etc/passwd"),
+            ("/metadata.json", "http://169.254.169.254/latest/meta-data"),
+            ("/loop.json", "/loop.json"),
+        ] {
+            server
+                .respond(
+                    from,
+                    ResponseTemplate::new(302).insert_header("Location", to),
+                )
+                .await;
+        }
+        server.serve("/example.json", EXAMPLE).await;
+        let url = |name: &str| server.url(&format!("/{name}"));
+        let redirect_reason = |err: MetadataError| match err {
+            MetadataError::Redirect { reason, .. } => reason,
+            err => panic!("expected a rejected redirect, got {err}"),
+        };
+
+        // The mock server is on localhost, so redirects within it are only followed when allowed
+        let err = MetadataClient::new()
+            .load::<Document>(url("moved.json"))
+            .await
+            .unwrap_err();
+        assert!(redirect_reason(err).contains("private"));
+        let local = MetadataClient::new().with_private_redirects();
+        let document = local.load::<Document>(url("moved.json")).await?;
+        assert_eq!(document, example_document());
+
+        let err = local.load::<Document>(url("file.json")).await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "refusing to follow redirect from {} to file:///etc/passwd: redirects must lead to http or https",
+                url("file.json")
+            )
+        );
+        let err = MetadataClient::new()
+            .load::<Document>(url("metadata.json"))
+            .await
+            .unwrap_err();
+        assert!(redirect_reason(err).contains("private"));
+
+        let err = local.load::<Document>(url("loop.json")).await.unwrap_err();
+        assert!(matches!(err, MetadataError::TooManyRedirects(5)), "{err}");
+        let err = local
+            .with_max_redirects(0)
+            .load::<Document>(url("moved.json"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, MetadataError::TooManyRedirects(0)), "{err}");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_address_policy() -> Result<()> {
+        let server = TestServer::start().await;
+        let direct = server.serve("/example.json", EXAMPLE).await;
+        let mut localhost = direct.clone();
+        localhost.set_host(Some("localhost"))?;
+        let moved = server
+            .respond(
+                "/moved.json",
+                ResponseTemplate::new(302).insert_header("Location", localhost.as_str()),
+            )
+            .await;
+        let denied = |err: MetadataError| match err {
+            MetadataError::DeniedAddress { host, address } => (host, address.to_string()),
+            err => panic!("expected a denied address, got {err}"),
+        };
+
+        // A literal IP is checked before connecting, and a domain once it's resolved
+        let client = MetadataClient::new().with_address_policy(AddressPolicy::new());
+        let err = client.load::<Document>(direct).await.unwrap_err();
+        assert_eq!(
+            denied(err),
+            ("127.0.0.1".to_string(), "127.0.0.1".to_string())
+        );
+        let err = client
+            .load::<Document>(localhost.clone())
+            .await
+            .unwrap_err();
+        assert!(!err.is_transient());
+        assert_eq!(denied(err).0, "localhost");
+
+        // Allowing a host doesn't allow the hosts it redirects to
+        let client = MetadataClient::new()
+            .with_private_redirects()
+            .with_address_policy(AddressPolicy::new().allow_host("127.0.0.1"));
+        let err = client.load::<Document>(moved).await.unwrap_err();
+        assert_eq!(denied(err).0, "localhost");
+
+        let client =
+            MetadataClient::new().with_address_policy(AddressPolicy::new().allow_host("localhost"));
+        let document = client.load::<Document>(localhost.clone()).await?;
+        assert_eq!(document, example_document());
+
+        // A proxy would resolve hosts itself, out of reach of the policy, so one on the builder is turned off
+        let builder =
+            reqwest::Client::builder().proxy(reqwest::Proxy::all(server.url("/").as_str())?);
+        let client = MetadataClient::new().with_address_policy_and_builder(
+            AddressPolicy::new().allow_host("127.0.0.1"),
+            builder,
+        )?;
+        let err = client.load::<Document>(localhost).await.unwrap_err();
+        assert_eq!(denied(err).0, "localhost");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ipfs_gateway() -> Result<()> {
+        let content = r#"{
+            "@context": {
+                "CIP100": "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0100/README.md#",
+                "hashAlgorithm": "CIP100:hashAlgorithm",
+                "body": "CIP100:body",
+                "comment": "CIP100:comment",
+                "externalUpdates": "CIP100:externalUpdates",
+                "title": "CIP100:update-title",
+                "uri": "CIP100:update-uri"
+            },
+            "hashAlgorithm": "blake2b-256",
+            "body": {
+                "comment": "Pinned to IPFS",
+                "externalUpdates": [{ "title": "Blog", "uri": { "@id": "blog" } }]
+            }
+        }"#;
+        let cid = "QmW6d6zUcDG4eTJ1eXdtVeQhEdVGoQ2QB6U1MRfRaKLm5w";
+        let server = TestServer::start().await;
+        server
+            .mount(
+                TestServer::get(&format!("/gateway/ipfs/{}/metadata.jsonld", cid))
+                    .respond_with(ResponseTemplate::new(200).set_body_string(content))
+                    .expect(1),
+            )
+            .await;
+        // The gateway is used whether or not it's given with a trailing slash
+        let client = MetadataClient::new().with_ipfs_gateway(server.url("/gateway/ipfs"));
+        let url = Url::parse(&format!("ipfs://{}/metadata.jsonld", cid))?;
+        let document = client.load::<Document>(url).await?;
+        assert_eq!(
+            document.body.external_updates[0].uri.as_str(),
+            format!("ipfs://{}/blog", cid)
+        );
+
+        let gateway = Url::parse(DEFAULT_IPFS_GATEWAY)?;
+        let arweave = Url::parse(DEFAULT_ARWEAVE_GATEWAY)?;
+        assert_eq!(
+            gateway_url(Url::parse(&format!("ipfs://{}", cid))?, &gateway, &arweave)?.as_str(),
+            format!("https://ipfs.io/ipfs/{}", cid)
+        );
+        let other = Url::parse("https://example.com/metadata.jsonld")?;
+        assert_eq!(gateway_url(other.clone(), &gateway, &arweave)?, other);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_arweave_gateway() -> Result<()> {
+        let txid = "bNbA3TEQVL60xlgCcqdz4ZPHFZ711cZ3hmkpGttDt_U";
+        let server = TestServer::start().await;
+        server.serve(&format!("/arweave/{}", txid), EXAMPLE).await;
+        let client = MetadataClient::new().with_arweave_gateway(server.url("/arweave"));
+        let url = Url::parse(&format!("ar://{}", txid))?;
+        let document = client.load::<Document>(url.clone()).await?;
+        assert_eq!(document, example_document());
+
+        let ipfs = Url::parse(DEFAULT_IPFS_GATEWAY)?;
+        let gateway = Url::parse(DEFAULT_ARWEAVE_GATEWAY)?;
+        assert_eq!(
+            gateway_url(url, &ipfs, &gateway)?.as_str(),
+            format!("https://arweave.net/{}", txid)
+        );
+        assert_eq!(
+            gateway_url(
+                Url::parse(&format!("ar://{}/data.json", txid))?,
+                &ipfs,
+                &gateway
+            )?
+            .as_str(),
+            format!("https://arweave.net/{}/data.json", txid)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_register_scheme() -> Result<()> {
+        let server = TestServer::start().await;
+        server.serve("/drive/metadata", EXAMPLE).await;
+        let drive = server.url("/drive/");
+        let client = MetadataClient::new()
+            .register_scheme("drive", move |url| {
+                let resolved = drive.join(url.host_str().unwrap_or_default());
+                Ok(Resolved::Url(resolved.map_err(anyhow::Error::from)?))
+            })
+            .register_scheme("memory", |_| {
+                Ok(Resolved::Content(EXAMPLE.as_bytes().to_vec()))
+            })
+            .register_scheme("ipfs", |_| {
+                Err(MetadataError::InvalidValue {
+                    field: "ipfs url".to_string(),
+                    reason: "is not pinned here".to_string(),
+                })
+            });
+
+        let document = client
+            .load::<Document>(Url::parse("drive://metadata")?)
+            .await?;
+        assert_eq!(document, example_document());
+        let document = client
+            .load::<Document>(Url::parse("memory://example")?)
+            .await?;
+        assert_eq!(document, example_document());
+
+        // A handler replaces the gateway, and its errors are returned as is
+        let err = client
+            .load::<Document>(Url::parse("ipfs://bafkreid")?)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("is not pinned here"), "{}", err);
+        let err = client
+            .clone()
+            .with_max_size(16)
+            .load::<Document>(Url::parse("memory://example")?)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, MetadataError::TooLarge { limit: 16 }),
+            "{}",
+            err
+        );
+
+        // Documents may reference urls of a registered scheme
+        let referencing = EXAMPLE.replace("https://314pool.com", "drive://updates");
+        let url = Url::parse("https://example.com/metadata.jsonld")?;
+        assert!(MetadataClient::new()
+            .load_str::<Document>(&referencing, url.clone())
+            .await
+            .is_err());
+        let document = client.load_str::<Document>(&referencing, url).await?;
+        assert_eq!(
+            document.body.external_updates[0].uri.as_str(),
+            "drive://updates"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_data_url() -> Result<()> {
+        let client = MetadataClient::new();
+        let url = Url::parse(&format!(
+            "data:application/ld+json;base64,{}",
+            BASE64_STANDARD.encode(EXAMPLE)
+        ))?;
+        let document = client.load::<Document>(url).await?;
+        assert_eq!(document, example_document());
+
+        // The payload doesn't have to be base64 encoded
+        let encoded: String = EXAMPLE
+            .bytes()
+            .map(|byte| match byte {
+                b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                    (byte as char).to_string()
+                }
+                _ => format!("%{:02X}", byte),
+            })
+            .collect();
+        let url = Url::parse(&format!("data:application/json,{}", encoded))?;
+        let document = client.load::<Document>(url).await?;
+        assert_eq!(document, example_document());
+
+        let url = Url::parse("data:application/json;base64,not*base64")?;
+        let err = client.load::<Document>(url).await.unwrap_err();
+        assert!(matches!(err, MetadataError::InvalidDataUrl(_)), "{}", err);
+
+        // The size limit applies to the decoded document, and a payload far too long isn't decoded at all
+        let small = MetadataClient::new().with_max_size(EXAMPLE.len() - 1);
+        let url = Url::parse(&format!(
+            "data:application/ld+json;base64,{}",
+            BASE64_STANDARD.encode(EXAMPLE)
+        ))?;
+        let err = small.load::<Document>(url).await.unwrap_err();
+        assert!(matches!(err, MetadataError::TooLarge { .. }), "{}", err);
+        let url = Url::parse(&format!(
+            "data:application/json,{}",
+            "%20".repeat(EXAMPLE.len())
+        ))?;
+        let err = small.load::<Document>(url).await.unwrap_err();
+        assert!(matches!(err, MetadataError::TooLarge { .. }), "{}", err);
+
+        let url = Url::parse("data:text/html,<script></script>")?;
+        let err = client.load::<Document>(url).await.unwrap_err();
+        assert!(
+            matches!(err, MetadataError::UnsupportedMediaType(ref mime) if mime == "text/html"),
+            "{}",
+            err
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_compressed_response() -> Result<()> {
+        use flate2::{write::GzEncoder, Compression};
+        use std::io::Write;
+
+        let gzip = |content: &[u8]| -> std::io::Result<Vec<u8>> {
+            let mut encoder = GzEncoder::new(vec![], Compression::default());
+            encoder.write_all(content)?;
+            encoder.finish()
+        };
+        let server = TestServer::start().await;
+        let url = server
+            .respond(
+                "/example.json",
+                ResponseTemplate::new(200)
+                    .insert_header("content-encoding", "gzip")
+                    .set_body_raw(gzip(EXAMPLE.as_bytes())?, "application/json"),
+            )
+            .await;
+        let document = MetadataClient::new().load::<Document>(url.clone()).await?;
+        assert_eq!(document, example_document());
+
+        // The size limit applies to the decompressed document, not what went over the wire
+        let err = MetadataClient::new()
+            .with_max_size(EXAMPLE.len() - 1)
+            .load::<Document>(url)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, MetadataError::TooLarge { .. }), "{}", err);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_content_types() -> Result<()> {
+        let server = TestServer::start().await;
+        for (name, content_type) in [
+            ("json", "Application/JSON; charset=utf-8"),
+            ("jsonld", "application/ld+json"),
+            ("html", "text/html"),
+        ] {
+            server
+                .respond(
+                    &format!("/example.{name}"),
+                    ResponseTemplate::new(200).set_body_raw(EXAMPLE, content_type),
+                )
+                .await;
+        }
+        let url = |name: &str| server.url(&format!("/example.{name}"));
+
+        let lenient = MetadataClient::new();
+        let strict = MetadataClient::new().with_content_type_validation();
+        for name in ["json", "jsonld", "html"] {
+            lenient.load::<Document>(url(name)).await?;
+        }
+        strict.load::<Document>(url("json")).await?;
+        strict.load::<Document>(url("jsonld")).await?;
+        let err = strict.load::<Document>(url("html")).await.unwrap_err();
+        assert!(
+            matches!(err, MetadataError::UnsupportedMediaType(ref t) if t == "text/html"),
+            "{}",
+            err
+        );
+
+        let custom = MetadataClient::new().with_content_types(["text/html"]);
+        custom.load::<Document>(url("html")).await?;
+        assert!(custom.load::<Document>(url("json")).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_uri_schemes() -> Result<()> {
+        let document = |uri: &str| {
+            Document::builder()
+                .comment("Where does this go?")
+                .add_reference(reference(ReferenceType::Other, "Link", uri))
+                .build()
+                .to_jsonld()
+        };
+        let url = Url::parse("https://example.com/metadata.jsonld")?;
+
+        let content = document("javascript:alert(1)")?;
+        let err = MetadataClient::new()
+            .load_str::<Document>(&content, url.clone())
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, MetadataError::DisallowedScheme { ref field, .. } if field == "reference uri"),
+            "{}",
+            err
+        );
+        let loaded = MetadataClient::new()
+            .without_uri_validation()
+            .load_str::<Document>(&content, url.clone())
+            .await?;
+        assert_eq!(
+            loaded.body.references[0].uri.as_str(),
+            "javascript:alert(1)"
+        );
+
+        let content = document("IPFS://QmW6d6zUcDG4eTJ1eXdtVeQhEdVGoQ2QB6U1MRfRaKLm5w")?;
+        assert!(MetadataClient::new()
+            .load_str::<Document>(&content, url.clone())
+            .await
+            .is_ok());
+        let err = MetadataClient::new()
+            .with_uri_schemes(["https"])
+            .load_str::<Document>(&content, url)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, MetadataError::DisallowedScheme { .. }),
+            "{}",
+            err
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_text_normalization() -> Result<()> {
+        let url = Url::parse("https://example.com/metadata.jsonld")?;
+        let padded = EXAMPLE
+            .replace("\"Pi Lanningham\"", "\"  Pi\\u2060Lanningham\\t\"")
+            .replace("\"label\": \"CIP-100\"", "\"label\": \"\\ufeffCIP-100 \"");
+
+        // Text is kept as written by default, and can be normalized after the fact
+        let raw = MetadataClient::new()
+            .load_str::<Document>(&padded, url.clone())
+            .await?;
+        assert_eq!(raw.authors[0].name.value(), "  Pi\u{2060}Lanningham\t");
+        assert_eq!(raw.authors[0].name.normalized(), "PiLanningham");
+        assert_eq!(raw.body.references[0].label.value(), "\u{FEFF}CIP-100 ");
+
+        let normalized = MetadataClient::new()
+            .with_text_normalization()
+            .load_str::<Document>(&padded, url.clone())
+            .await?;
+        assert_eq!(normalized.authors[0].name.value(), "PiLanningham");
+        assert_eq!(normalized.authors[0].name.language(), Some("en-us"));
+        assert_eq!(normalized.body.references[0].label.value(), "CIP-100");
+        assert_eq!(
+            normalized.body.references[0].uri,
+            example_document().body.references[0].uri
+        );
+        assert_ne!(normalized.canonical_hash()?, raw.canonical_hash()?);
+
+        // A document without stray characters is unchanged
+        let clean = MetadataClient::new()
+            .with_text_normalization()
+            .load_str::<Document>(EXAMPLE, url)
+            .await?;
+        assert_eq!(clean, example_document());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_strict_hash_algorithm() -> Result<()> {
+        let url = Url::parse("https://example.com/metadata.jsonld")?;
+        let typo = EXAMPLE.replace("\"blake2b-256\"", "\"blake2b256\"");
+
+        // Leniently, the typo is kept, but flagged
+        let document = MetadataClient::new()
+            .load_str::<Document>(&typo, url.clone())
+            .await?;
+        assert_eq!(
+            document.hash_algorithm,
+            HashAlgorithm::Unknown("blake2b256".to_string())
+        );
+        assert!(!document.hash_algorithm_recognized());
+        assert!(example_document().hash_algorithm_recognized());
+
+        let strict = MetadataClient::new().with_strict_hash_algorithm();
+        let err = strict
+            .load_str::<Document>(&typo, url.clone())
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "hash algorithm \"blake2b256\" is not one of blake2b-256, blake2b-224, sha256"
+        );
+        let document = strict.load_str::<Document>(EXAMPLE, url).await?;
+        assert_eq!(document, example_document());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_load_raw() -> Result<()> {
+        let content = EXAMPLE.replacen(
+            "\"hashAlgorithm\": \"blake2b-256\",",
+            "\"hashAlgorithm\": \"blake2b-256\", \"https://example.com/vendor#rating\": 5,",
+            1,
+        );
+        let server = TestServer::start().await;
+        let url = server.serve("/example.json", content).await;
+        let (document, expanded) = MetadataClient::new().load_raw::<Document>(url).await?;
+        assert_eq!(document.body, example_document().body);
+
+        let node = expanded.objects().iter().next().unwrap().as_node().unwrap();
+        let rating = node
+            .get_any(&Iri::new("https://example.com/vendor#rating")?)
+            .context("vendor property is missing")?;
+        assert_eq!(
+            rating
+                .as_value()
+                .and_then(|v| v.as_number())
+                .map(|n| n.as_str()),
+            Some("5")
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_load_with_contexts() -> Result<()> {
+        use json_ld::syntax::{IntoJsonWithContext, Print};
+
+        let server = TestServer::start().await;
+        let client = MetadataClient::new();
+        let contextual = server.serve("/example.json", EXAMPLE).await;
+        let (document, contexts) = client.load_with_contexts::<Document>(contextual).await?;
+        assert_eq!(document, example_document());
+        assert!(matches!(&contexts[..], [DeclaredContext::Inline(_)]));
+
+        // The expanded form is plain JSON keyed by full IRIs, so it parses the same with no @context at all
+        let url = Url::parse("https://example.com/metadata.jsonld")?;
+        let (_, expanded) = parse_raw::<Document, _>(
+            EXAMPLE,
+            url,
+            &mut &StaticContextLoader::new(),
+            &ParseOptions::default(),
+        )
+        .await?;
+        let contextless = server
+            .serve(
+                "/expanded.json",
+                json_ld::syntax::Value::Array(
+                    expanded
+                        .into_iter()
+                        .map(|object| object.into_json_with(&()))
+                        .collect(),
+                )
+                .compact_print()
+                .to_string(),
+            )
+            .await;
+        let (document, contexts) = client.load_with_contexts::<Document>(contextless).await?;
+        assert_eq!(document, example_document());
+        assert!(contexts.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_load_with_warnings() -> Result<()> {
+        let server = TestServer::start().await;
+        for (name, content) in [
+            ("example.json", EXAMPLE.to_string()),
+            (
+                "unknown.json",
+                EXAMPLE.replace(
+                    "\"@type\": \"Other\"",
+                    "\"@type\": \"https://example.com/Spec\"",
+                ),
+            ),
+            (
+                "broken.json",
+                EXAMPLE.replace("\"label\": \"CIP-100\",", ""),
+            ),
+        ] {
+            server.serve(&format!("/{name}"), content).await;
+        }
+        let url = |name: &str| server.url(&format!("/{name}"));
+        let client = MetadataClient::new();
+
+        let (document, warnings) = client
+            .load_with_warnings::<Document>(url("example.json"))
+            .await?;
+        assert_eq!(document, example_document());
+        assert_eq!(warnings, vec![]);
+
+        let (document, warnings) = client
+            .load_with_warnings::<Document>(url("unknown.json"))
+            .await?;
+        assert_eq!(document.body.comment, example_document().body.comment);
+        assert_eq!(
+            warnings,
+            vec![Warning::UnknownReferenceType {
+                label: "CIP-100".to_string(),
+                reference_type: IriBuf::new("https://example.com/Spec".to_string()).unwrap(),
+            }]
+        );
+
+        // A reference without a label is a schema violation, not a warning
+        let err = client
+            .load_with_warnings::<Document>(url("broken.json"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, MetadataError::MissingField { .. }), "{err}");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_load_reader() -> Result<()> {
+        let url = Url::parse("https://example.com/metadata.jsonld")?;
+        let client = MetadataClient::new();
+        let reader = tokio::io::BufReader::with_capacity(64, EXAMPLE.as_bytes());
+        let document = client.load_reader::<Document>(reader, url.clone()).await?;
+        assert_eq!(document, example_document());
+
+        // An endless stream is cut off at the max size
+        let err = client
+            .with_max_size(1024)
+            .load_reader::<Document>(tokio::io::repeat(b' '), url)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, MetadataError::TooLarge { limit: 1024 }),
+            "{err}"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_load_verified() -> Result<()> {
+        let server = TestServer::start().await;
+        let url = server.serve("/example.json", EXAMPLE).await;
+        let client = MetadataClient::new();
+
+        let hash: [u8; 32] = Blake2b::<U32>::digest(EXAMPLE.as_bytes()).into();
+        let document = client.load_verified::<Document>(url.clone(), hash).await?;
+        assert_eq!(document, example_document());
+
+        let err = client
+            .load_verified::<Document>(url, [0; 32])
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, MetadataError::HashMismatch { expected, actual } if expected == [0; 32] && actual == hash),
+            "{}",
+            err
+        );
+
+        // Content that isn't downloaded is checked all the same
+        let data = Url::parse(&format!(
+            "data:application/json;base64,{}",
+            BASE64_STANDARD.encode(EXAMPLE)
+        ))?;
+        assert!(client
+            .load_verified::<Document>(data.clone(), hash)
+            .await
+            .is_ok());
+        let err = client
+            .load_verified::<Document>(data, [0; 32])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, MetadataError::HashMismatch { .. }), "{}", err);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_verify_anchors() -> Result<()> {
+        let server = TestServer::start().await;
+        let url = server.serve("/example.json", EXAMPLE).await;
+        let missing = server
+            .respond("/missing.json", ResponseTemplate::new(404))
+            .await;
+        let hash = hex::encode(Blake2b::<U32>::digest(EXAMPLE.as_bytes()));
+        let input = [
+            format!(r#"{{"url": "{url}", "hash": "{hash}", "tx": "abc"}}"#),
+            String::new(),
+            format!(r#"{{"url": "{url}", "hash": "{}"}}"#, "00".repeat(32)),
+            format!(r#"{{"url": "{missing}", "hash": "{hash}"}}"#),
+            format!(r#"{{"url": "{url}"}}"#),
+            "not json".to_string(),
+        ]
+        .join("\n");
+
+        let client = MetadataClient::new();
+        let checks: Vec<AnchorCheck> = client
+            .verify_anchors::<Document, _>(input.as_bytes(), 2)
+            .collect()
+            .await;
+        let summary: Vec<_> = checks
+            .iter()
+            .map(|check| (check.line, check.url.as_str(), check.ok))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1, url.as_str(), true),
+                (3, url.as_str(), false),
+                (4, missing.as_str(), false),
+                (5, url.as_str(), false),
+                (6, "", false),
+            ]
+        );
+        assert_eq!(checks[0].error, None);
+        assert!(
+            checks[1].error.as_ref().unwrap().contains("hash"),
+            "{:?}",
+            checks[1]
+        );
+        assert_eq!(checks[3].error.as_deref(), Some("no anchor hash field"));
+        Ok(())
+    }
+
+    #[cfg(feature = "anchor")]
+    #[tokio::test]
+    async fn test_load_anchor() -> Result<()> {
+        let server = TestServer::start().await;
+        let document = example_document();
+        let url = server.url("/anchored.jsonld");
+        let anchor = Anchor::for_document(&document, url)?;
+        server
+            .serve("/anchored.jsonld", document.to_jsonld()?)
+            .await;
+
+        let client = MetadataClient::new();
+        assert_eq!(client.load_anchor::<Document>(&anchor).await?, document);
+        let tampered = Anchor::new(anchor.url.clone(), [0; 32]);
+        assert!(matches!(
+            client.load_anchor::<Document>(&tampered).await,
+            Err(MetadataError::HashMismatch { .. })
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_request_headers() -> Result<()> {
+        let server = TestServer::start().await;
+        server
+            .mount(
+                TestServer::get("/default.json")
+                    .and(header("user-agent", DEFAULT_USER_AGENT))
+                    .respond_with(ResponseTemplate::new(200).set_body_string(EXAMPLE))
+                    .expect(1),
+            )
+            .await;
+        let url = server.url("/default.json");
+        MetadataClient::new().load::<Document>(url).await?;
+
+        // The API key is only for the server it was configured for, not one it redirects to
+        let elsewhere = TestServer::start().await;
+        let target = elsewhere.serve("/moved.json", EXAMPLE).await;
+        server
+            .mount(
+                TestServer::get("/keyed.json")
+                    .and(header("user-agent", "indexer/1.0"))
+                    .and(header("x-api-key", "secret"))
+                    .respond_with(
+                        ResponseTemplate::new(302).insert_header("location", target.as_str()),
+                    )
+                    .expect(1),
+            )
+            .await;
+        let client = MetadataClient::new()
+            .with_private_redirects()
+            .with_user_agent("indexer/1.0")?
+            .with_header("X-Api-Key", "secret")?;
+        let url = server.url("/keyed.json");
+        assert_eq!(client.load::<Document>(url).await?, example_document());
+        let requests = elsewhere.received_requests().await;
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].headers["user-agent"], "indexer/1.0");
+        assert!(!requests[0].headers.contains_key("x-api-key"));
+
+        assert!(matches!(
+            MetadataClient::new().with_header("bad name", "value"),
+            Err(MetadataError::InvalidValue { .. })
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_invalid_json() -> Result<()> {
+        let server = TestServer::start().await;
+        let url = server.serve("/invalid.json", "{\"hashAlgorithm\": ").await;
+        let err = MetadataClient::new()
+            .load::<Document>(url)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("at byte 18"), "{}", err);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_load_bytes() -> Result<()> {
+        let client = MetadataClient::new();
+        let url = Url::parse("https://example.com/metadata.jsonld")?;
+        let with_bom = include_bytes!("../tests/fixtures/example-bom.json");
+        let document = client.load_bytes::<Document>(with_bom, url.clone()).await?;
+        assert_eq!(document, example_document());
+
+        // Served with a byte order mark, the document still parses, and is hashed exactly as served
+        let server = TestServer::start().await;
+        let served = server
+            .respond(
+                "/bom.json",
+                ResponseTemplate::new(200).set_body_bytes(with_bom.as_slice()),
+            )
+            .await;
+        let hash: [u8; 32] = Blake2b::<U32>::digest(with_bom).into();
+        let document = client.load_verified::<Document>(served, hash).await?;
+        assert_eq!(document, example_document());
+
+        let mut invalid = with_bom.to_vec();
+        invalid[10] = 0xFF;
+        let err = client
+            .load_bytes::<Document>(&invalid, url.clone())
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unable to parse document at byte 10: invalid UTF-8"
+        );
+
+        let utf16: Vec<u8> = [0xFF, 0xFE]
+            .into_iter()
+            .chain("{}".encode_utf16().flat_map(u16::to_le_bytes))
+            .collect();
+        let err = client
+            .load_bytes::<Document>(&utf16, url)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("UTF-16"), "{}", err);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_error_kinds() -> Result<()> {
+        let server = TestServer::start().await;
+        let empty = server
+            .serve(
+                "/empty.json",
+                r#"{"@context": {"hashAlgorithm": "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0100/README.md#hashAlgorithm"}, "hashAlgorithm": "blake2b-256"}"#,
+            )
+            .await;
+        let client = MetadataClient::new();
+
+        let url = server.url("/missing.json");
+        let err = client.load::<Document>(url.clone()).await.unwrap_err();
+        assert!(
+            matches!(err, MetadataError::Status { status: reqwest::StatusCode::NOT_FOUND, url: ref at, content_type: None } if *at == url),
+            "{}",
+            err
+        );
+
+        // The error reports where the response actually came from, after redirects, and what it was
+        let gone = server
+            .respond(
+                "/gone.json",
+                ResponseTemplate::new(404)
+                    .set_body_raw("<h1>Not Found</h1>", "text/html; charset=utf-8"),
+            )
+            .await;
+        let moved = server
+            .respond(
+                "/moved.json",
+                ResponseTemplate::new(302).insert_header("location", gone.as_str()),
+            )
+            .await;
+        let err = MetadataClient::new()
+            .with_private_redirects()
+            .load::<Document>(moved)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, MetadataError::Status { status: reqwest::StatusCode::NOT_FOUND, url, content_type: Some(content_type) }
+                if *url == gone && content_type == "text/html; charset=utf-8"),
+            "{}",
+            err
+        );
+        assert_eq!(
+            err.to_string(),
+            format!("{gone} responded with 404 Not Found (text/html; charset=utf-8)")
+        );
+        assert!(!err.is_transient());
+
+        let err = client.load::<Document>(empty.clone()).await.unwrap_err();
+        assert!(
+            matches!(err, MetadataError::MissingField { ref field, .. } if field == "body"),
+            "{}",
+            err
+        );
+
+        // Still usable with anyhow
+        let err = client
+            .load::<Document>(empty)
+            .await
+            .context("unable to load document")
+            .unwrap_err();
+        assert!(err.downcast_ref::<MetadataError>().is_some());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_graph() -> Result<()> {
+        let server = TestServer::start().await;
+        let url = server
+            .serve("/graph.json", include_str!("../tests/fixtures/graph.json"))
+            .await;
+        let client = MetadataClient::new();
+
+        let documents = client.load_all::<Document>(url.clone()).await?;
+        let comments: Vec<_> = documents
+            .iter()
+            .map(|d| d.body.comment.as_ref().unwrap().to_string())
+            .collect();
+        assert_eq!(comments, vec!["The first document", "The second document"]);
+
+        let err = client.load::<Document>(url).await.unwrap_err();
+        assert!(
+            matches!(
+                err,
+                MetadataError::MultipleDocuments {
+                    parseable: 2,
+                    objects: 2
+                }
+            ),
+            "{}",
+            err
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_count_objects() -> Result<()> {
+        let server = TestServer::start().await;
+        let client = MetadataClient::new();
+        let example = server.serve("/example.json", EXAMPLE).await;
+        assert_eq!(client.count_objects(example).await?, 1);
+
+        let graph = server
+            .serve("/graph.json", include_str!("../tests/fixtures/graph.json"))
+            .await;
+        assert_eq!(client.count_objects(graph).await?, 2);
+
+        // Without a context, none of the keys mean anything, so there's nothing left after expansion
+        let bare = server
+            .serve("/bare.json", r#"{"body": {"comment": "No context"}}"#)
+            .await;
+        assert_eq!(client.count_objects(bare.clone()).await?, 0);
+        let err = client.load::<Document>(bare).await.unwrap_err();
+        assert!(matches!(err, MetadataError::NoObjects), "{}", err);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_parse_governance_action() -> Result<()> {
+        let content = include_str!("../tests/fixtures/cip108-example.json");
+        let url = Url::parse("https://example.com/treasury-withdrawal.jsonld").unwrap();
+        let document = MetadataClient::new()
+            .load_str::<GovernanceActionDocument>(content, url)
+            .await?;
+        let expected = GovernanceActionDocument {
+            hash_algorithm: HashAlgorithm::Blake2b256,
+            authors: vec![],
+            body: GovernanceActionBody {
+                title: en("Withdraw 200000000000 ADA from the Treasury").into(),
+                r#abstract: en("Withdraw 200000000000 ADA from the Treasury to fund the Cardano Foundation's ongoing operations").into(),
+                motivation: None,
+                rationale: Some(en("Funding the operations of the Cardano Foundation ensures the ecosystem continues to grow").into()),
+                references: vec![reference(
+                    ReferenceType::Other,
+                    en("CIP-108"),
+                    "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0108/README.md",
+                )],
             },
         };
-        assert_eq!(cip100, expected);
+        assert_eq!(document, expected);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_parse_vote_rationale() -> Result<()> {
+        let content = include_str!("../tests/fixtures/cip136-example.json");
+        let url = Url::parse("https://example.com/rationale.jsonld").unwrap();
+        let document = MetadataClient::new()
+            .load_str::<VoteRationaleDocument>(content, url)
+            .await?;
+        let body = &document.body;
+        assert_eq!(
+            body.summary,
+            en("The treasury withdrawal is constitutional").into()
+        );
+        assert_eq!(body.precedent_discussion, None);
+        assert_eq!(
+            body.conclusion,
+            Some(en("We vote that the withdrawal is constitutional").into())
+        );
+        assert_eq!(
+            body.internal_vote,
+            Some(InternalVote {
+                constitutional: 5,
+                unconstitutional: 1,
+                abstain: 0,
+                did_not_vote: 1,
+            })
+        );
+        assert!(matches!(
+            &body.references[0].reference_type,
+            ReferenceType::Unknown(iri) if iri.as_str().ends_with("CIP-0136/README.md#RelevantArticles")
+        ));
+
+        let negative = content.replace(r#""abstain": 0"#, r#""abstain": -1"#);
+        let err = MetadataClient::new()
+            .load_str::<VoteRationaleDocument>(&negative, Url::parse("https://example.com/")?)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, MetadataError::WrongType { ref field, .. } if field == "internal vote abstain"),
+            "{}",
+            err
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_internal_vote_encodings() -> Result<()> {
+        let content = include_str!("../tests/fixtures/cip136-example.json");
+        let url = Url::parse("https://example.com/rationale.jsonld")?;
+        let client = MetadataClient::new();
+        let internal_vote = |abstain: &str| {
+            let content = content.replace(r#""abstain": 0"#, &format!(r#""abstain": {abstain}"#));
+            let client = &client;
+            let url = url.clone();
+            async move {
+                client
+                    .load_str::<VoteRationaleDocument>(&content, url)
+                    .await
+                    .map(|document| document.body.internal_vote.unwrap())
+            }
+        };
+
+        let plain = internal_vote("0").await?;
+        assert_eq!(plain.total(), 7);
+        for typed in [
+            r#"{ "@value": 0 }"#,
+            r#"{ "@value": 0, "@type": "http://www.w3.org/2001/XMLSchema#integer" }"#,
+            r#"{ "@value": "0", "@type": "http://www.w3.org/2001/XMLSchema#nonNegativeInteger" }"#,
+        ] {
+            assert_eq!(internal_vote(typed).await?, plain, "{typed}");
+        }
+
+        for invalid in [
+            "1.5",
+            r#""3""#,
+            r#"{ "@value": "3" }"#,
+            r#"{ "@value": "-3", "@type": "http://www.w3.org/2001/XMLSchema#integer" }"#,
+            r#"{ "@value": "3", "@type": "http://www.w3.org/2001/XMLSchema#string" }"#,
+        ] {
+            let err = internal_vote(invalid).await.unwrap_err();
+            assert!(
+                matches!(err, MetadataError::WrongType { ref field, .. } if field == "internal vote abstain"),
+                "{invalid}: {err}"
+            );
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_parse_drep() -> Result<()> {
+        let content = include_str!("../tests/fixtures/cip119-example.json");
+        let url = Url::parse("https://example.com/drep.jsonld").unwrap();
+        let document = MetadataClient::new()
+            .load_str::<DRepDocument>(content, url)
+            .await?;
+        assert_eq!(document.body.given_name, en("Ryan Williams").into());
+        assert_eq!(
+            document
+                .body
+                .objectives
+                .as_ref()
+                .map(Localized::to_string)
+                .as_deref(),
+            Some("Buy myself an island.")
+        );
+        assert_eq!(
+            document.body.image,
+            Some(Image {
+                content_url: IriBuf::new(
+                    "https://avatars.githubusercontent.com/u/44342099?v=4".to_string()
+                )
+                .unwrap(),
+                sha256: Some(
+                    "2a21e4f7b20c8c72f573707b068fb8fc6d8c64d5035c4e18ecae287947fe2b2e".to_string()
+                ),
+            })
+        );
+        assert_eq!(document.body.references.len(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_drep_profile_fields() -> Result<()> {
+        let content = include_str!("../tests/fixtures/cip119-example.json");
+        let url = Url::parse("https://example.com/drep.jsonld").unwrap();
+        let document = parse_str::<DRepDocument>(content, url.clone()).await?;
+        assert_eq!(document.objectives(), Some(&en("Buy myself an island.")));
+        assert_eq!(
+            document.motivations(),
+            Some(&en("I really would like to own an island."))
+        );
+        assert_eq!(
+            document.qualifications(),
+            Some(&en(
+                "I have my 100m swimming badge, so I would be qualified to be able to swim around island."
+            ))
+        );
+        assert_eq!(document.references(), &document.body.references[..]);
+        assert!(document
+            .references()
+            .iter()
+            .all(|reference| reference.label.value() != "Buy myself an island."));
+
+        let without = content.replace("\"objectives\": \"Buy myself an island.\",", "");
+        let document = parse_str::<DRepDocument>(&without, url).await?;
+        assert_eq!(document.objectives(), None);
+        assert!(document.motivations().is_some());
         Ok(())
     }
 }
@@ -0,0 +1,350 @@
+//! A command line tool for fetching and validating governance metadata documents
+//!
+//! Loads the document at a url or path, prints a summary of it, and exits non-zero if it can't be
+//! loaded as the expected CIP, or, with --verify, if any author's signature doesn't check out or there are
+//! no authors at all
+
+use std::{path::PathBuf, process::ExitCode};
+
+use cardano_governance_metadata::{
+    AnyDocument, DRepDocument, Document, GovernanceActionDocument, MetadataClient, MetadataError,
+    Result, VoteRationaleDocument,
+};
+use clap::{Parser, ValueEnum};
+use json_ld::Node;
+use serde::Serialize;
+use url::Url;
+
+/// Arguments that look like urls with one of these schemes are fetched; anything else is read from disk
+const URL_SCHEMES: &[&str] = &["https", "http", "ipfs", "ar", "data"];
+
+#[derive(Parser)]
+#[command(
+    version,
+    about = "Fetch and validate Cardano governance metadata documents"
+)]
+struct Args {
+    /// The url of the document, such as https://, ipfs:// or ar://, or a path to a file on disk
+    source: String,
+    /// Which CIP the document follows; detected from the document if not given
+    #[arg(long, value_enum)]
+    cip: Option<Cip>,
+    /// Check every author's signature, failing if any is missing or invalid, or if there are no authors
+    #[arg(long)]
+    verify: bool,
+    /// Print the document, and any signature checks, as JSON instead of a summary
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Cip {
+    #[value(name = "100")]
+    Cip100,
+    #[value(name = "108")]
+    Cip108,
+    #[value(name = "119")]
+    Cip119,
+    #[value(name = "136")]
+    Cip136,
+}
+
+/// Where to load the document from
+#[derive(Debug, PartialEq)]
+enum Source {
+    Url(Url),
+    File(PathBuf),
+}
+
+impl From<&str> for Source {
+    fn from(source: &str) -> Self {
+        match Url::parse(source) {
+            Ok(url) if URL_SCHEMES.contains(&url.scheme()) => Source::Url(url),
+            _ => Source::File(PathBuf::from(source)),
+        }
+    }
+}
+
+/// A document, along with the expanded node it was parsed from, which is what its signatures are checked against
+struct Loaded<T> {
+    document: T,
+    node: Node,
+}
+
+impl<T> TryFrom<&Node> for Loaded<T>
+where
+    T: for<'a> TryFrom<&'a Node>,
+    for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+{
+    type Error = MetadataError;
+
+    fn try_from(node: &Node) -> Result<Self> {
+        Ok(Self {
+            document: T::try_from(node).map_err(Into::into)?,
+            node: node.clone(),
+        })
+    }
+}
+
+impl<T: Into<AnyDocument>> Loaded<T> {
+    fn into_any(self) -> Loaded<AnyDocument> {
+        Loaded {
+            document: self.document.into(),
+            node: self.node,
+        }
+    }
+}
+
+/// The kind of document, and the field that best identifies it
+fn headline(document: &AnyDocument) -> (&'static str, String) {
+    match document {
+        AnyDocument::Cip100(document) => (
+            "governance metadata",
+            document
+                .body
+                .comment
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+        ),
+        AnyDocument::Cip108(document) => ("governance action", document.body.title.to_string()),
+        AnyDocument::Cip119(document) => ("DRep", document.body.given_name.to_string()),
+        AnyDocument::Cip136(document) => ("vote rationale", document.body.summary.to_string()),
+    }
+}
+
+/// The outcome of checking one author's signature
+#[derive(Serialize)]
+struct Signature {
+    author: String,
+    signed: bool,
+    valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Output<'a> {
+    cip: u16,
+    document: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    signatures: Option<&'a [Signature]>,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(args).await {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Load, summarize, and optionally verify the document; returns whether every check passed
+async fn run(args: Args) -> Result<bool> {
+    let client = MetadataClient::new();
+    let source = Source::from(args.source.as_str());
+    let Loaded {
+        document: loaded,
+        node,
+    } = match args.cip {
+        Some(cip) => load_as(&client, &source, cip).await?,
+        None => load(&client, &source).await?,
+    };
+
+    let authors = loaded.base().authors;
+    let signatures = match args.verify {
+        false => None,
+        true => Some(
+            loaded
+                .verify_all(&node)?
+                .into_iter()
+                .zip(authors)
+                .map(|((author, result), signer)| Signature {
+                    author,
+                    signed: signer.witness.is_some(),
+                    valid: matches!(result, Ok(true)),
+                    error: result.err().map(|err| err.to_string()),
+                })
+                .collect::<Vec<_>>(),
+        ),
+    };
+    let valid = passed(signatures.as_deref());
+
+    if args.json {
+        // The document itself, without the name of the variant wrapped around it
+        let document = match &loaded {
+            AnyDocument::Cip100(document) => serde_json::to_value(document),
+            AnyDocument::Cip108(document) => serde_json::to_value(document),
+            AnyDocument::Cip119(document) => serde_json::to_value(document),
+            AnyDocument::Cip136(document) => serde_json::to_value(document),
+        }
+        .map_err(anyhow::Error::from)?;
+        let output = Output {
+            cip: loaded.cip(),
+            document,
+            signatures: signatures.as_deref(),
+        };
+        let json = serde_json::to_string_pretty(&output).map_err(anyhow::Error::from)?;
+        println!("{json}");
+    } else {
+        print_summary(&loaded, signatures.as_deref());
+    }
+    Ok(valid)
+}
+
+/// Load the document as the given CIP, rather than whichever it appears to follow
+async fn load_as(
+    client: &MetadataClient,
+    source: &Source,
+    cip: Cip,
+) -> Result<Loaded<AnyDocument>> {
+    Ok(match cip {
+        Cip::Cip100 => load::<Document>(client, source).await?.into_any(),
+        Cip::Cip108 => load::<GovernanceActionDocument>(client, source)
+            .await?
+            .into_any(),
+        Cip::Cip119 => load::<DRepDocument>(client, source).await?.into_any(),
+        Cip::Cip136 => load::<VoteRationaleDocument>(client, source)
+            .await?
+            .into_any(),
+    })
+}
+
+async fn load<T>(client: &MetadataClient, source: &Source) -> Result<Loaded<T>>
+where
+    T: for<'a> TryFrom<&'a Node>,
+    for<'a> <T as TryFrom<&'a Node>>::Error: Into<MetadataError>,
+{
+    match source {
+        Source::Url(url) => client.load(url.clone()).await,
+        Source::File(path) => client.load_file(path).await,
+    }
+}
+
+fn print_summary(loaded: &AnyDocument, signatures: Option<&[Signature]>) {
+    let (kind, headline) = headline(loaded);
+    let base = loaded.base();
+    println!("CIP-{} {}: {}", loaded.cip(), kind, headline);
+    println!("hash algorithm: {}", base.hash_algorithm.as_str());
+    println!("authors:");
+    if base.authors.is_empty() {
+        match signatures {
+            Some(_) => println!("  none, so there are no signatures to verify"),
+            None => println!("  none"),
+        }
+    }
+    for (index, author) in base.authors.iter().enumerate() {
+        let status = signatures
+            .and_then(|signatures| signatures.get(index))
+            .map(signature_status)
+            .unwrap_or_default();
+        println!("  {author}{status}");
+    }
+    println!("references:");
+    if base.references.is_empty() {
+        println!("  none");
+    }
+    for reference in base.references {
+        println!("  {reference}");
+    }
+}
+
+/// Whether the signature checks, if any were asked for, passed; a document with no authors has no signature
+/// to vouch for it, so it fails verification rather than passing for want of anything to check
+fn passed(signatures: Option<&[Signature]>) -> bool {
+    match signatures {
+        None => true,
+        Some(signatures) => {
+            !signatures.is_empty() && signatures.iter().all(|signature| signature.valid)
+        }
+    }
+}
+
+fn signature_status(signature: &Signature) -> String {
+    match (&signature.error, signature.signed, signature.valid) {
+        (Some(err), _, _) => format!(": {err}"),
+        (None, false, _) => ": unsigned".to_string(),
+        (None, true, true) => ": valid signature".to_string(),
+        (None, true, false) => ": INVALID signature".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source() {
+        assert_eq!(
+            Source::from("ipfs://QmW6d6zUcDG4eTJ1eXdtVeQhEdVGoQ2QB6U1MRfRaKLm5w"),
+            Source::Url(
+                Url::parse("ipfs://QmW6d6zUcDG4eTJ1eXdtVeQhEdVGoQ2QB6U1MRfRaKLm5w").unwrap()
+            )
+        );
+        assert_eq!(
+            Source::from("tests/fixtures/example.json"),
+            Source::File(PathBuf::from("tests/fixtures/example.json"))
+        );
+        assert_eq!(
+            Source::from("C:/metadata.json"),
+            Source::File(PathBuf::from("C:/metadata.json"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_load() -> Result<()> {
+        let client = MetadataClient::new();
+        for (fixture, cip) in [
+            ("example.json", 100),
+            ("cip108-example.json", 108),
+            ("cip119-example.json", 119),
+            ("cip136-example.json", 136),
+        ] {
+            let source = Source::from(format!("tests/fixtures/{fixture}").as_str());
+            let loaded: Loaded<AnyDocument> = load(&client, &source).await?;
+            assert_eq!(loaded.document.cip(), cip, "{fixture}");
+            // Signatures can be checked whichever CIP the document follows
+            loaded.document.verify_all(&loaded.node)?;
+        }
+        let example = Source::from("tests/fixtures/example.json");
+        let loaded = load_as(&client, &example, Cip::Cip100).await?;
+        assert_eq!(loaded.document.cip(), 100);
+        let verified = loaded.document.verify_all(&loaded.node)?;
+        assert!(matches!(verified[..], [(_, Ok(true))]));
+        assert!(load_as(&client, &example, Cip::Cip108).await.is_err());
+        let missing = Source::from("tests/fixtures/missing.json");
+        assert!(load::<AnyDocument>(&client, &missing).await.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_passed() {
+        let signature = |signed, valid| Signature {
+            author: "Alice".to_string(),
+            signed,
+            valid,
+            error: None,
+        };
+        assert!(passed(None));
+        assert!(passed(Some(&[signature(true, true)])));
+        assert!(!passed(Some(&[])));
+        assert!(!passed(Some(&[
+            signature(true, true),
+            signature(true, false)
+        ])));
+
+        // An author who hasn't signed fails verification, but isn't reported as a bad signature
+        let unsigned = signature(false, false);
+        assert!(!passed(Some(std::slice::from_ref(&unsigned))));
+        assert_eq!(signature_status(&unsigned), ": unsigned");
+        assert_eq!(
+            signature_status(&signature(true, false)),
+            ": INVALID signature"
+        );
+    }
+}
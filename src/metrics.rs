@@ -0,0 +1,159 @@
+use crate::cip100::Document;
+use crate::references::DocumentTree;
+
+/// How big a document is, for flagging anomalously large ones before doing anything expensive with them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DocumentMetrics {
+    /// How many authors are listed, whether or not they've signed
+    pub authors: usize,
+    /// How many references the body has
+    pub references: usize,
+    /// How many external updates the body has
+    pub external_updates: usize,
+    /// How many properties the document and its body have beyond those CIP-100 defines
+    pub extensions: usize,
+    /// The total length, in characters, of the comment in every language, the author names, and the
+    /// reference labels and update titles
+    pub text_length: usize,
+    /// How many levels of referenced documents were loaded beneath this one, for metrics of a
+    /// [DocumentTree]; None for a document on its own, whose references haven't been followed
+    pub reference_depth: Option<usize>,
+}
+
+impl Document {
+    /// Count the parts of the document; cheap, since it only tallies what's already been parsed
+    pub fn metrics(&self) -> DocumentMetrics {
+        let comment = self.body.comment.iter().flat_map(|comment| comment.iter());
+        let names = self.authors.iter().map(|author| &author.name);
+        let labels = self
+            .body
+            .references
+            .iter()
+            .map(|reference| &reference.label);
+        let titles = self
+            .body
+            .external_updates
+            .iter()
+            .map(|update| &update.title);
+        let text_length = comment
+            .chain(names)
+            .chain(labels)
+            .chain(titles)
+            .map(|text| text.value().chars().count())
+            .sum();
+        DocumentMetrics {
+            authors: self.authors.len(),
+            references: self.body.references.len(),
+            external_updates: self.body.external_updates.len(),
+            extensions: self.extensions.len() + self.body.extensions.len(),
+            text_length,
+            reference_depth: None,
+        }
+    }
+}
+
+impl DocumentTree {
+    /// The metrics of the root document, along with how deep the references that loaded go
+    pub fn metrics(&self) -> DocumentMetrics {
+        DocumentMetrics {
+            reference_depth: Some(self.depth()),
+            ..self.document.metrics()
+        }
+    }
+
+    /// How many levels of documents loaded beneath this one; 0 if none of its references loaded
+    fn depth(&self) -> usize {
+        self.references
+            .iter()
+            .filter_map(|reference| reference.document.as_ref().ok())
+            .map(|tree| tree.depth() + 1)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cip100::{Author, Reference, ReferenceType, Update};
+    use crate::error::MetadataError;
+    use crate::references::ReferencedDocument;
+    use iref::IriBuf;
+    use url::Url;
+
+    fn reference(label: &str) -> Reference {
+        crate::test_server::reference(
+            ReferenceType::GovernanceMetadata,
+            label,
+            &format!("https://example.com/{label}"),
+        )
+    }
+
+    fn tree(document: Document, references: Vec<ReferencedDocument>) -> DocumentTree {
+        DocumentTree {
+            url: Url::parse("https://example.com/root.jsonld").unwrap(),
+            document,
+            references,
+        }
+    }
+
+    #[test]
+    fn test_metrics() {
+        let document = Document::builder()
+            .comment("Hello")
+            .add_author(Author {
+                name: "Ada".into(),
+                witness: None,
+            })
+            .add_reference(reference("a"))
+            .add_reference(reference("bb"))
+            .add_external_update(Update {
+                title: "Blog".into(),
+                uri: IriBuf::new("https://example.com/blog".to_string()).unwrap(),
+                extensions: Default::default(),
+            })
+            .build();
+        assert_eq!(
+            document.metrics(),
+            DocumentMetrics {
+                authors: 1,
+                references: 2,
+                external_updates: 1,
+                extensions: 0,
+                text_length: 5 + 3 + 1 + 2 + 4,
+                reference_depth: None,
+            }
+        );
+        assert_eq!(
+            Document::builder().build().metrics(),
+            DocumentMetrics::default()
+        );
+
+        let leaf = || tree(Document::builder().build(), vec![]);
+        let nested = tree(
+            Document::builder().build(),
+            vec![ReferencedDocument {
+                reference: reference("leaf"),
+                document: Ok(leaf()),
+            }],
+        );
+        let root = tree(
+            document,
+            vec![
+                ReferencedDocument {
+                    reference: reference("a"),
+                    document: Ok(nested),
+                },
+                ReferencedDocument {
+                    reference: reference("bb"),
+                    document: Err(MetadataError::MaxDepth(4)),
+                },
+            ],
+        );
+        let metrics = root.metrics();
+        assert_eq!(metrics.reference_depth, Some(2));
+        assert_eq!(metrics.references, 2);
+        assert_eq!(leaf().metrics().reference_depth, Some(0));
+    }
+}
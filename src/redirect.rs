@@ -0,0 +1,109 @@
+//! Redirects are followed by hand, rather than by reqwest, so each hop can be checked before it's fetched
+
+use std::net::IpAddr;
+
+use reqwest::{header::HeaderMap, StatusCode};
+use url::{Host, Url};
+
+use crate::{
+    address,
+    error::{MetadataError, Result},
+};
+
+/// The headers configured on the client to send when fetching the target, while fetching the given url
+///
+/// Headers such as an API key are meant for the host they were configured for, so once a redirect leads to
+/// another origin, only the User-Agent is sent on
+pub(crate) fn headers_for(headers: &HeaderMap, url: &Url, target: &Url) -> HeaderMap {
+    if url.origin() == target.origin() {
+        return headers.clone();
+    }
+    headers
+        .iter()
+        .filter(|(name, _)| *name == reqwest::header::USER_AGENT)
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect()
+}
+
+/// Whether the response is a redirect to follow; other 3xx responses, such as 304 Not Modified, aren't
+pub(crate) fn is_redirect(status: StatusCode) -> bool {
+    matches!(status.as_u16(), 301 | 302 | 303 | 307 | 308)
+}
+
+/// The url a redirect response points at, resolved against the url that responded with it
+///
+/// Redirects may only lead to http or https urls, and, unless allow_private is set, not to loopback or
+/// private network addresses, so a malicious host can't point the client at a local file or internal service
+pub(crate) fn redirect_target(from: &Url, headers: &HeaderMap, allow_private: bool) -> Result<Url> {
+    let rejected = |to: &str, reason| MetadataError::Redirect {
+        from: from.to_string(),
+        to: to.to_string(),
+        reason,
+    };
+    let location = headers
+        .get(reqwest::header::LOCATION)
+        .ok_or_else(|| rejected("nowhere", "the response has no Location header"))?;
+    let location = String::from_utf8_lossy(location.as_bytes());
+    let to = from
+        .join(&location)
+        .map_err(|_| rejected(&location, "the Location isn't a valid url"))?;
+    if !matches!(to.scheme(), "http" | "https") {
+        return Err(rejected(
+            to.as_str(),
+            "redirects must lead to http or https",
+        ));
+    }
+    if !allow_private && to.host().is_some_and(|host| is_private_host(&host)) {
+        return Err(rejected(
+            to.as_str(),
+            "redirects must not lead to loopback or private addresses",
+        ));
+    }
+    Ok(to)
+}
+
+/// Whether the host is localhost, or an IP address on the local machine, a private network, or any other
+/// range an [crate::AddressPolicy] denies by default
+pub(crate) fn is_private_host(host: &Host<&str>) -> bool {
+    match host {
+        Host::Domain(domain) => {
+            let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+            domain == "localhost" || domain.ends_with(".localhost")
+        }
+        Host::Ipv4(ip) => address::is_denied(IpAddr::V4(*ip)),
+        Host::Ipv6(ip) => address::is_denied(IpAddr::V6(*ip)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_private_hosts() {
+        for url in [
+            "http://localhost/",
+            "http://api.localhost./",
+            "http://127.0.0.1/",
+            "http://10.1.2.3/",
+            "http://192.168.0.1/",
+            "http://169.254.169.254/",
+            "http://0.0.0.0/",
+            "http://[::1]/",
+            "http://[fd00::1]/",
+            "http://[fe80::1]/",
+            "http://[::ffff:10.0.0.1]/",
+        ] {
+            let url = Url::parse(url).unwrap();
+            assert!(is_private_host(&url.host().unwrap()), "{url}");
+        }
+        for url in [
+            "https://example.com/",
+            "http://8.8.8.8/",
+            "http://[2001:db8::1]/",
+        ] {
+            let url = Url::parse(url).unwrap();
+            assert!(!is_private_host(&url.host().unwrap()), "{url}");
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use url::Url;
+
+use crate::cip100::{Document, Reference};
+use crate::error::Result;
+
+/// How many levels of references to follow, unless configured otherwise
+#[cfg(feature = "client")]
+pub(crate) const DEFAULT_MAX_DEPTH: usize = 4;
+
+/// A governance metadata document, along with the documents its GovernanceMetadata references point at
+#[derive(Debug)]
+pub struct DocumentTree {
+    /// Where the document was loaded from
+    pub url: Url,
+    /// The document itself
+    pub document: Document,
+    /// Each GovernanceMetadata reference in the document body, in order, with the document it points at
+    pub references: Vec<ReferencedDocument>,
+}
+
+/// A GovernanceMetadata reference, and the result of loading the document it points at
+#[derive(Debug)]
+pub struct ReferencedDocument {
+    /// The reference, as it appears in the referencing document
+    pub reference: Reference,
+    /// The referenced document, or why it couldn't be loaded
+    pub document: Result<DocumentTree>,
+}
+
+impl DocumentTree {
+    /// Every document in the tree that loaded successfully, starting with this one, depth first
+    pub fn documents(&self) -> Vec<&Document> {
+        let mut documents = vec![&self.document];
+        for reference in &self.references {
+            if let Ok(tree) = &reference.document {
+                documents.extend(tree.documents());
+            }
+        }
+        documents
+    }
+}
@@ -0,0 +1,165 @@
+//! A JSON Schema for the plain JSON documents are written as by [crate::Document::to_compact_json]
+//!
+//! The types here only describe that JSON, field for field; they're never built, just handed to schemars
+
+#![allow(dead_code)]
+
+use schemars::JsonSchema;
+
+/// A CIP-100 governance metadata document, in the shape it's written before its @context is applied
+///
+/// Properties beyond these, such as those added by CIPs extending CIP-100, are allowed, keyed by their full IRI
+#[derive(JsonSchema)]
+#[cfg_attr(test, derive(serde::Deserialize))]
+#[schemars(rename = "Document")]
+struct CompactDocument {
+    /// The IRI identifying the subject of the document, if it has one
+    #[serde(rename = "@id", default)]
+    id: Option<String>,
+    /// The algorithm the document body is hashed with for signing, usually blake2b-256
+    #[serde(rename = "hashAlgorithm")]
+    hash_algorithm: String,
+    body: CompactBody,
+    /// Everyone who has signed, or is yet to sign, the document
+    authors: Vec<CompactAuthor>,
+}
+
+/// The body of the document, which is what authors sign
+#[derive(JsonSchema)]
+#[cfg_attr(test, derive(serde::Deserialize))]
+#[schemars(rename = "Body")]
+struct CompactBody {
+    references: Vec<CompactReference>,
+    /// A free-form comment, in one language or several
+    #[serde(default)]
+    comment: Option<Comment>,
+    #[serde(rename = "externalUpdates")]
+    external_updates: Vec<CompactUpdate>,
+}
+
+/// An author, and their signature once they've signed
+#[derive(JsonSchema)]
+#[cfg_attr(test, derive(serde::Deserialize))]
+#[schemars(rename = "Author")]
+struct CompactAuthor {
+    name: Text,
+    #[serde(default)]
+    witness: Option<CompactWitness>,
+}
+
+/// An author's signature of the hash of the canonicalized body
+#[derive(JsonSchema)]
+#[cfg_attr(test, derive(serde::Deserialize))]
+#[schemars(rename = "Witness")]
+struct CompactWitness {
+    /// The signature algorithm, such as ed25519
+    #[serde(rename = "witnessAlgorithm")]
+    algorithm: String,
+    /// The hex encoded public key
+    #[serde(rename = "publicKey")]
+    public_key: String,
+    /// The hex encoded signature
+    signature: String,
+}
+
+/// A reference to another document, such as a specification or the governance action being discussed
+#[derive(JsonSchema)]
+#[cfg_attr(test, derive(serde::Deserialize))]
+#[schemars(rename = "Reference")]
+struct CompactReference {
+    /// GovernanceMetadata, Other, or the IRI of a type defined elsewhere, followed by any other types
+    #[serde(rename = "@type")]
+    reference_type: Types,
+    label: Text,
+    uri: String,
+}
+
+/// Somewhere updates about the document may be found, such as a blog
+#[derive(JsonSchema)]
+#[cfg_attr(test, derive(serde::Deserialize))]
+#[schemars(rename = "Update")]
+struct CompactUpdate {
+    title: Text,
+    uri: String,
+}
+
+/// Text in the document's default language, or a value object giving its own
+#[derive(JsonSchema)]
+#[cfg_attr(test, derive(serde::Deserialize))]
+#[serde(untagged)]
+enum Text {
+    Plain(String),
+    Tagged {
+        #[serde(rename = "@value")]
+        value: String,
+        #[serde(rename = "@language", default)]
+        language: Option<String>,
+    },
+}
+
+/// A single type, or several
+#[derive(JsonSchema)]
+#[cfg_attr(test, derive(serde::Deserialize))]
+#[serde(untagged)]
+enum Types {
+    Single(String),
+    Several(Vec<String>),
+}
+
+/// A comment in one language, or a list of its translations
+#[derive(JsonSchema)]
+#[cfg_attr(test, derive(serde::Deserialize))]
+#[serde(untagged)]
+enum Comment {
+    Single(Text),
+    Translations(Vec<Text>),
+}
+
+/// The JSON Schema of the JSON written by [crate::Document::to_compact_json], for validating it in other languages
+pub fn schema() -> serde_json::Value {
+    schemars::schema_for!(CompactDocument).to_value()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::EXAMPLE;
+    use crate::{cip100::Document, LangString};
+    use anyhow::Result;
+
+    #[tokio::test]
+    async fn test_schema() -> Result<()> {
+        let schema = schema();
+        assert_eq!(schema["title"], "Document");
+        assert_eq!(
+            schema["required"],
+            serde_json::json!(["hashAlgorithm", "body", "authors"])
+        );
+        assert!(schema["$defs"]["Witness"]["properties"]["publicKey"].is_object());
+
+        // The schema types read back exactly what to_compact_json writes, in every shape text can take
+        let example: Document = crate::parse_str(
+            EXAMPLE,
+            url::Url::parse("https://example.com/metadata.jsonld")?,
+        )
+        .await?;
+        let mut translated = example.clone();
+        translated.body.comment = Some(
+            vec![
+                LangString::new("Un commentaire", Some("fr".to_string())),
+                LangString::new("Untagged", None),
+            ]
+            .into(),
+        );
+        let mut uncommented = example.clone();
+        uncommented.body.comment = None;
+        uncommented.authors[0].witness = None;
+        uncommented.body.references[0]
+            .other_types
+            .push(iref::IriBuf::new("https://example.com/vocab#Reference".to_string()).unwrap());
+        for document in [example, translated, uncommented] {
+            serde_json::from_value::<CompactDocument>(document.to_compact_json())?;
+        }
+        Ok(())
+    }
+}
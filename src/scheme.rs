@@ -0,0 +1,36 @@
+//! Handlers for url schemes the client doesn't fetch itself, registered with
+//! [crate::MetadataClient::register_scheme]
+
+use std::{collections::HashMap, sync::Arc};
+
+use url::Url;
+
+use crate::error::Result;
+
+/// Where a [SchemeHandler] found the document a url points at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolved {
+    /// An http or https url to fetch in its place, with the client's usual timeout, retries, redirect checks
+    /// and size limit; the document is still expanded with the original url as its base IRI
+    Url(Url),
+    /// The document itself, such as read from storage the handler has access to
+    Content(Vec<u8>),
+}
+
+/// Resolves a url of some scheme to something the client can load; see [Resolved]
+pub type SchemeHandler = Arc<dyn Fn(&Url) -> Result<Resolved> + Send + Sync>;
+
+/// The handlers registered on a client, keyed by lowercase scheme
+#[derive(Clone, Default)]
+pub(crate) struct SchemeHandlers(HashMap<String, SchemeHandler>);
+
+impl SchemeHandlers {
+    pub(crate) fn insert(&mut self, scheme: &str, handler: SchemeHandler) {
+        self.0.insert(scheme.to_ascii_lowercase(), handler);
+    }
+
+    /// Resolve the url with the handler for its scheme, or None if no handler is registered for it
+    pub(crate) fn resolve(&self, url: &Url) -> Option<Result<Resolved>> {
+        self.0.get(url.scheme()).map(|handler| handler(url))
+    }
+}
@@ -0,0 +1,131 @@
+//! A local HTTP server for tests, so they never depend on the network or on documents hosted elsewhere, and
+//! the fixtures tests share
+
+use std::collections::HashMap;
+
+use iref::IriBuf;
+#[cfg(feature = "client")]
+use url::Url;
+#[cfg(feature = "client")]
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockBuilder, MockServer, Request, ResponseTemplate,
+};
+
+use crate::cip100::{
+    Author, Body, Document, Reference, ReferenceType, Update, Witness, WitnessAlgorithm,
+};
+use crate::hash::HashAlgorithm;
+use crate::lang::LangString;
+
+/// The CIP-100 example.json test vector, as published alongside the CIP
+pub(crate) const EXAMPLE: &str = include_str!("../tests/fixtures/example.json");
+
+/// Text tagged with the language the test fixtures use
+pub(crate) fn en(value: &str) -> LangString {
+    LangString::new(value, Some("en-us".to_string()))
+}
+
+/// A reference with no other types or extensions
+pub(crate) fn reference(
+    reference_type: ReferenceType,
+    label: impl Into<LangString>,
+    uri: &str,
+) -> Reference {
+    Reference {
+        reference_type,
+        label: label.into(),
+        uri: IriBuf::new(uri.to_string()).unwrap(),
+        other_types: vec![],
+        extensions: Default::default(),
+    }
+}
+
+/// The document in [EXAMPLE], which is signed by its author
+pub(crate) fn example_document() -> Document {
+    Document {
+        id: None,
+        hash_algorithm: HashAlgorithm::Blake2b256,
+        authors: vec![Author {
+            name: en("Pi Lanningham"),
+            witness: Some(Witness {
+                algorithm: WitnessAlgorithm::Ed25519,
+                public_key: "7ea09a34aebb13c9841c71397b1cabfec5ddf950405293dee496cac2f437480a".to_string(),
+                signature: "340c2ef8d6abda96769844ab9dca2634ae21ef97ddbfad1f8843bea1058e40d656455a2962143adc603d063bbbe27b54b88d002d23d1dff1cd0e05017cd4f506".to_string(),
+            }),
+        }],
+        body: Body {
+            references: vec![reference(
+                ReferenceType::Other,
+                en("CIP-100"),
+                "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0100/README.md",
+            )],
+            comment: Some(en("This is a test vector for CIP-100").into()),
+            external_updates: vec![Update {
+                title: en("Blog"),
+                uri: IriBuf::new("https://314pool.com".to_string()).unwrap(),
+                extensions: Default::default(),
+            }],
+            language: Some("en-us".to_string()),
+            extensions: HashMap::new(),
+        },
+        extensions: HashMap::new(),
+    }
+}
+
+/// A mock server on localhost, serving canned responses at the paths they're mounted on
+#[cfg(feature = "client")]
+pub(crate) struct TestServer {
+    server: MockServer,
+}
+
+#[cfg(feature = "client")]
+impl TestServer {
+    pub(crate) async fn start() -> Self {
+        Self {
+            server: MockServer::start().await,
+        }
+    }
+
+    /// Serve the content with a 200 at the path, returning its url
+    pub(crate) async fn serve(&self, path: &str, content: impl AsRef<[u8]>) -> Url {
+        self.respond(
+            path,
+            ResponseTemplate::new(200).set_body_bytes(content.as_ref()),
+        )
+        .await
+    }
+
+    /// Respond to GET requests for the path with the given response, returning its url
+    pub(crate) async fn respond(&self, at: &str, response: ResponseTemplate) -> Url {
+        self.mount(Self::get(at).respond_with(response)).await;
+        self.url(at)
+    }
+
+    /// A mock of GET requests for the path, for tests that match on more than the path, or expect a number
+    /// of requests; [TestServer::mount] it once it responds
+    pub(crate) fn get(at: &str) -> MockBuilder {
+        Mock::given(method("GET")).and(path(at))
+    }
+
+    pub(crate) async fn mount(&self, mock: Mock) {
+        mock.mount(&self.server).await;
+    }
+
+    /// Check every mock got the number of requests it expected
+    pub(crate) async fn verify(&self) {
+        self.server.verify().await;
+    }
+
+    /// Every request the server has received so far
+    pub(crate) async fn received_requests(&self) -> Vec<Request> {
+        self.server.received_requests().await.unwrap_or_default()
+    }
+
+    /// The url of the path on this server, whether or not anything is served there
+    pub(crate) fn url(&self, path: &str) -> Url {
+        Url::parse(&self.server.uri())
+            .and_then(|base| base.join(path))
+            .expect("the mock server has a valid url")
+    }
+}
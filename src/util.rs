@@ -0,0 +1,351 @@
+//! Helpers for reading values out of expanded JSON-LD nodes, for implementing `TryFrom<&Node>` for your own
+//! CIP types
+//!
+//! Each takes the full IRI of the field, and names it in any error, so a document author can tell exactly
+//! which field is wrong
+
+use iref::{Iri, IriBuf, IriRef};
+use json_ld::{Node, Object};
+
+use crate::error::{MetadataError, OptionExt, Result};
+
+/// The string held by the field, or an error if it's missing or holds something else
+pub fn get_required_string(node: &Node, field: &str) -> Result<String> {
+    get_optional_string(node, field)?.missing(field)
+}
+
+/// The string held by the field, if it's present; an error if it holds something other than a string
+///
+/// A field may end up with several values once expanded, such as when two terms map to it; a string literal
+/// is chosen over any @id or other value among them, and the first string literal if there are several.
+/// Otherwise the first value is used, which is an error unless it's a string or an @id
+pub fn get_optional_string(node: &Node, field: &str) -> Result<Option<String>> {
+    get_named_string(node, field, field)
+}
+
+/// Like [get_optional_string], but calling the field by the given name in any error, for the fields of
+/// this crate's own documents, which are reported by their familiar names rather than their IRIs
+pub(crate) fn get_named_string(node: &Node, field: &str, name: &str) -> Result<Option<String>> {
+    let iri = field_iri(field)?;
+    let literal = node
+        .get(&iri)
+        .find(|value| value.as_value().and_then(|value| value.as_str()).is_some());
+    literal
+        .or_else(|| node.get_any(&iri))
+        .map(|value| value.as_str().wrong_type(name, "a string"))
+        .transpose()
+        .map(|value| value.map(str::to_string))
+}
+
+/// The IRI held by the field, or an error if it's missing, or isn't a valid IRI; see [iri_value] for the
+/// shapes it may be given in
+pub fn get_iri(node: &Node, field: &str) -> Result<IriBuf> {
+    let iri = node.get_any(&field_iri(field)?).missing(field)?;
+    let iri = iri_value(iri).wrong_type(field, "a string or an @id")?;
+    IriBuf::new(iri.to_string()).map_err(|err| MetadataError::InvalidIri {
+        field: field.to_string(),
+        iri: err.0,
+    })
+}
+
+/// The IRI an expanded value holds, whether it was written as a plain string, or as a reference to a node,
+/// such as `{ "@id": "https://..." }` or a string in a field whose context gives it `"@type": "@id"`; blank
+/// nodes, and nodes with properties of their own, aren't IRI values
+pub fn iri_value(value: &Object) -> Option<&str> {
+    match value {
+        Object::Node(node) if node.is_empty() => node.as_str(),
+        Object::Node(_) => None,
+        _ => value.as_str(),
+    }
+}
+
+/// Resolve a possibly relative IRI reference, such as `./spec.pdf` or `../index.html`, against the base IRI,
+/// as JSON-LD expansion resolves relative @ids against the document's url; absolute IRIs are returned as they
+/// are, apart from any dot segments being removed
+pub fn resolve_iri(base: &Iri, reference: &str) -> Result<IriBuf> {
+    let reference = IriRef::new(reference).map_err(|err| MetadataError::InvalidIri {
+        field: "IRI reference".to_string(),
+        iri: err.0.to_string(),
+    })?;
+    Ok(reference.resolved(base))
+}
+
+/// A canonical form of the IRI, so IRIs that differ only in how they're written compare equal, such as when
+/// deduplicating references
+///
+/// Beyond the syntax based normalization of RFC 3986 (lowercasing the scheme, uppercasing percent-encodings
+/// and decoding those of unreserved characters, and removing dot segments), http and https urls have their
+/// host lowercased and default port dropped, while other schemes keep their host as is, since an ipfs:// CID
+/// is case sensitive; any trailing slash is dropped from the path. That's lossy: a server may well
+/// serve something different at `/docs` than at `/docs/`, so the result is only for comparing, and nothing
+/// is normalized unless this is called
+pub fn normalize_iri(iri: &Iri) -> IriBuf {
+    let scheme = iri.scheme().as_str().to_ascii_lowercase();
+    let mut normalized = format!("{scheme}:");
+    if let Some(authority) = iri.authority() {
+        normalized.push_str("//");
+        if let Some(user_info) = authority.user_info() {
+            normalized.push_str(&normalize_percent_encoding(user_info.as_str()));
+            normalized.push('@');
+        }
+        let host = normalize_percent_encoding(authority.host().as_str());
+        let default_port = match scheme.as_str() {
+            "http" => Some("80"),
+            "https" => Some("443"),
+            _ => None,
+        };
+        match default_port {
+            Some(_) => normalized.push_str(&host.to_lowercase()),
+            None => normalized.push_str(&host),
+        }
+        match authority.port().map(|port| port.as_str()) {
+            Some(port) if !port.is_empty() && Some(port) != default_port => {
+                normalized.push(':');
+                normalized.push_str(port);
+            }
+            _ => {}
+        }
+    }
+    let path = remove_dot_segments(&normalize_percent_encoding(iri.path().as_str()));
+    match path.strip_suffix('/') {
+        _ if path.is_empty() && matches!(scheme.as_str(), "http" | "https") => normalized.push('/'),
+        Some(stripped) if !stripped.is_empty() => normalized.push_str(stripped),
+        _ => normalized.push_str(&path),
+    }
+    if let Some(query) = iri.query() {
+        normalized.push('?');
+        normalized.push_str(&normalize_percent_encoding(query.as_str()));
+    }
+    if let Some(fragment) = iri.fragment() {
+        normalized.push('#');
+        normalized.push_str(&normalize_percent_encoding(fragment.as_str()));
+    }
+    IriBuf::new(normalized).unwrap_or_else(|_| iri.to_owned())
+}
+
+/// Resolve the . and .. segments of a path, as in section 5.2.4 of RFC 3986
+fn remove_dot_segments(path: &str) -> String {
+    let segments: Vec<&str> = path.split('/').collect();
+    let last = segments.len() - 1;
+    let mut output = vec![];
+    for (i, segment) in segments.iter().enumerate() {
+        match *segment {
+            // The empty segment before the leading slash of an absolute path
+            "" if i == 0 => {}
+            "." | ".." => {
+                if *segment == ".." {
+                    output.pop();
+                }
+                // A path ending in a dot segment still refers to a directory
+                if i == last {
+                    output.push("");
+                }
+            }
+            segment => output.push(segment),
+        }
+    }
+    let resolved = output.join("/");
+    match path.starts_with('/') {
+        true => format!("/{resolved}"),
+        false => resolved,
+    }
+}
+
+/// Decode percent-encoded unreserved characters, which never need encoding, and uppercase the rest
+fn normalize_percent_encoding(value: &str) -> String {
+    let mut normalized = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('%') {
+        normalized.push_str(&rest[..start]);
+        let encoded = rest.get(start + 1..start + 3);
+        match encoded.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+            Some(byte) if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) => {
+                normalized.push(byte as char);
+            }
+            Some(_) => {
+                normalized.push('%');
+                normalized.push_str(&encoded.unwrap_or_default().to_ascii_uppercase());
+            }
+            None => {
+                normalized.push('%');
+                rest = &rest[start + 1..];
+                continue;
+            }
+        }
+        rest = &rest[start + 3..];
+    }
+    normalized.push_str(rest);
+    normalized
+}
+
+fn field_iri(field: &str) -> Result<&Iri> {
+    Ok(Iri::new(field)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cip100::CIP100_FIELDS;
+    use anyhow::Result;
+    use url::Url;
+
+    /// Something like a reference, read with the helpers
+    #[derive(Debug)]
+    struct Link {
+        label: String,
+        uri: IriBuf,
+        note: Option<String>,
+    }
+
+    const HREF: &str = "https://example.com/vocab#href";
+    const NOTE: &str = "https://example.com/vocab#note";
+
+    impl TryFrom<&Node> for Link {
+        type Error = MetadataError;
+
+        fn try_from(node: &Node) -> crate::Result<Self> {
+            Ok(Self {
+                label: get_required_string(node, CIP100_FIELDS.reference_label)?,
+                uri: get_iri(node, HREF)?,
+                note: get_optional_string(node, NOTE)?,
+            })
+        }
+    }
+
+    #[test]
+    fn test_resolve_iri() -> Result<()> {
+        let base = Iri::new("https://example.com/docs/proposal.jsonld")?;
+        let resolve = |reference| resolve_iri(base, reference).map(|iri| iri.to_string());
+        assert_eq!(resolve("./foo")?, "https://example.com/docs/foo");
+        assert_eq!(resolve("../bar")?, "https://example.com/bar");
+        assert_eq!(resolve("foo/../baz")?, "https://example.com/docs/baz");
+        assert_eq!(
+            resolve("#rationale")?,
+            "https://example.com/docs/proposal.jsonld#rationale"
+        );
+        assert_eq!(
+            resolve("ipfs://QmW6d6zUcDG4eTJ1eXdtVeQhEdVGoQ2QB6U1MRfRaKLm5w")?,
+            "ipfs://QmW6d6zUcDG4eTJ1eXdtVeQhEdVGoQ2QB6U1MRfRaKLm5w"
+        );
+        assert!(matches!(
+            resolve("not a reference"),
+            Err(MetadataError::InvalidIri { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_normalize_iri() {
+        let normalize = |iri: &str| normalize_iri(Iri::new(iri).unwrap()).to_string();
+        assert_eq!(
+            normalize("HTTPS://Example.COM/Docs/Spec.json"),
+            "https://example.com/Docs/Spec.json"
+        );
+        assert_eq!(
+            normalize("https://example.com/a/./b/../c/%7euser/%2fx%2F"),
+            "https://example.com/a/c/~user/%2Fx%2F"
+        );
+        assert_eq!(
+            normalize("https://example.com:443/docs/?q=%3f#Top"),
+            "https://example.com/docs?q=%3F#Top"
+        );
+        assert_eq!(normalize("http://example.com"), "http://example.com/");
+        assert_eq!(
+            normalize("http://example.com:8080/"),
+            "http://example.com:8080/"
+        );
+        assert_eq!(
+            normalize("IPFS://QmW6d6zUcDG4eTJ1eXdtVeQhEdVGoQ2QB6U1MRfRaKLm5w"),
+            "ipfs://QmW6d6zUcDG4eTJ1eXdtVeQhEdVGoQ2QB6U1MRfRaKLm5w"
+        );
+        assert_eq!(normalize("urn:isbn:0451450523"), "urn:isbn:0451450523");
+    }
+
+    #[tokio::test]
+    async fn test_helpers() -> Result<()> {
+        let base = Url::parse("https://example.com/metadata.jsonld")?;
+        let document = |fields: &str| {
+            format!(
+                r#"{{
+                    "@context": {{
+                        "CIP100": "https://github.com/cardano-foundation/CIPs/blob/master/CIP-0100/README.md#",
+                        "label": "CIP100:reference-label",
+                        "uri": "{HREF}",
+                        "note": "{NOTE}"
+                    }},
+                    {fields}
+                }}"#
+            )
+        };
+
+        let link: Link = crate::parse_str(
+            &document(r#""label": "Spec", "uri": "https://example.com/spec""#),
+            base.clone(),
+        )
+        .await?;
+        assert_eq!(link.label, "Spec");
+        assert_eq!(link.uri.as_str(), "https://example.com/spec");
+        assert_eq!(link.note, None);
+
+        let link: Link = crate::parse_str(
+            &document(r#""label": "Spec", "uri": { "@id": "https://example.com/spec" }"#),
+            base.clone(),
+        )
+        .await?;
+        assert_eq!(link.uri.as_str(), "https://example.com/spec");
+
+        // Given both, the label is the string rather than the @id, whichever comes first
+        for label in [
+            r#"[{ "@id": "https://example.com/label" }, "Spec"]"#,
+            r#"["Spec", { "@id": "https://example.com/label" }]"#,
+            r#"[42, { "@id": "https://example.com/label" }, "Spec", "Other"]"#,
+        ] {
+            let link: Link = crate::parse_str(
+                &document(&format!(
+                    r#""label": {label}, "uri": "https://example.com/spec""#
+                )),
+                base.clone(),
+            )
+            .await?;
+            assert_eq!(link.label, "Spec", "{label}");
+        }
+
+        let err =
+            crate::parse_str::<Link>(&document(r#""uri": "https://example.com""#), base.clone())
+                .await
+                .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("no {} field", CIP100_FIELDS.reference_label)
+        );
+
+        let err = crate::parse_str::<Link>(
+            &document(r#""label": "Spec", "uri": "not an iri""#),
+            base.clone(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("{HREF} is not a valid IRI: not an iri")
+        );
+
+        let err = crate::parse_str::<Link>(
+            &document(r#""label": "Spec", "uri": "https://example.com", "note": 42"#),
+            base,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.to_string(), format!("{NOTE} is not a string"));
+
+        // A typo in the field itself is reported as such, rather than blamed on the document
+        let node = Node::new();
+        assert_eq!(
+            get_optional_string(&node, "not a field")
+                .unwrap_err()
+                .to_string(),
+            "not a field is not a valid field IRI"
+        );
+        Ok(())
+    }
+}
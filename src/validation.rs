@@ -0,0 +1,126 @@
+use std::collections::HashSet;
+
+use thiserror::Error;
+
+use crate::cip100::Document;
+
+/// A problem with a document that parsed successfully, but doesn't make sense, such as one nobody has signed
+///
+/// [Document::validate] checks that:
+/// - the document has at least one author
+/// - every author has signed, i.e. has a witness
+/// - the body has a comment, and it isn't empty, or only whitespace
+/// - no two references share a label
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ValidationIssue {
+    /// The document has no authors, so nobody attests to it
+    #[error("document has no authors")]
+    NoAuthors,
+    /// The named author is listed, but hasn't signed the document
+    #[error("author {0} has not signed the document")]
+    UnsignedAuthor(String),
+    /// The body has no comment, which CIP-100 requires, though some CIPs extending it don't
+    #[error("body has no comment")]
+    NoComment,
+    /// The body comment is empty, or only whitespace
+    #[error("body comment is empty")]
+    EmptyComment,
+    /// Several references share the given label, so readers can't tell them apart
+    #[error("more than one reference is labelled {0}")]
+    DuplicateReferenceLabel(String),
+}
+
+impl Document {
+    /// Check the document against every rule listed on [ValidationIssue], returning all the issues found
+    /// rather than just the first, so they can be reported together
+    pub fn validate(&self) -> Result<(), Vec<ValidationIssue>> {
+        let mut issues = vec![];
+        if self.authors.is_empty() {
+            issues.push(ValidationIssue::NoAuthors);
+        }
+        for author in &self.authors {
+            if author.witness.is_none() {
+                issues.push(ValidationIssue::UnsignedAuthor(author.name.to_string()));
+            }
+        }
+        match &self.body.comment {
+            None => issues.push(ValidationIssue::NoComment),
+            Some(comment) if comment.iter().all(|text| text.value().trim().is_empty()) => {
+                issues.push(ValidationIssue::EmptyComment)
+            }
+            Some(_) => {}
+        }
+        let mut labels = HashSet::new();
+        let mut duplicates = HashSet::new();
+        for reference in &self.body.references {
+            let label = reference.label.value();
+            if !labels.insert(label) && duplicates.insert(label) {
+                issues.push(ValidationIssue::DuplicateReferenceLabel(label.to_string()));
+            }
+        }
+        match issues.is_empty() {
+            true => Ok(()),
+            false => Err(issues),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cip100::{Reference, ReferenceType};
+    use crate::test_server::EXAMPLE;
+    use anyhow::Result;
+
+    fn reference(label: &str, uri: &str) -> Reference {
+        crate::test_server::reference(ReferenceType::Other, label, uri)
+    }
+
+    #[tokio::test]
+    async fn test_validate() -> Result<()> {
+        let example: Document = crate::parse_str(
+            EXAMPLE,
+            url::Url::parse("https://example.com/metadata.jsonld")?,
+        )
+        .await?;
+        assert_eq!(example.validate(), Ok(()));
+
+        let anonymous = Document::builder()
+            .comment("A fine document")
+            .add_reference(reference("CIP-100", "https://example.com/cip-100"))
+            .build();
+        assert_eq!(anonymous.validate(), Err(vec![ValidationIssue::NoAuthors]));
+
+        let mut uncommented = example.clone();
+        uncommented.body.comment = None;
+        assert_eq!(
+            uncommented.validate(),
+            Err(vec![ValidationIssue::NoComment])
+        );
+
+        let mut draft = Document::builder()
+            .comment("  ")
+            .add_reference(reference("Spec", "https://example.com/a"))
+            .add_reference(reference("Spec", "https://example.com/b"))
+            .add_reference(reference("Spec", "https://example.com/c"))
+            .add_reference(reference("Blog", "https://example.com/d"))
+            .build();
+        draft.authors.push(crate::cip100::Author {
+            name: "Alice".into(),
+            witness: None,
+        });
+        assert_eq!(
+            draft.validate(),
+            Err(vec![
+                ValidationIssue::UnsignedAuthor("Alice".to_string()),
+                ValidationIssue::EmptyComment,
+                ValidationIssue::DuplicateReferenceLabel("Spec".to_string()),
+            ])
+        );
+        assert_eq!(
+            ValidationIssue::DuplicateReferenceLabel("Spec".to_string()).to_string(),
+            "more than one reference is labelled Spec"
+        );
+        Ok(())
+    }
+}
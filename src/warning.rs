@@ -0,0 +1,116 @@
+use iref::{Iri, IriBuf};
+use json_ld::{ExpandedDocument, Node};
+use thiserror::Error;
+
+use crate::cip100::{parse_references, Document, Reference, ReferenceType, CIP100_FIELDS};
+use crate::cip108::CIP108_FIELDS;
+use crate::cip119::CIP119_FIELDS;
+use crate::cip136::CIP136_FIELDS;
+use crate::hash::HashAlgorithm;
+
+/// Something odd about a document that still loaded, worth flagging for review but not worth rejecting it over
+///
+/// Unlike a [crate::ValidationIssue], which is a rule about what a sensible document looks like, each of these
+/// is a part of the document this library had to guess at or skip over while parsing it
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum Warning {
+    /// The hashAlgorithm isn't one this library recognizes, so the document can't be hashed to check signatures
+    #[error("hash algorithm {0} is not one this library recognizes")]
+    UnknownHashAlgorithm(String),
+    /// The reference with the given label has a type this library doesn't recognize, such as one added by a later CIP
+    #[error("reference {label} has unrecognized type {reference_type}")]
+    UnknownReferenceType {
+        label: String,
+        reference_type: IriBuf,
+    },
+    /// The document leaves out the named field, which it may, but usually shouldn't
+    #[error("document has no {0}")]
+    MissingField(&'static str),
+}
+
+/// Collect the warnings for every node of an expanded document, for documents that already parsed
+pub(crate) fn collect(expanded: &ExpandedDocument) -> Vec<Warning> {
+    expanded
+        .objects()
+        .iter()
+        .filter_map(|object| object.as_node())
+        .flat_map(node_warnings)
+        .collect()
+}
+
+fn node_warnings(node: &Node) -> Vec<Warning> {
+    let mut warnings = vec![];
+    let field = |iri: &str| node.get_any(&Iri::new(iri).expect("CIP fields are valid IRIs"));
+    if let Some(algorithm) = field(CIP100_FIELDS.hash_algorithm).and_then(|a| a.as_str()) {
+        if !HashAlgorithm::from(algorithm).is_recognized() {
+            warnings.push(Warning::UnknownHashAlgorithm(algorithm.to_string()));
+        }
+    }
+    if !Document::has_authors_field(node) {
+        warnings.push(Warning::MissingField("authors"));
+    }
+    for (body, references) in [
+        (CIP100_FIELDS.body, CIP100_FIELDS.body_references),
+        (CIP108_FIELDS.body, CIP108_FIELDS.body_references),
+        (CIP119_FIELDS.body, CIP119_FIELDS.body_references),
+        (CIP136_FIELDS.body, CIP136_FIELDS.body_references),
+    ] {
+        let Some(body) = field(body).and_then(|body| body.as_node()) else {
+            continue;
+        };
+        // The body of another CIP may not parse as references at all; it's only the document's own that matter
+        let references: Vec<Reference> = parse_references(body, references).unwrap_or_default();
+        for reference in references {
+            if let ReferenceType::Unknown(reference_type) = reference.reference_type {
+                warnings.push(Warning::UnknownReferenceType {
+                    label: reference.label.value().to_string(),
+                    reference_type,
+                });
+            }
+        }
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::EXAMPLE;
+    use crate::{cip100::Document, parse_str_with_warnings};
+    use anyhow::Result;
+    use url::Url;
+
+    #[tokio::test]
+    async fn test_warnings() -> Result<()> {
+        let base = Url::parse("https://example.com/metadata.jsonld")?;
+        let (_, warnings) = parse_str_with_warnings::<Document>(EXAMPLE, base.clone()).await?;
+        assert_eq!(warnings, vec![]);
+
+        // Authors with a term the context doesn't define are dropped during expansion
+        let odd = EXAMPLE
+            .replace("\"blake2b-256\"", "\"blake2b256\"")
+            .replace(
+                "\"@type\": \"Other\"",
+                "\"@type\": \"https://example.com/Spec\"",
+            )
+            .replace("\"authors\": [", "\"signers\": [");
+        let (document, warnings) = parse_str_with_warnings::<Document>(&odd, base).await?;
+        assert!(document.authors.is_empty());
+        assert_eq!(
+            warnings,
+            vec![
+                Warning::UnknownHashAlgorithm("blake2b256".to_string()),
+                Warning::MissingField("authors"),
+                Warning::UnknownReferenceType {
+                    label: "CIP-100".to_string(),
+                    reference_type: IriBuf::new("https://example.com/Spec".to_string()).unwrap(),
+                },
+            ]
+        );
+        assert_eq!(
+            Warning::MissingField("authors").to_string(),
+            "document has no authors"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+//! The whole signing pipeline, from the bytes of a published document to a verified witness, with no network
+
+use anyhow::Result;
+use cardano_governance_metadata::{parse_str, Document};
+use url::Url;
+
+const EXAMPLE: &str = include_str!("fixtures/example.json");
+
+/// The blake2b-256 hash of the canonicalized body of the CIP-100 example.json test vector, which its author signed
+const EXAMPLE_HASH: &str = "cc4ab8ead604ddb498ed4b2916af7b454c65ac783b5d836fddf388e72a40eccb";
+
+async fn parse(content: &str) -> Result<Document> {
+    let base = Url::parse("https://example.com/metadata.jsonld")?;
+    Ok(parse_str(content, base).await?)
+}
+
+#[tokio::test]
+async fn test_verify_offline() -> Result<()> {
+    let document = parse(EXAMPLE).await?;
+    let hash = document.canonical_hash()?;
+    assert_eq!(hex::encode(hash), EXAMPLE_HASH);
+    assert_eq!(document.authors.len(), 1);
+    assert!(document.authors[0].verify(&hash)?);
+
+    let results = document.verify_all()?;
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].0, "Pi Lanningham");
+    assert!(matches!(results[0].1, Ok(true)));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_verify_offline_detects_edits() -> Result<()> {
+    let edited = EXAMPLE.replace("This is a test vector", "This is an edited test vector");
+    let document = parse(&edited).await?;
+    assert_ne!(hex::encode(document.canonical_hash()?), EXAMPLE_HASH);
+    assert!(matches!(document.verify_all()?[0].1, Ok(false)));
+
+    // Formatting isn't signed, only the canonicalized body, so reformatting keeps the signature valid
+    let reformatted: serde_json::Value = serde_json::from_str(EXAMPLE)?;
+    let document = parse(&serde_json::to_string(&reformatted)?).await?;
+    assert_eq!(hex::encode(document.canonical_hash()?), EXAMPLE_HASH);
+    assert!(matches!(document.verify_all()?[0].1, Ok(true)));
+    Ok(())
+}